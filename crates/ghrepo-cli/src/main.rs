@@ -1,5 +1,12 @@
-use ghrepo::{LocalRepo, LocalRepoError};
-use lexopt::{Arg, Parser};
+use ghrepo::{
+    BranchUpstream, CloneOptions, ForkInfo, GH_REPO_ENV, GHRepo, LocalRepo, LocalRepoError,
+    Permalink, Remote, RemoteUrlChange, ScannedRepo, Scanner, StatusSummary, Submodule, UrlStyle,
+};
+use lexopt::{Arg, Parser, ValueExt};
+use serde::Serialize;
+use std::env;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use std::process::exit;
 
@@ -7,25 +14,116 @@ use std::process::exit;
 enum Command {
     Run {
         json: bool,
-        remote: String,
+        push: bool,
+        remote: Option<RemoteChoice>,
+        dirpath: Option<PathBuf>,
+    },
+    Submodules {
+        json: bool,
+        dirpath: Option<PathBuf>,
+    },
+    Link {
+        remote: Option<String>,
+        file: PathBuf,
+        lines: Option<RangeInclusive<usize>>,
+    },
+    Status {
+        json: bool,
+        prompt: bool,
+        dirpath: Option<PathBuf>,
+    },
+    Fork {
+        json: bool,
+        parent: Option<String>,
+        fork: Option<String>,
+        dirpath: Option<PathBuf>,
+    },
+    SetUrl {
+        style: UrlStyle,
+        dry_run: bool,
+        remote: Option<String>,
+    },
+    RemoteAdd {
+        name: String,
+        repo: GHRepo,
+        style: UrlStyle,
+        pulls: bool,
+    },
+    Clone {
+        repo: GHRepo,
+        dir: Option<PathBuf>,
+        style: Option<UrlStyle>,
+        depth: Option<u32>,
+        branch: Option<String>,
+        upstream: Option<GHRepo>,
+    },
+    Scan {
+        json: bool,
+        max_depth: Option<usize>,
+        skip: Vec<String>,
+        jobs: Option<NonZeroUsize>,
         dirpath: Option<PathBuf>,
     },
     Help,
+    SubmodulesHelp,
+    LinkHelp,
+    StatusHelp,
+    ForkHelp,
+    SetUrlHelp,
+    RemoteHelp,
+    RemoteAddHelp,
+    CloneHelp,
+    ScanHelp,
     Version,
 }
 
 impl Command {
     fn from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
         let mut json = false;
-        let mut remote = String::from("origin");
+        let mut push = false;
+        let mut remote: Option<RemoteChoice> = None;
         let mut dirpath: Option<PathBuf> = None;
+        let mut first = true;
         while let Some(arg) = parser.next()? {
             match arg {
+                Arg::Value(val) if first && val == "submodules" => {
+                    return Command::submodules_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "link" => {
+                    return Command::link_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "status" => {
+                    return Command::status_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "fork" => {
+                    return Command::fork_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "set-url" => {
+                    return Command::set_url_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "remote" => {
+                    return Command::remote_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "clone" => {
+                    return Command::clone_from_parser(parser);
+                }
+                Arg::Value(val) if first && val == "scan" => {
+                    return Command::scan_from_parser(parser);
+                }
                 Arg::Short('J') | Arg::Long("json") => {
                     json = true;
                 }
+                Arg::Short('p') | Arg::Long("push") => {
+                    push = true;
+                }
                 Arg::Short('r') | Arg::Long("remote") => {
-                    remote = parser.value()?.into_string()?;
+                    remote = Some(RemoteChoice::Named(parser.value()?.into_string()?));
+                }
+                Arg::Short('a') | Arg::Long("auto") => {
+                    remote = Some(RemoteChoice::Auto);
+                }
+                Arg::Long("all") => {
+                    remote = Some(RemoteChoice::All);
                 }
                 Arg::Short('h') | Arg::Long("help") => return Ok(Command::Help),
                 Arg::Short('V') | Arg::Long("version") => return Ok(Command::Version),
@@ -34,10 +132,292 @@ impl Command {
                 }
                 _ => return Err(arg.unexpected()),
             }
+            first = false;
         }
         Ok(Command::Run {
             json,
+            push,
+            remote,
+            dirpath,
+        })
+    }
+
+    fn submodules_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut json = false;
+        let mut dirpath: Option<PathBuf> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('J') | Arg::Long("json") => {
+                    json = true;
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::SubmodulesHelp),
+                Arg::Value(val) if dirpath.is_none() => {
+                    dirpath = Some(val.into());
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        Ok(Command::Submodules { json, dirpath })
+    }
+
+    fn link_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut remote: Option<String> = None;
+        let mut spec: Option<String> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('r') | Arg::Long("remote") => {
+                    remote = Some(parser.value()?.into_string()?);
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::LinkHelp),
+                Arg::Value(val) if spec.is_none() => {
+                    spec = Some(val.into_string()?);
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        let spec = spec.ok_or("missing FILE argument")?;
+        let (file, lines) = parse_link_spec(&spec);
+        Ok(Command::Link {
+            remote,
+            file: file.into(),
+            lines,
+        })
+    }
+
+    fn status_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut json = false;
+        let mut prompt = false;
+        let mut dirpath: Option<PathBuf> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('J') | Arg::Long("json") => {
+                    json = true;
+                }
+                Arg::Long("prompt") => {
+                    prompt = true;
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::StatusHelp),
+                Arg::Value(val) if dirpath.is_none() => {
+                    dirpath = Some(val.into());
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        Ok(Command::Status {
+            json,
+            prompt,
+            dirpath,
+        })
+    }
+
+    fn fork_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut json = false;
+        let mut parent: Option<String> = None;
+        let mut fork: Option<String> = None;
+        let mut dirpath: Option<PathBuf> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('J') | Arg::Long("json") => {
+                    json = true;
+                }
+                Arg::Long("parent") => {
+                    parent = Some(parser.value()?.into_string()?);
+                }
+                Arg::Long("fork") => {
+                    fork = Some(parser.value()?.into_string()?);
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::ForkHelp),
+                Arg::Value(val) if dirpath.is_none() => {
+                    dirpath = Some(val.into());
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        Ok(Command::Fork {
+            json,
+            parent,
+            fork,
+            dirpath,
+        })
+    }
+
+    fn set_url_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut style: Option<UrlStyle> = None;
+        let mut host: Option<String> = None;
+        let mut dry_run = false;
+        let mut remote: Option<String> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Long("https") => {
+                    style = Some(UrlStyle::Https);
+                }
+                Arg::Long("ssh") => {
+                    style = Some(UrlStyle::Ssh);
+                }
+                Arg::Long("ssh-443") => {
+                    style = Some(UrlStyle::Ssh443);
+                }
+                Arg::Long("host") => {
+                    host = Some(parser.value()?.into_string()?);
+                }
+                Arg::Short('n') | Arg::Long("dry-run") => {
+                    dry_run = true;
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::SetUrlHelp),
+                Arg::Value(val) if remote.is_none() => {
+                    remote = Some(val.into_string()?);
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        let style = style.ok_or("one of --https, --ssh, or --ssh-443 is required")?;
+        Ok(Command::SetUrl {
+            style: with_host(style, host)?,
+            dry_run,
             remote,
+        })
+    }
+
+    fn remote_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        match parser.next()? {
+            Some(Arg::Value(val)) if val == "add" => Command::remote_add_from_parser(parser),
+            Some(Arg::Short('h') | Arg::Long("help")) => Ok(Command::RemoteHelp),
+            Some(arg) => Err(arg.unexpected()),
+            None => Err("missing remote subcommand".into()),
+        }
+    }
+
+    fn remote_add_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut style: Option<UrlStyle> = None;
+        let mut host: Option<String> = None;
+        let mut pulls = false;
+        let mut name: Option<String> = None;
+        let mut repo: Option<GHRepo> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Long("https") => {
+                    style = Some(UrlStyle::Https);
+                }
+                Arg::Long("ssh") => {
+                    style = Some(UrlStyle::Ssh);
+                }
+                Arg::Long("ssh-443") => {
+                    style = Some(UrlStyle::Ssh443);
+                }
+                Arg::Long("host") => {
+                    host = Some(parser.value()?.into_string()?);
+                }
+                Arg::Long("pulls") => {
+                    pulls = true;
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::RemoteAddHelp),
+                Arg::Value(val) if name.is_none() => {
+                    name = Some(val.into_string()?);
+                }
+                Arg::Value(val) if repo.is_none() => {
+                    repo = Some(parse_repo_spec(val.into_string()?)?);
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        let name = name.ok_or("missing NAME argument")?;
+        let repo = repo.ok_or("missing REPO argument")?;
+        Ok(Command::RemoteAdd {
+            name,
+            repo,
+            style: with_host(style.unwrap_or(UrlStyle::Https), host)?,
+            pulls,
+        })
+    }
+
+    fn clone_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut style: Option<UrlStyle> = None;
+        let mut host: Option<String> = None;
+        let mut depth: Option<u32> = None;
+        let mut branch: Option<String> = None;
+        let mut upstream: Option<GHRepo> = None;
+        let mut repo: Option<GHRepo> = None;
+        let mut dir: Option<PathBuf> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Long("https") => {
+                    style = Some(UrlStyle::Https);
+                }
+                Arg::Long("ssh") => {
+                    style = Some(UrlStyle::Ssh);
+                }
+                Arg::Long("ssh-443") => {
+                    style = Some(UrlStyle::Ssh443);
+                }
+                Arg::Long("host") => {
+                    host = Some(parser.value()?.into_string()?);
+                }
+                Arg::Long("depth") => {
+                    depth = Some(parser.value()?.parse()?);
+                }
+                Arg::Short('b') | Arg::Long("branch") => {
+                    branch = Some(parser.value()?.into_string()?);
+                }
+                Arg::Long("upstream") => {
+                    upstream = Some(parse_repo_spec(parser.value()?.into_string()?)?);
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::CloneHelp),
+                Arg::Value(val) if repo.is_none() => {
+                    repo = Some(parse_repo_spec(val.into_string()?)?);
+                }
+                Arg::Value(val) if dir.is_none() => {
+                    dir = Some(val.into());
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        let repo = repo.ok_or("missing REPO argument")?;
+        let style = match (style, host) {
+            (None, None) => None,
+            (style, host) => Some(with_host(style.unwrap_or(UrlStyle::Https), host)?),
+        };
+        Ok(Command::Clone {
+            repo,
+            dir,
+            style,
+            depth,
+            branch,
+            upstream,
+        })
+    }
+
+    fn scan_from_parser(mut parser: Parser) -> Result<Command, lexopt::Error> {
+        let mut json = false;
+        let mut max_depth: Option<usize> = None;
+        let mut skip: Vec<String> = Vec::new();
+        let mut jobs: Option<NonZeroUsize> = None;
+        let mut dirpath: Option<PathBuf> = None;
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Arg::Short('J') | Arg::Long("json") => {
+                    json = true;
+                }
+                Arg::Long("max-depth") => {
+                    max_depth = Some(parser.value()?.parse()?);
+                }
+                Arg::Long("skip") => {
+                    skip.push(parser.value()?.into_string()?);
+                }
+                Arg::Short('j') | Arg::Long("jobs") => {
+                    jobs = Some(parser.value()?.parse()?);
+                }
+                Arg::Short('h') | Arg::Long("help") => return Ok(Command::ScanHelp),
+                Arg::Value(val) if dirpath.is_none() => {
+                    dirpath = Some(val.into());
+                }
+                _ => return Err(arg.unexpected()),
+            }
+        }
+        Ok(Command::Scan {
+            json,
+            max_depth,
+            skip,
+            jobs,
             dirpath,
         })
     }
@@ -49,13 +429,55 @@ impl Command {
                     "Usage: {} [<options>] [<repo-path>]",
                     env!("CARGO_BIN_NAME")
                 );
+                println!(
+                    "       {} submodules [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} link [<options>] <file>[:<line>[-<line>]]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} status [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} fork [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} set-url --https|--ssh|--ssh-443 [<options>] [<remote>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} remote add [<options>] <name> <owner>/<repo>",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} clone [<options>] <owner>/<repo> [<directory>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!(
+                    "       {} scan [<options>] [<directory>]",
+                    env!("CARGO_BIN_NAME")
+                );
                 println!();
                 println!("Show current GitHub repository");
                 println!();
+                println!(
+                    "If the {GH_REPO_ENV} environment variable is set and neither a path nor a remote"
+                );
+                println!("is given, the repository that it specifies is shown instead.");
+                println!();
                 println!("Visit <https://github.com/jwodder/ghrepo-rust> for more information.");
                 println!();
                 println!("Options:");
+                println!(
+                    "  -a, --auto        Select the remote the same way as the GitHub CLI does"
+                );
+                println!("  --all             List all remotes and their GitHub repositories");
                 println!("  -J, --json        Output JSON");
+                println!("  -p, --push        Show the repository that `git push` would push to");
                 println!("  -r REMOTE, --remote REMOTE");
                 println!(
                     "                    Parse the GitHub URL from the given remote [default: origin]"
@@ -63,65 +485,911 @@ impl Command {
                 println!("  -h, --help        Display this help message and exit");
                 println!("  -V, --version     Show the program version and exit");
             }
+            Command::SubmodulesHelp => {
+                println!(
+                    "Usage: {} submodules [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("List the submodules of a repository and their GitHub repositories");
+                println!();
+                println!("Options:");
+                println!("  -J, --json        Output JSON");
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::LinkHelp => {
+                println!(
+                    "Usage: {} link [<options>] <file>[:<line>[-<line>]]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("Show a GitHub permalink to a file at the current commit");
+                println!();
+                println!("Options:");
+                println!("  -r REMOTE, --remote REMOTE");
+                println!(
+                    "                    Link to the GitHub repository of the given remote [default:"
+                );
+                println!("                    the current branch's remote, or origin]");
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::StatusHelp => {
+                println!(
+                    "Usage: {} status [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!(
+                    "Summarize the current branch and working tree in a single line of the form"
+                );
+                println!(
+                    "BRANCH[↑AHEAD][↓BEHIND] [+STAGED] [~UNSTAGED] [!CONFLICTED] [?UNTRACKED]"
+                );
+                println!();
+                println!("Options:");
+                println!("  -J, --json        Output JSON");
+                println!(
+                    "  --prompt          Print nothing instead of failing, for use in shell prompts"
+                );
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::ForkHelp => {
+                println!(
+                    "Usage: {} fork [<options>] [<repo-path>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("Show whether one remote's GitHub repository is a fork of another's");
+                println!();
+                println!("Options:");
+                println!("  -J, --json        Output JSON");
+                println!(
+                    "  --parent REMOTE   The remote for the parent repository [default: upstream]"
+                );
+                println!("  --fork REMOTE     The remote for the fork [default: origin]");
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::SetUrlHelp => {
+                println!(
+                    "Usage: {} set-url --https|--ssh|--ssh-443 [<options>] [<remote>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("Rewrite the URL of a remote [default: origin] in a different style");
+                println!();
+                println!("Options:");
+                println!("  --https           Use an HTTPS URL");
+                println!("  --ssh             Use an SSH URL");
+                println!("  --ssh-443         Use an SSH URL for connecting over port 443");
+                println!(
+                    "  --host HOST       Use the given GitHub Enterprise Server host instead of"
+                );
+                println!("                    github.com");
+                println!("  -n, --dry-run     Show the new URL without changing the remote");
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::RemoteHelp => {
+                println!(
+                    "Usage: {} remote add [<options>] <name> <owner>/<repo>",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("Manage remotes pointing to GitHub repositories");
+                println!();
+                println!("Subcommands:");
+                println!("  add               Add a remote for a GitHub repository");
+            }
+            Command::RemoteAddHelp => {
+                println!(
+                    "Usage: {} remote add [<options>] <name> <owner>/<repo>",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!("Add a remote for a GitHub repository.  If a remote with the given name");
+                println!("already exists and points to the same repository, it is left as-is.");
+                println!();
+                println!("Options:");
+                println!("  --https           Use an HTTPS URL [default]");
+                println!("  --ssh             Use an SSH URL");
+                println!("  --ssh-443         Use an SSH URL for connecting over port 443");
+                println!(
+                    "  --host HOST       Use the given GitHub Enterprise Server host instead of"
+                );
+                println!("                    github.com");
+                println!("  --pulls           Also fetch pull request heads as <name>/pr/<number>");
+                println!("  -h, --help        Display this help message and exit");
+            }
+            Command::CloneHelp => {
+                println!(
+                    "Usage: {} clone [<options>] <owner>/<repo> [<directory>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!(
+                    "Clone a GitHub repository into a directory [default: the repository name]"
+                );
+                println!();
+                println!("Options:");
+                println!("  --https           Use an HTTPS URL");
+                println!("  --ssh             Use an SSH URL");
+                println!("  --ssh-443         Use an SSH URL for connecting over port 443");
+                println!(
+                    "  --host HOST       Use the given GitHub Enterprise Server host instead of"
+                );
+                println!("                    github.com");
+                println!(
+                    "  --depth N         Create a shallow clone with the last N commits of history"
+                );
+                println!("  -b BRANCH, --branch BRANCH");
+                println!("                    Check out BRANCH instead of the default branch");
+                println!(
+                    "  --upstream REPO   Add an `upstream` remote for REPO, the parent of a fork"
+                );
+                println!("  -h, --help        Display this help message and exit");
+                println!();
+                println!(
+                    "If no URL style is given, the `ghrepo.protocol` Git config setting (https,"
+                );
+                println!("ssh, or ssh-443) is used, defaulting to HTTPS.");
+            }
+            Command::ScanHelp => {
+                println!(
+                    "Usage: {} scan [<options>] [<directory>]",
+                    env!("CARGO_BIN_NAME")
+                );
+                println!();
+                println!(
+                    "Find the Git repositories in a directory tree [default: the current directory]"
+                );
+                println!("and show the current branch & GitHub remotes of each one");
+                println!();
+                println!("Options:");
+                println!("  -J, --json        Output a line of JSON for each repository");
+                println!(
+                    "  --max-depth N     Only look for repositories at most N levels below the"
+                );
+                println!("                    directory");
+                println!("  --skip NAME       Also skip directories named NAME; node_modules and");
+                println!("                    target are always skipped");
+                println!("  -j N, --jobs N    Inspect N repositories at once [default: number of");
+                println!("                    CPUs]");
+                println!("  -h, --help        Display this help message and exit");
+            }
             Command::Version => {
                 println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
             }
             Command::Run {
                 json,
+                push,
+                remote,
+                dirpath,
+            } => {
+                let override_repo = if dirpath.is_none()
+                    && !push
+                    && matches!(remote, None | Some(RemoteChoice::Auto))
+                {
+                    match GHRepo::from_gh_repo_env() {
+                        Ok(r) => r,
+                        Err(e) => {
+                            eprintln!("ghrepo: invalid {GH_REPO_ENV} value: {e}");
+                            exit(1);
+                        }
+                    }
+                } else {
+                    None
+                };
+                match run(override_repo, dirpath, json, push, remote) {
+                    Ok(s) => println!("{s}"),
+                    Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                    Err(LocalRepoError::NoSuchRemote(_)) => exit(2),
+                    Err(e) => {
+                        eprintln!("ghrepo: {e}");
+                        exit(1);
+                    }
+                }
+            }
+            Command::Submodules { json, dirpath } => match show_submodules(dirpath, json) {
+                Ok(s) if s.is_empty() => (),
+                Ok(s) => println!("{s}"),
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                Err(e) => {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            },
+            Command::Link {
                 remote,
+                file,
+                lines,
+            } => match link(remote, file, lines) {
+                Ok(link) => {
+                    for warning in &link.warnings {
+                        eprintln!("ghrepo: warning: {warning}");
+                    }
+                    println!("{}", link.url);
+                }
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                Err(LocalRepoError::NoSuchRemote(_)) => exit(2),
+                Err(e) => {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            },
+            Command::Fork {
+                json,
+                parent,
+                fork,
                 dirpath,
-            } => match run(dirpath, json, remote) {
+            } => match show_fork(dirpath, parent, fork, json) {
                 Ok(s) => println!("{s}"),
-                Err(LocalRepoError::CommandFailed(rc)) => exit(rc.code().unwrap_or(1)),
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                Err(LocalRepoError::NoSuchRemote(_)) => exit(2),
+                Err(e) => {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            },
+            Command::SetUrl {
+                style,
+                dry_run,
+                remote,
+            } => match set_url(remote, &style, dry_run) {
+                Ok(change) if change.is_changed() => {
+                    println!(
+                        "{}: {} -> {}",
+                        change.remote, change.old_url, change.new_url
+                    );
+                }
+                Ok(change) => println!("{}: {} (unchanged)", change.remote, change.old_url),
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
                 Err(LocalRepoError::NoSuchRemote(_)) => exit(2),
                 Err(e) => {
                     eprintln!("ghrepo: {e}");
                     exit(1);
                 }
             },
+            Command::RemoteAdd {
+                name,
+                repo,
+                style,
+                pulls,
+            } => match remote_add(&name, &repo, &style, pulls) {
+                Ok(()) => (),
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                Err(e) => {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            },
+            Command::Clone {
+                repo,
+                dir,
+                style,
+                depth,
+                branch,
+                upstream,
+            } => {
+                let mut options = CloneOptions::new();
+                if let Some(style) = style {
+                    options = options.style(style);
+                }
+                if let Some(depth) = depth {
+                    options = options.depth(depth);
+                }
+                if let Some(branch) = branch {
+                    options = options.branch(&branch);
+                }
+                if let Some(upstream) = upstream {
+                    options = options.upstream(upstream);
+                }
+                let dir = dir.unwrap_or_else(|| PathBuf::from(repo.name()));
+                match repo.clone_into(dir, &options) {
+                    Ok(_) => (),
+                    Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                    Err(e) => {
+                        eprintln!("ghrepo: {e}");
+                        exit(1);
+                    }
+                }
+            }
+            Command::Scan {
+                json,
+                max_depth,
+                skip,
+                jobs,
+                dirpath,
+            } => {
+                let mut scanner = Scanner::new(dirpath.unwrap_or_else(|| PathBuf::from(".")));
+                if let Some(depth) = max_depth {
+                    scanner = scanner.max_depth(depth);
+                }
+                for name in skip {
+                    scanner = scanner.skip_dir(name);
+                }
+                if let Some(jobs) = jobs {
+                    scanner = scanner.jobs(jobs);
+                }
+                if let Err(e) = scan(&scanner, json) {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            }
+            Command::Status {
+                json,
+                prompt,
+                dirpath,
+            } => match show_status(dirpath, json, prompt) {
+                Ok(s) => println!("{s}"),
+                Err(_) if prompt => (),
+                Err(LocalRepoError::CommandFailed(e)) => exit(e.status().code().unwrap_or(1)),
+                Err(e) => {
+                    eprintln!("ghrepo: {e}");
+                    exit(1);
+                }
+            },
         }
     }
 }
 
+/// How to select the Git remote from which to determine the GitHub repository
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum RemoteChoice {
+    Named(String),
+    Auto,
+    All,
+}
+
 fn main() -> Result<(), lexopt::Error> {
     Command::from_parser(Parser::from_env())?.run();
     Ok(())
 }
 
-fn run(dirpath: Option<PathBuf>, json: bool, remote: String) -> Result<String, LocalRepoError> {
+fn run(
+    override_repo: Option<GHRepo>,
+    dirpath: Option<PathBuf>,
+    json: bool,
+    push: bool,
+    remote: Option<RemoteChoice>,
+) -> Result<String, LocalRepoError> {
+    let (gr, local) = if let Some(gr) = override_repo {
+        (gr, None)
+    } else {
+        let lr = match dirpath {
+            Some(p) => LocalRepo::new(p),
+            None => LocalRepo::for_cwd()?,
+        };
+        let (gr, remote_name) = match (remote, push) {
+            (Some(RemoteChoice::Named(r)), false) => (lr.github_remote(&r)?, r),
+            (Some(RemoteChoice::Named(r)), true) => (lr.github_push_remote(&r)?, r),
+            (Some(RemoteChoice::Auto), false) => {
+                let dr = lr.default_github_repo()?;
+                (dr.repo, dr.remote)
+            }
+            (Some(RemoteChoice::Auto), true) => {
+                let dr = lr.default_github_repo()?;
+                (lr.github_push_remote(&dr.remote)?, dr.remote)
+            }
+            (Some(RemoteChoice::All), _) => return show_remotes(&lr, &lr.remotes()?, json),
+            (None, false) => (lr.github_remote("origin")?, String::from("origin")),
+            (None, true) => {
+                let r = lr.push_remote_for_branch(&lr.current_branch()?)?;
+                (lr.github_push_remote(&r)?, r)
+            }
+        };
+        (gr, Some((lr, remote_name)))
+    };
+    if json {
+        let default_branch = match local {
+            Some((lr, remote)) => get_default_branch(&lr, &remote)?,
+            None => None,
+        };
+        Ok(to_json(&RunDetails {
+            repo: RepoDetails::new(&gr),
+            default_branch,
+        }))
+    } else {
+        Ok(gr.to_string())
+    }
+}
+
+/// Determine the default branch of the given remote, returning `None` if it
+/// is not known
+fn get_default_branch(lr: &LocalRepo, remote: &str) -> Result<Option<String>, LocalRepoError> {
+    match lr.default_branch(remote) {
+        Ok(branch) => Ok(Some(branch)),
+        Err(LocalRepoError::NoRemoteHead(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn show_remotes(lr: &LocalRepo, remotes: &[Remote], json: bool) -> Result<String, LocalRepoError> {
+    if json {
+        let details = remotes
+            .iter()
+            .map(|r| Ok(RemoteDetails::new(r, get_default_branch(lr, &r.name)?)))
+            .collect::<Result<Vec<_>, LocalRepoError>>()?;
+        return Ok(to_json(&details));
+    }
+    let rows = remotes
+        .iter()
+        .map(|r| {
+            [
+                r.name.as_str(),
+                r.repo.as_ref().map_or("-", GHRepo::as_str),
+                r.fetch_urls.first().map_or("", String::as_str),
+            ]
+        })
+        .collect::<Vec<_>>();
+    Ok(columns(&rows))
+}
+
+fn show_submodules(dirpath: Option<PathBuf>, json: bool) -> Result<String, LocalRepoError> {
     let lr = match dirpath {
         Some(p) => LocalRepo::new(p),
         None => LocalRepo::for_cwd()?,
     };
-    let gr = lr.github_remote(&remote)?;
+    let submodules = lr.submodules()?;
+    if json {
+        let details = submodules
+            .iter()
+            .map(SubmoduleDetails::new)
+            .collect::<Vec<_>>();
+        return Ok(to_json(&details));
+    }
+    let rows = submodules
+        .iter()
+        .map(|sm| {
+            (
+                sm.path.as_str(),
+                sm.repo.as_ref().map_or("-", GHRepo::as_str),
+                submodule_tree_url(sm).unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(columns(
+        &rows
+            .iter()
+            .map(|(path, repo, url)| [*path, *repo, url.as_str()])
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn link(
+    remote: Option<String>,
+    file: PathBuf,
+    lines: Option<RangeInclusive<usize>>,
+) -> Result<Permalink, LocalRepoError> {
+    let lr = LocalRepo::for_cwd()?;
+    match remote {
+        Some(remote) => lr.remote_permalink(&remote, file, lines),
+        None => lr.permalink(file, lines),
+    }
+}
+
+fn show_status(
+    dirpath: Option<PathBuf>,
+    json: bool,
+    prompt: bool,
+) -> Result<String, LocalRepoError> {
+    let dirpath = match dirpath {
+        Some(p) => p,
+        None => env::current_dir().map_err(LocalRepoError::CurdirError)?,
+    };
+    // In prompt mode, Git's error messages are captured so that nothing is
+    // printed on failure
+    let lr = LocalRepo::builder(dirpath).capture_stderr(prompt).build();
+    let summary = lr.status_summary()?;
     if json {
-        // The various values here all consist entirely of printable ASCII
-        // characters, excluding double-quote and backslash (as long as GitHub
-        // owner & repo names continue to contain only those characters), so we
-        // don't need any special JSON processing for escapes.
-        Ok(format!(
-            concat!(
-                "{{\n",
-                "    \"owner\": \"{}\",\n",
-                "    \"name\": \"{}\",\n",
-                "    \"fullname\": \"{}\",\n",
-                "    \"api_url\": \"{}\",\n",
-                "    \"clone_url\": \"{}\",\n",
-                "    \"git_url\": \"{}\",\n",
-                "    \"html_url\": \"{}\",\n",
-                "    \"ssh_url\": \"{}\"\n",
-                "}}"
-            ),
-            gr.owner(),
-            gr.name(),
-            gr,
-            gr.api_url(),
-            gr.clone_url(),
-            gr.git_url(),
-            gr.html_url(),
-            gr.ssh_url()
-        ))
+        Ok(to_json(&StatusDetails::new(&summary)))
     } else {
-        Ok(gr.to_string())
+        Ok(status_line(&summary))
+    }
+}
+
+fn show_fork(
+    dirpath: Option<PathBuf>,
+    parent: Option<String>,
+    fork: Option<String>,
+    json: bool,
+) -> Result<String, LocalRepoError> {
+    let lr = match dirpath {
+        Some(p) => LocalRepo::new(p),
+        None => LocalRepo::for_cwd()?,
+    };
+    let info = lr.remote_fork_info(
+        parent.as_deref().unwrap_or("upstream"),
+        fork.as_deref().unwrap_or("origin"),
+    )?;
+    if json {
+        return Ok(to_json(&ForkDetails::new(&info)));
+    }
+    let relation = info.relation.to_string();
+    Ok(columns(&[
+        ["parent", &info.parent_remote, info.parent.as_str()],
+        ["fork", &info.fork_remote, info.fork.as_str()],
+        ["relation", "", &relation],
+    ]))
+}
+
+fn set_url(
+    remote: Option<String>,
+    style: &UrlStyle,
+    dry_run: bool,
+) -> Result<RemoteUrlChange, LocalRepoError> {
+    let lr = LocalRepo::for_cwd()?;
+    let remote = remote.as_deref().unwrap_or("origin");
+    if dry_run {
+        lr.remote_url_in_style(remote, style)
+    } else {
+        lr.set_remote_url_style(remote, style)
+    }
+}
+
+fn remote_add(
+    name: &str,
+    repo: &GHRepo,
+    style: &UrlStyle,
+    pulls: bool,
+) -> Result<(), LocalRepoError> {
+    let lr = LocalRepo::for_cwd()?;
+    lr.add_github_remote(name, repo, style)?;
+    if pulls {
+        lr.fetch_pull_requests(name)?;
+    }
+    Ok(())
+}
+
+/// Parse a repository specifier given on the command line
+fn parse_repo_spec(spec: String) -> Result<GHRepo, lexopt::Error> {
+    spec.parse()
+        .map_err(|e| format!("invalid repository {spec:?}: {e}").into())
+}
+
+/// Apply a `--host` option to the URL style selected on the command line
+fn with_host(style: UrlStyle, host: Option<String>) -> Result<UrlStyle, lexopt::Error> {
+    match (style, host) {
+        (UrlStyle::Https, Some(host)) => Ok(UrlStyle::EnterpriseHttps(host)),
+        (UrlStyle::Ssh, Some(host)) => Ok(UrlStyle::EnterpriseSsh(host)),
+        (_, Some(_)) => Err("--host cannot be used with --ssh-443".into()),
+        (style, None) => Ok(style),
+    }
+}
+
+/// Find & inspect the repositories in a directory tree.  With `json`, a line
+/// of JSON is printed for each repository as soon as it has been inspected;
+/// otherwise, a table sorted by path is printed once all repositories have
+/// been inspected, followed by any errors on stderr.
+fn scan(scanner: &Scanner, json: bool) -> Result<(), LocalRepoError> {
+    if json {
+        return scanner.scan_each(|scanned| {
+            let line = serde_json::to_string(&ScanDetails::new(&scanned))
+                .expect("serializing output should not fail");
+            println!("{line}");
+        });
+    }
+    let results = scanner.scan()?;
+    let fields = results
+        .iter()
+        .map(|scanned| {
+            let remotes = scanned
+                .remotes
+                .iter()
+                .map(|r| format!("{}:{}", r.name, r.repo.as_ref().map_or("-", GHRepo::as_str)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            (
+                scanned.path.to_string_lossy(),
+                scanned.branch.as_deref().unwrap_or("-"),
+                remotes,
+            )
+        })
+        .collect::<Vec<_>>();
+    let rows = fields
+        .iter()
+        .map(|(path, branch, remotes)| [path.as_ref(), *branch, remotes.as_str()])
+        .collect::<Vec<_>>();
+    if !rows.is_empty() {
+        println!("{}", columns(&rows));
+    }
+    for scanned in &results {
+        for e in &scanned.errors {
+            eprintln!("ghrepo: {}: {e}", scanned.path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Format a status summary as a single compact line, e.g., `main↑1↓2 +3 ~1
+/// ?2`.  If `HEAD` is detached, the abbreviated commit hash prefixed with `@`
+/// is shown in place of the branch name.
+fn status_line(summary: &StatusSummary) -> String {
+    let mut line = match (&summary.branch, &summary.commit) {
+        (Some(branch), _) => branch.clone(),
+        (None, Some(commit)) => format!("@{}", commit.get(..7).unwrap_or(commit)),
+        (None, None) => String::from("@"),
+    };
+    for (symbol, count) in [
+        ("↑", summary.ahead),
+        ("↓", summary.behind),
+        (" +", summary.staged),
+        (" ~", summary.unstaged),
+        (" !", summary.conflicted),
+        (" ?", summary.untracked),
+    ] {
+        if count > 0 {
+            line.push_str(symbol);
+            line.push_str(&count.to_string());
+        }
+    }
+    line
+}
+
+/// Split an argument of the form `FILE[:LINE[-LINE]]` into a file path and an
+/// optional range of lines.  If the part after the last colon is not a line
+/// number or range, the entire argument is treated as the file path.
+fn parse_link_spec(spec: &str) -> (&str, Option<RangeInclusive<usize>>) {
+    let Some((file, suffix)) = spec.rsplit_once(':') else {
+        return (spec, None);
+    };
+    let (start, end) = suffix.split_once('-').unwrap_or((suffix, suffix));
+    match (start.parse::<usize>(), end.parse::<usize>()) {
+        (Ok(start), Ok(end)) if 0 < start && start <= end && !file.is_empty() => {
+            (file, Some(start..=end))
+        }
+        _ => (spec, None),
+    }
+}
+
+/// Return the URL for the web view of a submodule's repository at its pinned
+/// commit, if both are known
+fn submodule_tree_url(sm: &Submodule) -> Option<String> {
+    match (&sm.repo, &sm.commit) {
+        (Ok(gr), Some(commit)) => Some(gr.tree_url(commit)),
+        _ => None,
+    }
+}
+
+/// Format rows of three fields as left-aligned columns, omitting trailing
+/// whitespace
+fn columns(rows: &[[&str; 3]]) -> String {
+    let width = |i: usize| rows.iter().map(|row| row[i].len()).max().unwrap_or(0);
+    let (width0, width1) = (width(0), width(1));
+    rows.iter()
+        .map(|[a, b, c]| {
+            format!("{a:<width0$}  {b:<width1$}  {c}")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Serialize a value as JSON with four-space indentation
+fn to_json<T: Serialize>(value: &T) -> String {
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    value
+        .serialize(&mut ser)
+        .expect("serializing output should not fail");
+    String::from_utf8(buf).expect("serialized JSON should be UTF-8")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct RepoDetails<'a> {
+    owner: &'a str,
+    name: &'a str,
+    fullname: &'a str,
+    api_url: String,
+    clone_url: String,
+    git_url: String,
+    html_url: String,
+    ssh_url: String,
+}
+
+impl<'a> RepoDetails<'a> {
+    fn new(gr: &'a GHRepo) -> Self {
+        RepoDetails {
+            owner: gr.owner(),
+            name: gr.name(),
+            fullname: gr.as_str(),
+            api_url: gr.api_url(),
+            clone_url: gr.clone_url(),
+            git_url: gr.git_url(),
+            html_url: gr.html_url(),
+            ssh_url: gr.ssh_url(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct RunDetails<'a> {
+    #[serde(flatten)]
+    repo: RepoDetails<'a>,
+    default_branch: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct RemoteDetails<'a> {
+    remote: &'a str,
+    fetch_urls: &'a [String],
+    push_urls: &'a [String],
+    raw_fetch_urls: &'a [String],
+    raw_push_urls: &'a [String],
+    default_branch: Option<String>,
+    repo: Option<RepoDetails<'a>>,
+    error: Option<String>,
+}
+
+impl<'a> RemoteDetails<'a> {
+    fn new(remote: &'a Remote, default_branch: Option<String>) -> Self {
+        let (repo, error) = match &remote.repo {
+            Ok(gr) => (Some(RepoDetails::new(gr)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        RemoteDetails {
+            remote: &remote.name,
+            fetch_urls: &remote.fetch_urls,
+            push_urls: &remote.push_urls,
+            raw_fetch_urls: &remote.raw_fetch_urls,
+            raw_push_urls: &remote.raw_push_urls,
+            default_branch,
+            repo,
+            error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct SubmoduleDetails<'a> {
+    name: &'a str,
+    path: &'a str,
+    url: Option<&'a str>,
+    branch: Option<&'a str>,
+    commit: Option<&'a str>,
+    repo: Option<RepoDetails<'a>>,
+    error: Option<String>,
+    tree_url: Option<String>,
+}
+
+impl<'a> SubmoduleDetails<'a> {
+    fn new(sm: &'a Submodule) -> Self {
+        let (repo, error) = match &sm.repo {
+            Ok(gr) => (Some(RepoDetails::new(gr)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        SubmoduleDetails {
+            name: &sm.name,
+            path: &sm.path,
+            url: sm.url.as_deref(),
+            branch: sm.branch.as_deref(),
+            commit: sm.commit.as_deref(),
+            repo,
+            error,
+            tree_url: submodule_tree_url(sm),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct UpstreamDetails<'a> {
+    remote: &'a str,
+    branch: &'a str,
+    repo: Option<RepoDetails<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct StatusDetails<'a> {
+    branch: Option<&'a str>,
+    commit: Option<&'a str>,
+    upstream: Option<UpstreamDetails<'a>>,
+    ahead: usize,
+    behind: usize,
+    staged: usize,
+    unstaged: usize,
+    untracked: usize,
+    conflicted: usize,
+}
+
+impl<'a> StatusDetails<'a> {
+    fn new(summary: &'a StatusSummary) -> Self {
+        let upstream = summary.upstream.as_ref().map(|up| match up {
+            BranchUpstream::Remote {
+                remote,
+                repo,
+                branch,
+            } => UpstreamDetails {
+                remote,
+                branch,
+                repo: Some(RepoDetails::new(repo)),
+            },
+            BranchUpstream::Local { branch } => UpstreamDetails {
+                remote: ".",
+                branch,
+                repo: None,
+            },
+        });
+        StatusDetails {
+            branch: summary.branch.as_deref(),
+            commit: summary.commit.as_deref(),
+            upstream,
+            ahead: summary.ahead,
+            behind: summary.behind,
+            staged: summary.staged,
+            unstaged: summary.unstaged,
+            untracked: summary.untracked,
+            conflicted: summary.conflicted,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct ForkDetails<'a> {
+    parent_remote: &'a str,
+    parent: RepoDetails<'a>,
+    fork_remote: &'a str,
+    fork: RepoDetails<'a>,
+    relation: String,
+    is_fork: bool,
+}
+
+impl<'a> ForkDetails<'a> {
+    fn new(info: &'a ForkInfo) -> Self {
+        ForkDetails {
+            parent_remote: &info.parent_remote,
+            parent: RepoDetails::new(&info.parent),
+            fork_remote: &info.fork_remote,
+            fork: RepoDetails::new(&info.fork),
+            relation: info.relation.to_string(),
+            is_fork: info.is_fork(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct ScanDetails<'a> {
+    path: String,
+    branch: Option<&'a str>,
+    remotes: Vec<ScanRemoteDetails<'a>>,
+    errors: Vec<String>,
+}
+
+impl<'a> ScanDetails<'a> {
+    fn new(scanned: &'a ScannedRepo) -> Self {
+        ScanDetails {
+            path: scanned.path.to_string_lossy().into_owned(),
+            branch: scanned.branch.as_deref(),
+            remotes: scanned.remotes.iter().map(ScanRemoteDetails::new).collect(),
+            errors: scanned.errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct ScanRemoteDetails<'a> {
+    remote: &'a str,
+    fetch_urls: &'a [String],
+    push_urls: &'a [String],
+    raw_fetch_urls: &'a [String],
+    raw_push_urls: &'a [String],
+    repo: Option<RepoDetails<'a>>,
+    error: Option<String>,
+}
+
+impl<'a> ScanRemoteDetails<'a> {
+    fn new(remote: &'a Remote) -> Self {
+        let (repo, error) = match &remote.repo {
+            Ok(gr) => (Some(RepoDetails::new(gr)), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        ScanRemoteDetails {
+            remote: &remote.name,
+            fetch_urls: &remote.fetch_urls,
+            push_urls: &remote.push_urls,
+            raw_fetch_urls: &remote.raw_fetch_urls,
+            raw_push_urls: &remote.raw_push_urls,
+            repo,
+            error,
+        }
     }
 }
@@ -1,6 +1,10 @@
 use assert_cmd::cargo::cargo_bin_cmd;
 use ghrepo::GHRepo;
 use repomaker::RepoMaker;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
 use tempfile::tempdir;
 use which::which;
 
@@ -31,11 +35,38 @@ fn test_run_noarg() {
     maker.add_remote("origin", repo.ssh_url()).unwrap();
     cargo_bin_cmd!("ghrepo")
         .current_dir(maker.path())
+        .env_remove("GH_REPO")
         .assert()
         .success()
         .stdout("octocat/repository\n");
 }
 
+#[test]
+fn test_run_dir_named_like_subcommand() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let maker = RepoMaker::at(tmp_path.path().join("status")).unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("./status")
+        .current_dir(tmp_path.path())
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+    cargo_bin_cmd!("ghrepo")
+        .arg("status")
+        .current_dir(tmp_path.path())
+        .env("GIT_CEILING_DIRECTORIES", tmp_path.path())
+        .assert()
+        .failure()
+        .stdout("");
+}
+
 #[test]
 fn test_run_json() {
     if which("git").is_err() {
@@ -50,7 +81,8 @@ fn test_run_json() {
     \"clone_url\": \"https://github.com/octocat/repository.git\",
     \"git_url\": \"git://github.com/octocat/repository.git\",
     \"html_url\": \"https://github.com/octocat/repository\",
-    \"ssh_url\": \"git@github.com:octocat/repository.git\"
+    \"ssh_url\": \"git@github.com:octocat/repository.git\",
+    \"default_branch\": null
 }\n";
     let maker = RepoMaker::new().unwrap();
     maker.init("trunk").unwrap();
@@ -77,7 +109,8 @@ fn test_run_json_noarg() {
     \"clone_url\": \"https://github.com/octocat/repository.git\",
     \"git_url\": \"git://github.com/octocat/repository.git\",
     \"html_url\": \"https://github.com/octocat/repository\",
-    \"ssh_url\": \"git@github.com:octocat/repository.git\"
+    \"ssh_url\": \"git@github.com:octocat/repository.git\",
+    \"default_branch\": null
 }\n";
     let maker = RepoMaker::new().unwrap();
     maker.init("trunk").unwrap();
@@ -85,6 +118,36 @@ fn test_run_json_noarg() {
     cargo_bin_cmd!("ghrepo")
         .arg("--json")
         .current_dir(maker.path())
+        .env_remove("GH_REPO")
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_run_json_default_branch() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let expected = "{
+    \"owner\": \"octocat\",
+    \"name\": \"repository\",
+    \"fullname\": \"octocat/repository\",
+    \"api_url\": \"https://api.github.com/repos/octocat/repository\",
+    \"clone_url\": \"https://github.com/octocat/repository.git\",
+    \"git_url\": \"git://github.com/octocat/repository.git\",
+    \"html_url\": \"https://github.com/octocat/repository\",
+    \"ssh_url\": \"git@github.com:octocat/repository.git\",
+    \"default_branch\": \"main\"
+}\n";
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", repo.ssh_url()).unwrap();
+    maker.set_remote_head("origin", "main").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--json")
+        .arg(maker.path())
         .assert()
         .success()
         .stdout(expected);
@@ -130,6 +193,275 @@ fn test_run_remote_noarg() {
         .stdout("sourcedog/repository\n");
 }
 
+#[test]
+fn test_run_auto_gh_resolved() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker.add_remote("upstream", upstream.clone_url()).unwrap();
+    maker
+        .set_config("remote.origin.gh-resolved", "base")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--auto")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+}
+
+#[test]
+fn test_run_auto_upstream() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker.add_remote("upstream", upstream.clone_url()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("-a")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("sourcedog/repository\n");
+}
+
+#[test]
+fn test_run_auto_no_default() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--auto")
+        .arg(maker.path())
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("ghrepo: could not determine default GitHub remote for Git repository\n");
+}
+
+#[test]
+fn test_run_all() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker
+        .add_remote("mirror", "https://git.example.com/repo.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--all")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(concat!(
+            "mirror  -                   https://git.example.com/repo.git\n",
+            "origin  octocat/repository  git@github.com:octocat/repository.git\n",
+        ));
+}
+
+#[test]
+fn test_run_all_json() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "fork").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.clone_url()).unwrap();
+    maker.add_push_url("origin", fork.ssh_url()).unwrap();
+    maker.set_remote_head("origin", "main").unwrap();
+    maker
+        .add_remote("upstream", "https://git.example.com/\"repo\".git")
+        .unwrap();
+    let expected = r#"[
+    {
+        "remote": "origin",
+        "fetch_urls": [
+            "https://github.com/octocat/repository.git"
+        ],
+        "push_urls": [
+            "git@github.com:octocat/fork.git"
+        ],
+        "raw_fetch_urls": [
+            "https://github.com/octocat/repository.git"
+        ],
+        "raw_push_urls": [
+            "git@github.com:octocat/fork.git"
+        ],
+        "default_branch": "main",
+        "repo": {
+            "owner": "octocat",
+            "name": "repository",
+            "fullname": "octocat/repository",
+            "api_url": "https://api.github.com/repos/octocat/repository",
+            "clone_url": "https://github.com/octocat/repository.git",
+            "git_url": "git://github.com/octocat/repository.git",
+            "html_url": "https://github.com/octocat/repository",
+            "ssh_url": "git@github.com:octocat/repository.git"
+        },
+        "error": null
+    },
+    {
+        "remote": "upstream",
+        "fetch_urls": [
+            "https://git.example.com/\"repo\".git"
+        ],
+        "push_urls": [
+            "https://git.example.com/\"repo\".git"
+        ],
+        "raw_fetch_urls": [
+            "https://git.example.com/\"repo\".git"
+        ],
+        "raw_push_urls": [],
+        "default_branch": null,
+        "repo": null,
+        "error": "invalid GitHub repository spec: \"https://git.example.com/\\\"repo\\\".git\""
+    }
+]
+"#;
+    cargo_bin_cmd!("ghrepo")
+        .arg("--all")
+        .arg("--json")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_run_push() {
+    if which("git").is_err() {
+        return;
+    }
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", upstream.clone_url()).unwrap();
+    maker.add_remote("fork", fork.ssh_url()).unwrap();
+    maker.set_upstream("trunk", "origin").unwrap();
+    maker.set_config("branch.trunk.pushRemote", "fork").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--push")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+}
+
+#[test]
+fn test_run_push_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", upstream.clone_url()).unwrap();
+    maker.add_push_url("origin", fork.ssh_url()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--push")
+        .arg("--remote=origin")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+}
+
+#[test]
+fn test_run_gh_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", repo.ssh_url()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .current_dir(maker.path())
+        .env("GH_REPO", "sourcedog/project")
+        .assert()
+        .success()
+        .stdout("sourcedog/project\n");
+}
+
+#[test]
+fn test_run_gh_repo_with_host() {
+    let tmp_path = tempdir().unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .current_dir(tmp_path.path())
+        .env("GH_REPO", "github.com/sourcedog/project")
+        .assert()
+        .success()
+        .stdout("sourcedog/project\n");
+}
+
+#[test]
+fn test_run_gh_repo_explicit_path() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", repo.ssh_url()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg(maker.path())
+        .env("GH_REPO", "sourcedog/project")
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+}
+
+#[test]
+fn test_run_gh_repo_explicit_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("--remote=origin")
+        .current_dir(maker.path())
+        .env("GH_REPO", "sourcedog/project")
+        .assert()
+        .success()
+        .stdout("octocat/repository\n");
+}
+
+#[test]
+fn test_run_gh_repo_invalid() {
+    let tmp_path = tempdir().unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .current_dir(tmp_path.path())
+        .env("GH_REPO", "ghe.example.com/sourcedog/project")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(concat!(
+            "ghrepo: invalid GH_REPO value: invalid GitHub repository spec:",
+            " \"ghe.example.com/sourcedog/project\"\n",
+        ));
+}
+
 #[test]
 fn test_run_empty() {
     if which("git").is_err() {
@@ -181,3 +513,828 @@ fn test_run_invalid_url() {
             " \"https://git.example.com/repo.git\"\n",
         ));
 }
+
+#[test]
+fn test_submodules() {
+    if which("git").is_err() {
+        return;
+    }
+    let sub = RepoMaker::new().unwrap();
+    sub.init("trunk").unwrap();
+    sub.commit_file("README.md", "Hello\n").unwrap();
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(sub.path())
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(commit.stdout).unwrap();
+    let commit = commit.trim();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/superproject.git")
+        .unwrap();
+    maker.add_submodule(sub.path(), "lib/sub").unwrap();
+    maker.add_submodule(sub.path(), "vendor").unwrap();
+    let r = Command::new("git")
+        .args([
+            "config",
+            "-f",
+            ".gitmodules",
+            "submodule.lib/sub.url",
+            "../sub.git",
+        ])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    cargo_bin_cmd!("ghrepo")
+        .arg("submodules")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "lib/sub  octocat/sub  https://github.com/octocat/sub/tree/{commit}\nvendor   -\n"
+        ));
+}
+
+#[test]
+fn test_submodules_json() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    fs::write(
+        maker.path().join(".gitmodules"),
+        concat!(
+            "[submodule \"lib\"]\n",
+            "\tpath = lib\n",
+            "\turl = https://github.com/octocat/lib.git\n",
+            "\tbranch = stable\n",
+            "[submodule \"other\"]\n",
+            "\tpath = other\n",
+            "\turl = https://git.example.com/other.git\n",
+        ),
+    )
+    .unwrap();
+    let expected = r#"[
+    {
+        "name": "lib",
+        "path": "lib",
+        "url": "https://github.com/octocat/lib.git",
+        "branch": "stable",
+        "commit": null,
+        "repo": {
+            "owner": "octocat",
+            "name": "lib",
+            "fullname": "octocat/lib",
+            "api_url": "https://api.github.com/repos/octocat/lib",
+            "clone_url": "https://github.com/octocat/lib.git",
+            "git_url": "git://github.com/octocat/lib.git",
+            "html_url": "https://github.com/octocat/lib",
+            "ssh_url": "git@github.com:octocat/lib.git"
+        },
+        "error": null,
+        "tree_url": null
+    },
+    {
+        "name": "other",
+        "path": "other",
+        "url": "https://git.example.com/other.git",
+        "branch": null,
+        "commit": null,
+        "repo": null,
+        "error": "invalid GitHub repository spec: \"https://git.example.com/other.git\"",
+        "tree_url": null
+    }
+]
+"#;
+    cargo_bin_cmd!("ghrepo")
+        .arg("submodules")
+        .arg("--json")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_submodules_none() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("submodules")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_link() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "git@github.com:octocat/repository.git")
+        .unwrap();
+    maker.commit_file("README.md", "Hello\n").unwrap();
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(maker.path())
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(commit.stdout).unwrap();
+    let commit = commit.trim();
+    cargo_bin_cmd!("ghrepo")
+        .args(["link", "README.md:1-2"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "https://github.com/octocat/repository/blob/{commit}/README.md#L1-L2\n"
+        ))
+        .stderr("ghrepo: warning: HEAD has not been pushed to remote \"origin\"\n");
+    let r = Command::new("git")
+        .args(["update-ref", "refs/remotes/origin/trunk", "HEAD"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    cargo_bin_cmd!("ghrepo")
+        .args(["link", "--remote", "origin", "README.md"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "https://github.com/octocat/repository/blob/{commit}/README.md\n"
+        ))
+        .stderr("");
+}
+
+#[test]
+fn test_link_no_such_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.commit_file("README.md", "Hello\n").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .args(["link", "-r", "upstream", "README.md:3"])
+        .current_dir(maker.path())
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_status() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.commit_file("one.txt", "One\n").unwrap();
+    maker.commit_file("two.txt", "Two\n").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("status")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("trunk\n");
+    maker.set_upstream("trunk", "origin").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/trunk")
+        .unwrap();
+    let r = Command::new("git")
+        .args(["update-ref", "refs/remotes/origin/trunk", "HEAD^"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    fs::write(maker.path().join("one.txt"), "Changed\n").unwrap();
+    fs::write(maker.path().join("new.txt"), "New\n").unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("status")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout("trunk↑1 ~1 ?1\n");
+}
+
+#[test]
+fn test_status_json() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.set_upstream("trunk", "origin").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/main")
+        .unwrap();
+    fs::write(maker.path().join("new.txt"), "New\n").unwrap();
+    let r = Command::new("git")
+        .args(["add", "new.txt"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    let expected = r#"{
+    "branch": "trunk",
+    "commit": null,
+    "upstream": {
+        "remote": "origin",
+        "branch": "main",
+        "repo": {
+            "owner": "octocat",
+            "name": "repository",
+            "fullname": "octocat/repository",
+            "api_url": "https://api.github.com/repos/octocat/repository",
+            "clone_url": "https://github.com/octocat/repository.git",
+            "git_url": "git://github.com/octocat/repository.git",
+            "html_url": "https://github.com/octocat/repository",
+            "ssh_url": "git@github.com:octocat/repository.git"
+        }
+    },
+    "ahead": 0,
+    "behind": 0,
+    "staged": 1,
+    "unstaged": 0,
+    "untracked": 0,
+    "conflicted": 0
+}
+"#;
+    cargo_bin_cmd!("ghrepo")
+        .args(["status", "--json"])
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_status_prompt_not_a_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmpdir = tempdir().unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .args(["status", "--prompt"])
+        .arg(tmpdir.path())
+        .env("GIT_CEILING_DIRECTORIES", tmpdir.path())
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    cargo_bin_cmd!("ghrepo")
+        .arg("status")
+        .arg(tmpdir.path())
+        .env("GIT_CEILING_DIRECTORIES", tmpdir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_fork() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "git@github.com:me/repository.git")
+        .unwrap();
+    maker
+        .add_remote("upstream", "https://github.com/octocat/repository.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("fork")
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(concat!(
+            "parent    upstream  octocat/repository\n",
+            "fork      origin    me/repository\n",
+            "relation            same-name\n",
+        ));
+    cargo_bin_cmd!("ghrepo")
+        .args(["fork", "--parent", "origin", "--fork", "origin"])
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(concat!(
+            "parent    origin  me/repository\n",
+            "fork      origin  me/repository\n",
+            "relation          same-repository\n",
+        ));
+}
+
+#[test]
+fn test_fork_json() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "git@github.com:me/my-fork.git")
+        .unwrap();
+    maker
+        .add_remote("upstream", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let expected = r#"{
+    "parent_remote": "upstream",
+    "parent": {
+        "owner": "octocat",
+        "name": "repository",
+        "fullname": "octocat/repository",
+        "api_url": "https://api.github.com/repos/octocat/repository",
+        "clone_url": "https://github.com/octocat/repository.git",
+        "git_url": "git://github.com/octocat/repository.git",
+        "html_url": "https://github.com/octocat/repository",
+        "ssh_url": "git@github.com:octocat/repository.git"
+    },
+    "fork_remote": "origin",
+    "fork": {
+        "owner": "me",
+        "name": "my-fork",
+        "fullname": "me/my-fork",
+        "api_url": "https://api.github.com/repos/me/my-fork",
+        "clone_url": "https://github.com/me/my-fork.git",
+        "git_url": "git://github.com/me/my-fork.git",
+        "html_url": "https://github.com/me/my-fork",
+        "ssh_url": "git@github.com:me/my-fork.git"
+    },
+    "relation": "unknown",
+    "is_fork": false
+}
+"#;
+    cargo_bin_cmd!("ghrepo")
+        .args(["fork", "--json"])
+        .arg(maker.path())
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn test_fork_no_upstream() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "git@github.com:me/repository.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("fork")
+        .arg(maker.path())
+        .assert()
+        .code(2)
+        .stdout("");
+}
+
+#[test]
+fn test_set_url() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let get_url = || {
+        let r = Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .current_dir(maker.path())
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap()
+    };
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh", "--dry-run"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout(
+            "origin: https://github.com/octocat/repository.git -> git@github.com:octocat/repository.git\n",
+        );
+    assert_eq!(get_url(), "https://github.com/octocat/repository.git\n");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh", "origin"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout(
+            "origin: https://github.com/octocat/repository.git -> git@github.com:octocat/repository.git\n",
+        );
+    assert_eq!(get_url(), "git@github.com:octocat/repository.git\n");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout("origin: git@github.com:octocat/repository.git (unchanged)\n");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--https", "--host", "github.example.com"])
+        .current_dir(maker.path())
+        .assert()
+        .success();
+    assert_eq!(
+        get_url(),
+        "https://github.example.com/octocat/repository.git\n"
+    );
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--https"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout(
+            "origin: https://github.example.com/octocat/repository.git -> https://github.com/octocat/repository.git\n",
+        );
+    assert_eq!(get_url(), "https://github.com/octocat/repository.git\n");
+}
+
+#[test]
+fn test_set_url_errors() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh", "upstream"])
+        .current_dir(maker.path())
+        .assert()
+        .code(2)
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "origin"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh-443", "--host", "github.example.com"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["set-url", "--ssh", "--host", "git@github.example.com"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn test_remote_add() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/me/repository.git")
+        .unwrap();
+    let git_config = |key: &str| {
+        let r = Command::new("git")
+            .args(["config", "--get-all", key])
+            .current_dir(maker.path())
+            .output()
+            .unwrap();
+        String::from_utf8(r.stdout).unwrap()
+    };
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "add", "--ssh", "upstream", "octocat/repository"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        git_config("remote.upstream.url"),
+        "git@github.com:octocat/repository.git\n"
+    );
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "add", "--pulls", "upstream", "octocat/repository"])
+        .current_dir(maker.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        git_config("remote.upstream.url"),
+        "git@github.com:octocat/repository.git\n"
+    );
+    assert_eq!(
+        git_config("remote.upstream.fetch"),
+        "+refs/heads/*:refs/remotes/upstream/*\n+refs/pull/*/head:refs/remotes/upstream/pr/*\n"
+    );
+    cargo_bin_cmd!("ghrepo")
+        .args([
+            "remote",
+            "add",
+            "--host",
+            "github.example.com",
+            "enterprise",
+            "octocat/repository",
+        ])
+        .current_dir(maker.path())
+        .assert()
+        .success();
+    assert_eq!(
+        git_config("remote.enterprise.url"),
+        "https://github.example.com/octocat/repository.git\n"
+    );
+}
+
+#[test]
+fn test_remote_add_errors() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/me/repository.git")
+        .unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "add", "origin", "octocat/repository"])
+        .current_dir(maker.path())
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(
+            "ghrepo: remote already exists in Git repository with a different URL: \"origin\"\n",
+        );
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "add", "upstream"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "add", "upstream", "not a repo"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["remote", "remove", "origin"])
+        .current_dir(maker.path())
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+/// Create a bare repository to stand in for `octocat/repository` when cloning
+/// and return the `url.<base>.insteadOf` key that redirects to it
+#[test]
+fn test_clone() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let key = maker.init_clone_source(&mirror).unwrap();
+    let tmpdir = tempdir().unwrap();
+    let get_url = |dir: &str, remote: &str| {
+        let r = Command::new("git")
+            .args(["remote", "get-url", remote])
+            .current_dir(tmpdir.path().join(dir))
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap()
+    };
+    cargo_bin_cmd!("ghrepo")
+        .args([
+            "clone",
+            "--depth",
+            "1",
+            "--upstream",
+            "parent/repository",
+            "https://github.com/octocat/repository",
+        ])
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", &key)
+        .env(
+            "GIT_CONFIG_VALUE_0",
+            "https://github.com/octocat/repository.git",
+        )
+        .current_dir(tmpdir.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert!(tmpdir.path().join("repository").join("two.txt").exists());
+    assert!(
+        tmpdir
+            .path()
+            .join("repository")
+            .join(".git")
+            .join("shallow")
+            .exists()
+    );
+    assert_eq!(
+        get_url("repository", "origin"),
+        "https://github.com/octocat/repository.git\n"
+    );
+    assert_eq!(
+        get_url("repository", "upstream"),
+        "https://github.com/parent/repository.git\n"
+    );
+    cargo_bin_cmd!("ghrepo")
+        .args(["clone", "octocat/repository", "ssh-clone"])
+        .env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", &key)
+        .env(
+            "GIT_CONFIG_VALUE_0",
+            "git@github.com:octocat/repository.git",
+        )
+        .env("GIT_CONFIG_KEY_1", "ghrepo.protocol")
+        .env("GIT_CONFIG_VALUE_1", "ssh")
+        .current_dir(tmpdir.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        get_url("ssh-clone", "origin"),
+        "git@github.com:octocat/repository.git\n"
+    );
+}
+
+#[test]
+fn test_clone_errors() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmpdir = tempdir().unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .args(["clone", "octocat/repository"])
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "ghrepo.protocol")
+        .env("GIT_CONFIG_VALUE_0", "git")
+        .current_dir(tmpdir.path())
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("ghrepo: invalid ghrepo.protocol setting: \"git\"\n");
+    cargo_bin_cmd!("ghrepo")
+        .args(["clone", "not a repo"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["clone"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["clone", "--depth", "many", "octocat/repository"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .failure()
+        .stdout("");
+    assert!(!tmpdir.path().join("repository").exists());
+}
+
+/// Create a directory tree for testing `ghrepo scan`: a repository with GitHub
+/// & non-GitHub remotes, a repository without remotes at a greater depth, and
+/// a repository in a skipped directory
+fn make_scan_tree(root: &Path) -> io::Result<()> {
+    let github = RepoMaker::at(root.join("github"))?;
+    github.init("main")?;
+    github.add_remote("origin", "https://github.com/octocat/repository.git")?;
+    github.add_remote("upstream", "https://example.com/repository.git")?;
+    RepoMaker::at(root.join("sub").join("other"))?.init("trunk")?;
+    RepoMaker::at(root.join("node_modules").join("dep"))?.init("main")?;
+    Ok(())
+}
+
+#[test]
+fn test_scan() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmpdir = tempdir().unwrap();
+    make_scan_tree(tmpdir.path()).unwrap();
+    cargo_bin_cmd!("ghrepo")
+        .arg("scan")
+        .current_dir(tmpdir.path())
+        .assert()
+        .success()
+        .stdout(concat!(
+            "./github     main   origin:octocat/repository upstream:-\n",
+            "./sub/other  trunk\n",
+        ))
+        .stderr("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["scan", "--max-depth", "1", "--jobs", "1", "."])
+        .current_dir(tmpdir.path())
+        .assert()
+        .success()
+        .stdout("./github  main  origin:octocat/repository upstream:-\n");
+    cargo_bin_cmd!("ghrepo")
+        .args(["scan", "--skip", "sub", "--skip", "github"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_scan_json() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmpdir = tempdir().unwrap();
+    make_scan_tree(tmpdir.path()).unwrap();
+    let output = cargo_bin_cmd!("ghrepo")
+        .args(["scan", "--json"])
+        .current_dir(tmpdir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "ghrepo scan --json should succeed");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines().collect::<Vec<_>>();
+    lines.sort_unstable();
+    assert_eq!(
+        lines,
+        [
+            concat!(
+                r#"{"path":"./github","branch":"main","remotes":["#,
+                r#"{"remote":"origin","#,
+                r#""fetch_urls":["https://github.com/octocat/repository.git"],"#,
+                r#""push_urls":["https://github.com/octocat/repository.git"],"#,
+                r#""raw_fetch_urls":["https://github.com/octocat/repository.git"],"#,
+                r#""raw_push_urls":[],"#,
+                r#""repo":{"owner":"octocat","name":"repository","#,
+                r#""fullname":"octocat/repository","#,
+                r#""api_url":"https://api.github.com/repos/octocat/repository","#,
+                r#""clone_url":"https://github.com/octocat/repository.git","#,
+                r#""git_url":"git://github.com/octocat/repository.git","#,
+                r#""html_url":"https://github.com/octocat/repository","#,
+                r#""ssh_url":"git@github.com:octocat/repository.git"},"#,
+                r#""error":null},"#,
+                r#"{"remote":"upstream","#,
+                r#""fetch_urls":["https://example.com/repository.git"],"#,
+                r#""push_urls":["https://example.com/repository.git"],"#,
+                r#""raw_fetch_urls":["https://example.com/repository.git"],"#,
+                r#""raw_push_urls":[],"repo":null,"#,
+                r#""error":"invalid GitHub repository spec: \"https://example.com/repository.git\""}],"#,
+                r#""errors":[]}"#,
+            ),
+            r#"{"path":"./sub/other","branch":"trunk","remotes":[],"errors":[]}"#,
+        ]
+    );
+}
+
+#[test]
+fn test_scan_errors() {
+    let tmpdir = tempdir().unwrap();
+    let output = cargo_bin_cmd!("ghrepo")
+        .args(["scan", "nowhere"])
+        .current_dir(tmpdir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("ghrepo: "),
+        "unexpected stderr: {stderr:?}"
+    );
+    cargo_bin_cmd!("ghrepo")
+        .args(["scan", "--jobs", "0"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .failure()
+        .stdout("");
+    cargo_bin_cmd!("ghrepo")
+        .args(["scan", "--max-depth", "deep"])
+        .current_dir(tmpdir.path())
+        .assert()
+        .failure()
+        .stdout("");
+}
@@ -0,0 +1,207 @@
+//! An asynchronous counterpart to [`LocalRepo`] built on [`tokio::process`]
+//!
+//! [`LocalRepo`]: crate::LocalRepo
+use crate::backend::{GitCommand, GitQuery};
+use crate::{GHRepo, LocalRepoError, ParseError, SshConfig};
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use tokio::process::Command;
+
+/// An asynchronous variant of [`LocalRepo`] that runs Git with
+/// [`tokio::process`] instead of blocking the current thread, for inspecting
+/// many repositories concurrently.
+///
+/// `AsyncLocalRepo` provides the same basic inspections as [`LocalRepo`]
+/// (using the [`GitCommand`] backend) and fails with the same
+/// [`LocalRepoError`] values.  To run Git with custom settings, such as a
+/// timeout, construct an `AsyncLocalRepo` with
+/// [`LocalRepoBuilder::build_async()`].
+///
+/// The methods of an `AsyncLocalRepo` must be called from within a Tokio
+/// runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ghrepo::{AsyncLocalRepo, LocalRepoError};
+/// # async fn example() -> Result<(), LocalRepoError> {
+/// let lr = AsyncLocalRepo::new("/path/to/repo");
+/// if lr.is_git_repo().await? {
+///     let branch = lr.current_branch().await?;
+///     println!("{branch} tracks {}", lr.branch_upstream(&branch).await?);
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`LocalRepo`]: crate::LocalRepo
+/// [`LocalRepoBuilder::build_async()`]: crate::LocalRepoBuilder::build_async
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AsyncLocalRepo {
+    path: PathBuf,
+    git: GitCommand,
+    ssh_config: Option<SshConfig>,
+}
+
+impl AsyncLocalRepo {
+    /// Create an [`AsyncLocalRepo`] for operating on the repository at or
+    /// containing the directory `dirpath`.
+    ///
+    /// No validation is done as to whether `dirpath` is a Git repository or
+    /// even an extant directory.
+    pub fn new<P: AsRef<Path>>(dirpath: P) -> Self {
+        AsyncLocalRepo::with_git(dirpath.as_ref().to_path_buf(), GitCommand::default(), None)
+    }
+
+    /// Create an [`AsyncLocalRepo`] for operating on the repository at or
+    /// containing the current directory.  As with
+    /// [`LocalRepo::for_cwd()`](crate::LocalRepo::for_cwd), the path to the
+    /// current directory is saved at the time the function is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if [`std::env::current_dir()`] failed
+    pub fn for_cwd() -> Result<Self, LocalRepoError> {
+        Ok(AsyncLocalRepo::new(
+            env::current_dir().map_err(LocalRepoError::CurdirError)?,
+        ))
+    }
+
+    /// (Private) Construct an `AsyncLocalRepo` from its parts
+    pub(crate) fn with_git(path: PathBuf, git: GitCommand, ssh_config: Option<SshConfig>) -> Self {
+        AsyncLocalRepo {
+            path,
+            git,
+            ssh_config,
+        }
+    }
+
+    /// Use the given SSH configuration to resolve host aliases in remote URLs;
+    /// see [`LocalRepo::with_ssh_config()`](crate::LocalRepo::with_ssh_config)
+    #[must_use]
+    pub fn with_ssh_config(mut self, ssh_config: SshConfig) -> Self {
+        self.ssh_config = Some(ssh_config);
+        self
+    }
+
+    /// Returns the path that was given to [`AsyncLocalRepo::new()`] or
+    /// obtained by [`AsyncLocalRepo::for_cwd()`]
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// (Private) Run a Git command in the repository directory and return its
+    /// exit status and, if `capture` is true, its stdout (and, if
+    /// `capture_stderr` is set, its stderr), subject to the configured timeout
+    async fn run(&self, args: &[&str], capture: bool) -> Result<Output, LocalRepoError> {
+        let mut cmd = Command::from(self.git.command(&self.path, args));
+        if capture {
+            cmd.stdout(Stdio::piped());
+            if self.git.capture_stderr {
+                cmd.stderr(Stdio::piped());
+            } else {
+                cmd.stderr(Stdio::inherit());
+            }
+        } else {
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        // Dropping the future when the timeout expires kills the command.
+        cmd.kill_on_drop(true);
+        let output = match self.git.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, cmd.output())
+                .await
+                .map_err(|_| LocalRepoError::Timeout(timeout))?,
+            None => cmd.output().await,
+        };
+        output.map_err(LocalRepoError::CouldNotExecute)
+    }
+
+    /// (Private) Run a Git command in the repository directory and return the
+    /// trimmed output
+    async fn read(&self, args: &[&str]) -> Result<String, LocalRepoError> {
+        let out = self.run(args, true).await?;
+        self.git
+            .finish(&self.path, args.iter().map(OsString::from), out)
+    }
+
+    /// (Private) Run a [`GitQuery`] in the repository directory
+    async fn query<T>(&self, query: GitQuery<'_, T>) -> Result<T, LocalRepoError> {
+        let r = self.read(&query.args).await;
+        query.finish(r)
+    }
+
+    /// Tests whether the directory is either a Git repository or contained in
+    /// one; see [`LocalRepo::is_git_repo()`](crate::LocalRepo::is_git_repo)
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute
+    pub async fn is_git_repo(&self) -> Result<bool, LocalRepoError> {
+        let out = self.run(&["rev-parse", "--git-dir"], false).await?;
+        Ok(out.status.success())
+    }
+
+    /// Get the current branch of the repository; see
+    /// [`LocalRepo::current_branch()`](crate::LocalRepo::current_branch)
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute or returns a nonzero status, if the command's output is invalid
+    /// UTF-8, or if the repository is in a detached `HEAD` state
+    pub async fn current_branch(&self) -> Result<String, LocalRepoError> {
+        self.query(GitQuery::current_branch()).await
+    }
+
+    /// Determines the GitHub repository that the local repository is a clone
+    /// of by parsing the URL for the specified Git remote, after applying any
+    /// `url.<base>.insteadOf` rewrites; see
+    /// [`LocalRepo::github_remote()`](crate::LocalRepo::github_remote)
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute or returns a nonzero status, if the command's output is invalid
+    /// UTF-8, if the given remote does not exist, or if the URL for the given
+    /// remote is not a valid GitHub URL
+    pub async fn github_remote(&self, remote: &str) -> Result<GHRepo, LocalRepoError> {
+        match self
+            .query(GitQuery::remote_urls(remote, false))
+            .await?
+            .first()
+        {
+            Some(url) => Ok(self.parse_url(url)?),
+            None => Err(LocalRepoError::NoSuchRemote(remote.to_string())),
+        }
+    }
+
+    /// Determines the GitHub repository for the upstream remote of the given
+    /// branch of the local repository; see
+    /// [`LocalRepo::branch_upstream()`](crate::LocalRepo::branch_upstream)
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status, if a command's output is invalid UTF-8, if
+    /// the branch does not have a remote configured, if the remote does not
+    /// exist, or if the URL for the remote is not a valid GitHub URL
+    pub async fn branch_upstream(&self, branch: &str) -> Result<GHRepo, LocalRepoError> {
+        let key = format!("branch.{branch}.remote");
+        match self.query(GitQuery::config_get(&key)).await? {
+            Some(upstream) => self.github_remote(&upstream).await,
+            None => Err(LocalRepoError::NoUpstream(branch.to_string())),
+        }
+    }
+
+    /// (Private) Parse a remote URL as a GitHub repository URL, resolving SSH
+    /// host aliases if an SSH configuration was supplied
+    fn parse_url(&self, url: &str) -> Result<GHRepo, ParseError> {
+        match &self.ssh_config {
+            Some(ssh_config) => Ok(GHRepo::from_url_with_ssh_config(url, ssh_config)?.repo),
+            None => GHRepo::from_url(url),
+        }
+    }
+}
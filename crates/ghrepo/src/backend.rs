@@ -0,0 +1,1247 @@
+//! The [`GitBackend`] trait and its implementations
+use crate::gitconfig::{Config, ConfigError, normalize_key};
+use crate::native::{self, GitDirs};
+use crate::{
+    CommandError, Description, FileStatus, LocalRepoError, RepoLayout, RepoState, WorkTreeStatus,
+    Worktree,
+};
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::str;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often to check whether a Git command with a timeout has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A means of inspecting a local Git repository, used by [`LocalRepo`] to
+/// query the repository's configuration & refs and to make simple changes to
+/// its configuration.
+///
+/// Each method takes the path that the [`LocalRepo`] was created with, which
+/// may be any directory within the repository's working tree.
+///
+/// [`LocalRepo`]: crate::LocalRepo
+pub trait GitBackend {
+    /// Tests whether the directory `path` is either a Git repository or
+    /// contained in one
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn is_git_repo(&self, path: &Path) -> Result<bool, LocalRepoError>;
+
+    /// Determines whether the directory `path` is in a Git repository and, if
+    /// so, what kind of location it is and where the repository's files are
+    ///
+    /// Problems with the repository itself should be reported as
+    /// [`RepoState::UnsafeOwnership`] or [`RepoState::Corrupt`] rather than as
+    /// errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the backend itself failed, e.g., if
+    /// Git could not be executed
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError>;
+
+    /// Locates the repository containing the directory `path` and returns the
+    /// locations of its files and of `path` within its working tree.  The
+    /// search for the repository should not go above any directories listed in
+    /// the `GIT_CEILING_DIRECTORIES` environment variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NotARepository`] if `path` is not in a Git
+    /// repository, or another [`LocalRepoError`] if the repository could not
+    /// be inspected
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError>;
+
+    /// Returns the repository's working trees, starting with the main working
+    /// tree (or, for a bare repository, the repository itself) and followed by
+    /// any linked worktrees
+    ///
+    /// The default implementation locates the repository with
+    /// [`GitBackend::layout()`] and reads the list of linked worktrees from the
+    /// repository's files.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn worktrees(&self, path: &Path) -> Result<Vec<Worktree>, LocalRepoError> {
+        let layout = self.layout(path)?;
+        let bare = self
+            .config_get(path, "core.bare")?
+            .is_some_and(|value| native::is_true(&value));
+        let core_worktree = self.config_get(path, "core.worktree")?;
+        native::worktrees(&layout.common_dir, bare, core_worktree.as_deref())
+    }
+
+    /// Returns the path (relative to the top level of the working tree) and
+    /// pinned commit hash of each submodule recorded in the repository's
+    /// index, sorted by path
+    ///
+    /// The default implementation locates the repository with
+    /// [`GitBackend::layout()`] and reads the index file directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    /// or the index could not be read
+    fn gitlinks(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        let layout = self.layout(path)?;
+        let sha256 = self
+            .config_get(path, "extensions.objectFormat")?
+            .is_some_and(|fmt| fmt.eq_ignore_ascii_case("sha256"));
+        native::read_gitlinks(&layout.git_dir.join("index"), if sha256 { 32 } else { 20 })
+    }
+
+    /// Returns the name of the currently checked-out branch
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::DetachedHead`] if the repository is in a
+    /// detached `HEAD` state, or another [`LocalRepoError`] if the repository
+    /// could not be inspected
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError>;
+
+    /// Returns the effective value of the given config key, or `None` if it is
+    /// not set
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError>;
+
+    /// Returns all config keys & values in the order in which Git reads them.
+    /// Section & variable names in the keys must be lowercased; subsection
+    /// names are left as-is.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError>;
+
+    /// Returns all values of the given config key, in the order in which Git
+    /// reads them
+    ///
+    /// The default implementation filters the results of
+    /// [`GitBackend::config_list()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn config_get_all(&self, path: &Path, key: &str) -> Result<Vec<String>, LocalRepoError> {
+        let key = normalize_key(key);
+        Ok(self
+            .config_list(path)?
+            .into_iter()
+            .filter_map(|(k, v)| (normalize_key(&k) == key).then_some(v))
+            .collect())
+    }
+
+    /// Returns the names of all remotes configured for the repository, sorted
+    /// by name
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError>;
+
+    /// Returns the fetch URLs (or, if `push` is true, the push URLs) of the
+    /// given remote, after applying `url.<base>.insteadOf` &
+    /// `url.<base>.pushInsteadOf` rewrites.  If `push` is true and the remote
+    /// does not have any push URLs configured, the fetch URLs are used.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoSuchRemote`] if the remote does not exist,
+    /// or another [`LocalRepoError`] if the repository could not be inspected
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError>;
+
+    /// If the given ref (e.g., `refs/remotes/origin/HEAD`) is a symbolic ref,
+    /// returns the full name of the ref it points to; otherwise, returns
+    /// `None`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError>;
+
+    /// Returns the hash of the commit that `HEAD` points to, or `None` if
+    /// `HEAD` is an unborn branch
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the repository could not be inspected
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError>;
+
+    /// Returns whether the file `file` (a `/`-separated path relative to the
+    /// top level of the working tree) is untracked, differs from `HEAD`
+    /// (either in the index or the working tree), or is unmodified
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot examine
+    /// the working tree, or another [`LocalRepoError`] if the repository could
+    /// not be inspected
+    fn file_status(&self, path: &Path, file: &str) -> Result<FileStatus, LocalRepoError>;
+
+    /// Returns whether the commit `commit` is reachable from any of the
+    /// remote-tracking branches of the given remote
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot walk
+    /// commit history, or another [`LocalRepoError`] if the repository could
+    /// not be inspected
+    fn remote_contains(
+        &self,
+        path: &Path,
+        remote: &str,
+        commit: &str,
+    ) -> Result<bool, LocalRepoError>;
+
+    /// Returns the names (without the `refs/tags/` prefix) of the tags that
+    /// point to the commit `commit`, either directly or via an annotated tag,
+    /// sorted by name
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot peel
+    /// annotated tags, or another [`LocalRepoError`] if the repository could
+    /// not be inspected
+    fn tags_at(&self, path: &Path, commit: &str) -> Result<Vec<String>, LocalRepoError>;
+
+    /// Describes the `HEAD` commit relative to the most recent tag reachable
+    /// from it, as with `git describe --tags`, or returns `None` if no tag is
+    /// reachable
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot walk
+    /// commit history, or another [`LocalRepoError`] if the repository could
+    /// not be inspected or `HEAD` does not point to a commit
+    fn describe(&self, path: &Path) -> Result<Option<Description>, LocalRepoError>;
+
+    /// Returns the current branch (`None` if `HEAD` is detached), the `HEAD`
+    /// commit (`None` if `HEAD` is an unborn branch), the numbers of staged,
+    /// unstaged, untracked, & conflicted files in the working tree, and how
+    /// many commits the current branch is ahead of & behind its upstream.
+    /// Untracked directories are counted as single entries, as with `git
+    /// status`.  If the current branch has no upstream or its upstream's
+    /// remote-tracking branch does not exist, the ahead & behind counts are
+    /// zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot examine
+    /// the working tree, or another [`LocalRepoError`] if the repository could
+    /// not be inspected
+    fn status(&self, path: &Path) -> Result<WorkTreeStatus, LocalRepoError>;
+
+    /// Returns whether any remote-tracking branch of the remote `remote1`
+    /// shares a common ancestor with any remote-tracking branch of the remote
+    /// `remote2`, or `None` if either remote has no remote-tracking branches
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot walk
+    /// commit history, or another [`LocalRepoError`] if the repository could
+    /// not be inspected
+    fn shares_history(
+        &self,
+        path: &Path,
+        remote1: &str,
+        remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError>;
+
+    /// Sets the first fetch URL (`remote.<remote>.url`) of the given remote to
+    /// `url`, as with `git remote set-url`.  Push URLs are not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot modify
+    /// the repository's configuration, or another [`LocalRepoError`] if the
+    /// remote does not exist or the configuration could not be written
+    fn set_remote_url(&self, path: &Path, remote: &str, url: &str) -> Result<(), LocalRepoError>;
+
+    /// Adds a remote with the given name & URL and the default fetch refspec
+    /// (`+refs/heads/*:refs/remotes/<name>/*`), as with `git remote add`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::RemoteExists`] if a remote with the given
+    /// name already exists, [`LocalRepoError::Unsupported`] if the backend
+    /// cannot modify the repository's configuration, or another
+    /// [`LocalRepoError`] if the configuration could not be written
+    fn add_remote(&self, path: &Path, name: &str, url: &str) -> Result<(), LocalRepoError>;
+
+    /// Adds `refspec` to the fetch refspecs (`remote.<remote>.fetch`) of the
+    /// given remote
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot modify
+    /// the repository's configuration, or another [`LocalRepoError`] if the
+    /// remote does not exist or the configuration could not be written
+    fn add_fetch_refspec(
+        &self,
+        path: &Path,
+        remote: &str,
+        refspec: &str,
+    ) -> Result<(), LocalRepoError>;
+}
+
+/// A [`GitBackend`] that runs the `git` command.  This is the default backend
+/// and requires Git 2.13 or later to be installed.
+///
+/// By default, `git` is looked up in `PATH`, it inherits the environment of
+/// the current process, and there is no limit on how long it may run.  These
+/// settings can be changed by constructing a [`LocalRepo`] with
+/// [`LocalRepo::builder()`].
+///
+/// [`LocalRepo`]: crate::LocalRepo
+/// [`LocalRepo::builder()`]: crate::LocalRepo::builder
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GitCommand {
+    /// The Git executable to run
+    pub(crate) git: PathBuf,
+
+    /// Environment variables to set (or, if the value is `None`, unset) when
+    /// running Git
+    pub(crate) env: Vec<(OsString, Option<OsString>)>,
+
+    /// Config options to pass to Git via `-c`
+    pub(crate) config: Vec<(String, String)>,
+
+    /// The maximum amount of time that each Git command may run
+    pub(crate) timeout: Option<Duration>,
+
+    /// Whether to capture Git's stderr instead of letting it pass through to
+    /// the current process's stderr
+    pub(crate) capture_stderr: bool,
+}
+
+impl GitCommand {
+    /// (Private) Construct a [`Command`] for running Git in the directory
+    /// `path` with the configured options & environment
+    pub(crate) fn command(&self, path: &Path, args: &[&str]) -> Command {
+        let mut cmd = Command::new(&self.git);
+        for (key, value) in &self.config {
+            cmd.arg("-c").arg(format!("{key}={value}"));
+        }
+        cmd.args(args).current_dir(path);
+        for (key, value) in &self.env {
+            match value {
+                Some(v) => cmd.env(key, v),
+                None => cmd.env_remove(key),
+            };
+        }
+        if self.capture_stderr {
+            // Git translates its error messages, which must be in English in
+            // order to be recognized by `classify_stderr()`.
+            cmd.env("LC_ALL", "C").env("LANGUAGE", "");
+        }
+        cmd
+    }
+
+    /// (Private) Run a Git command in the directory `path` and return its exit
+    /// status and, if `capture` is true, its stdout (and, if `capture_stderr`
+    /// is set, its stderr).  If `capture` is false, the command's stdout &
+    /// stderr are discarded.
+    fn run(&self, path: &Path, args: &[&str], capture: bool) -> Result<Output, LocalRepoError> {
+        self.run_command(self.command(path, args), capture)
+    }
+
+    /// (Private) Run a Git command constructed by [`GitCommand::command()`]
+    /// with its output handled as described for [`GitCommand::run()`]
+    fn run_command(&self, mut cmd: Command, capture: bool) -> Result<Output, LocalRepoError> {
+        if capture {
+            cmd.stdout(Stdio::piped());
+            if self.capture_stderr {
+                cmd.stderr(Stdio::piped());
+            } else {
+                cmd.stderr(Stdio::inherit());
+            }
+        } else {
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        self.output(cmd)
+    }
+
+    /// (Private) Run a Git command constructed by [`GitCommand::command()`],
+    /// wait for it to exit (subject to the configured timeout), and return its
+    /// exit status and any piped output
+    fn output(&self, mut cmd: Command) -> Result<Output, LocalRepoError> {
+        let Some(timeout) = self.timeout else {
+            return cmd.output().map_err(LocalRepoError::CouldNotExecute);
+        };
+        let mut child = cmd.spawn().map_err(LocalRepoError::CouldNotExecute)?;
+        // Read the pipes in separate threads so that the command doesn't block
+        // on a full pipe while we're waiting for it to exit.  If the command
+        // times out, the threads are left to finish on their own once the
+        // pipes are closed.
+        let stdout_reader = child.stdout.take().map(spawn_reader);
+        let stderr_reader = child.stderr.take().map(spawn_reader);
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(LocalRepoError::CouldNotExecute)? {
+                break status;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                // Errors are ignored here, as they only happen if the command
+                // has already exited.
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(LocalRepoError::Timeout(timeout));
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        };
+        // A process started by the command (e.g., a credential helper) may
+        // have inherited the pipes and kept them open after the command
+        // exited, so the wait for the readers is also subject to the deadline.
+        Ok(Output {
+            status,
+            stdout: recv_reader(stdout_reader, deadline, timeout)?,
+            stderr: recv_reader(stderr_reader, deadline, timeout)?,
+        })
+    }
+
+    /// (Private) Run a Git command in the directory `path` and return the
+    /// trimmed output
+    fn read(&self, path: &Path, args: &[&str]) -> Result<String, LocalRepoError> {
+        let out = self.run(path, args, true)?;
+        self.finish(path, args.iter().map(OsString::from), out)
+    }
+
+    /// (Private) Run a [`GitQuery`] in the directory `path`
+    fn query<T>(&self, path: &Path, query: GitQuery<'_, T>) -> Result<T, LocalRepoError> {
+        let r = self.read(path, &query.args);
+        query.finish(r)
+    }
+
+    /// (Private) Return the trimmed output of a Git command that was run in
+    /// the directory `path` with the arguments `args`, or an error if it
+    /// failed
+    pub(crate) fn finish<I: IntoIterator<Item = OsString>>(
+        &self,
+        path: &Path,
+        args: I,
+        out: Output,
+    ) -> Result<String, LocalRepoError> {
+        if out.status.success() {
+            return Ok(str::from_utf8(&out.stdout)?.trim().to_string());
+        }
+        let stderr = self
+            .capture_stderr
+            .then(|| String::from_utf8_lossy(&out.stderr).trim_end().to_string());
+        if let Some(e) = stderr.as_deref().and_then(|s| classify_stderr(path, s)) {
+            return Err(e);
+        }
+        let mut argv = vec![self.git.clone().into_os_string()];
+        for (key, value) in &self.config {
+            argv.push("-c".into());
+            argv.push(format!("{key}={value}").into());
+        }
+        argv.extend(args);
+        Err(LocalRepoError::CommandFailed(CommandError {
+            argv,
+            status: out.status,
+            stderr,
+        }))
+    }
+
+    /// (Private) Returns the value of the given config key as seen by `git
+    /// clone` when cloning into the absolute path `dir`, i.e., from the
+    /// system, global, & command-line configuration only.  Git is pointed at
+    /// `dir` as its repository so that the configuration of whatever
+    /// repository contains the current directory is not read; `dir` is not a
+    /// Git directory at this point, as `git clone` only accepts a nonexistent
+    /// or empty destination.
+    pub(crate) fn clone_config_get(
+        &self,
+        dir: &Path,
+        key: &str,
+    ) -> Result<Option<String>, LocalRepoError> {
+        let mut git = self.clone();
+        git.env
+            .push((OsString::from("GIT_DIR"), Some(dir.as_os_str().to_owned())));
+        git.config_get(clone_cwd(dir), key)
+    }
+
+    /// (Private) Clone the repository at `url` into the absolute path `dir`
+    /// with `git clone`, optionally passing `--depth` and `--branch`
+    pub(crate) fn clone_repo(
+        &self,
+        url: &str,
+        dir: &Path,
+        depth: Option<u32>,
+        branch: Option<&str>,
+    ) -> Result<(), LocalRepoError> {
+        let depth = depth.map(|d| d.to_string());
+        let mut args = vec!["clone"];
+        if let Some(depth) = &depth {
+            args.extend(["--depth", depth]);
+        }
+        if let Some(branch) = branch {
+            args.extend(["--branch", branch]);
+        }
+        args.extend(["--", url]);
+        let cwd = clone_cwd(dir);
+        let mut cmd = self.command(cwd, &args);
+        cmd.arg(dir);
+        let out = self.run_command(cmd, true)?;
+        self.finish(
+            cwd,
+            args.iter()
+                .map(OsString::from)
+                .chain([dir.as_os_str().to_owned()]),
+            out,
+        )?;
+        Ok(())
+    }
+}
+
+/// (Private) Returns the directory in which to run Git when cloning into the
+/// absolute path `dir`: the nearest existing ancestor of `dir`, so that Git is
+/// not run inside whatever repository contains the current directory
+fn clone_cwd(dir: &Path) -> &Path {
+    dir.ancestors()
+        .skip(1)
+        .find(|p| p.is_dir())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// (Private) Read all of `reader` in a separate thread, which sends the
+/// result over the returned channel
+fn spawn_reader<R: Read + Send + 'static>(mut reader: R) -> Receiver<io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        // The receiver is only gone if the command timed out, in which case
+        // the data is no longer wanted.
+        let _ = sender.send(reader.read_to_end(&mut buf).map(|_| buf));
+    });
+    receiver
+}
+
+/// (Private) Wait until `deadline` for a thread started by [`spawn_reader()`]
+/// to finish and return the data it read, or return a
+/// [`LocalRepoError::Timeout`] for the given timeout if it does not finish in
+/// time
+fn recv_reader(
+    receiver: Option<Receiver<io::Result<Vec<u8>>>>,
+    deadline: Instant,
+    timeout: Duration,
+) -> Result<Vec<u8>, LocalRepoError> {
+    let Some(receiver) = receiver else {
+        return Ok(Vec::new());
+    };
+    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(r) => r.map_err(LocalRepoError::CouldNotExecute),
+        Err(RecvTimeoutError::Timeout) => Err(LocalRepoError::Timeout(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(LocalRepoError::CouldNotExecute(
+            io::Error::other("thread reading Git output exited unexpectedly"),
+        )),
+    }
+}
+
+/// (Private) Determine the [`RepoState`] reported by the stderr output of a
+/// failed `git rev-parse` command
+fn inspect_failure(stderr: &str) -> RepoState {
+    let mut fatal = None;
+    for msg in stderr.lines().filter_map(|ln| ln.strip_prefix("fatal: ")) {
+        if msg.starts_with("not a git repository (") {
+            return RepoState::NotARepository;
+        }
+        if let Some(repo) = dubious_ownership_path(msg) {
+            return RepoState::UnsafeOwnership {
+                path: PathBuf::from(repo),
+            };
+        }
+        fatal.get_or_insert(msg);
+    }
+    RepoState::Corrupt {
+        message: fatal.unwrap_or_else(|| stderr.trim()).to_string(),
+    }
+}
+
+/// (Private) If `msg` is Git's error message for a repository with dubious
+/// ownership (minus the "fatal: " prefix), return the path to the repository
+fn dubious_ownership_path(msg: &str) -> Option<&str> {
+    msg.strip_prefix("detected dubious ownership in repository at '")?
+        .strip_suffix('\'')
+}
+
+/// (Private) If the stderr output of a failed Git command run in the
+/// directory `path` contains a recognized error message, convert it to the
+/// corresponding [`LocalRepoError`]
+fn classify_stderr(path: &Path, stderr: &str) -> Option<LocalRepoError> {
+    for line in stderr.lines() {
+        let Some(msg) = line.strip_prefix("fatal: ") else {
+            continue;
+        };
+        if msg.starts_with("not a git repository") {
+            return Some(LocalRepoError::NotARepository(path.to_path_buf()));
+        }
+        if let Some(repo) = dubious_ownership_path(msg) {
+            return Some(LocalRepoError::DubiousOwnership(PathBuf::from(repo)));
+        }
+        if let Some((Ok(line), file)) = msg
+            .strip_prefix("bad config line ")
+            .and_then(|s| s.split_once(" in file "))
+            .map(|(line, file)| (line.parse::<usize>(), file))
+        {
+            return Some(LocalRepoError::InvalidConfig(ConfigError::new(
+                path.join(file),
+                line,
+            )));
+        }
+    }
+    None
+}
+
+/// (Private) A Git command to run in a repository along with the conversion of
+/// its trimmed output (or failure) into a value of type `T`.  Queries are
+/// shared by [`GitCommand`] and [`AsyncLocalRepo`] so that both build the same
+/// arguments and interpret Git's exit statuses the same way.
+///
+/// [`AsyncLocalRepo`]: crate::AsyncLocalRepo
+pub(crate) struct GitQuery<'a, T> {
+    /// The arguments to pass to Git
+    pub(crate) args: Vec<&'a str>,
+
+    /// Converts the result of [`GitCommand::finish()`] to the query's result
+    convert: Box<dyn FnOnce(GitOutput) -> Result<T, LocalRepoError> + Send + 'a>,
+}
+
+/// (Private) The result of running a Git command, as passed to a [`GitQuery`]
+type GitOutput = Result<String, LocalRepoError>;
+
+impl<'a, T> GitQuery<'a, T> {
+    /// (Private) Construct a query from its arguments and conversion
+    fn new<F>(args: Vec<&'a str>, convert: F) -> Self
+    where
+        F: FnOnce(GitOutput) -> Result<T, LocalRepoError> + Send + 'a,
+    {
+        GitQuery {
+            args,
+            convert: Box::new(convert),
+        }
+    }
+
+    /// (Private) Convert the result of running the query's command
+    pub(crate) fn finish(self, r: GitOutput) -> Result<T, LocalRepoError> {
+        (self.convert)(r)
+    }
+}
+
+impl GitQuery<'static, String> {
+    /// (Private) Query the current branch, failing with
+    /// [`LocalRepoError::DetachedHead`] if `HEAD` is detached
+    pub(crate) fn current_branch() -> Self {
+        GitQuery::new(vec!["symbolic-ref", "--short", "-q", "HEAD"], |r| match r {
+            Err(LocalRepoError::CommandFailed(rc)) if rc.status.code() == Some(1) => {
+                Err(LocalRepoError::DetachedHead)
+            }
+            r => r,
+        })
+    }
+}
+
+impl<'a> GitQuery<'a, Option<String>> {
+    /// (Private) Query the effective value of a config key, or `None` if it
+    /// is not set
+    pub(crate) fn config_get(key: &'a str) -> Self {
+        GitQuery::new(vec!["config", "--get", "--", key], |r| match r {
+            Ok(value) => Ok(Some(value)),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(1) => Ok(None),
+            Err(e) => Err(e),
+        })
+    }
+
+    /// (Private) Query the target of a symbolic ref, or `None` if the ref is
+    /// not symbolic
+    pub(crate) fn symbolic_ref(refname: &'a str) -> Self {
+        GitQuery::new(vec!["symbolic-ref", "-q", refname], |r| match r {
+            Ok(target) => Ok(Some(target)),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(1) => Ok(None),
+            Err(e) => Err(e),
+        })
+    }
+}
+
+impl<'a> GitQuery<'a, Vec<String>> {
+    /// (Private) Query all values of a config key
+    pub(crate) fn config_get_all(key: &'a str) -> Self {
+        GitQuery::new(vec!["config", "-z", "--get-all", "--", key], |r| match r {
+            Ok(out) => Ok(out
+                .split_terminator('\0')
+                .map(ToString::to_string)
+                .collect()),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(1) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        })
+    }
+
+    /// (Private) Query the fetch or push URLs of a remote, failing with
+    /// [`LocalRepoError::NoSuchRemote`] if the remote does not exist
+    pub(crate) fn remote_urls(remote: &'a str, push: bool) -> Self {
+        let mut args = vec!["remote", "get-url", "--all"];
+        if push {
+            args.push("--push");
+        }
+        args.extend(["--", remote]);
+        GitQuery::new(args, move |r| match r {
+            Ok(out) => Ok(out.lines().map(ToString::to_string).collect()),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(2) => {
+                Err(LocalRepoError::NoSuchRemote(remote.to_string()))
+            }
+            Err(e) => Err(e),
+        })
+    }
+}
+
+impl Default for GitCommand {
+    fn default() -> GitCommand {
+        GitCommand {
+            git: PathBuf::from("git"),
+            env: Vec::new(),
+            config: Vec::new(),
+            timeout: None,
+            capture_stderr: false,
+        }
+    }
+}
+
+impl GitBackend for GitCommand {
+    fn is_git_repo(&self, path: &Path) -> Result<bool, LocalRepoError> {
+        let out = self.run(path, &["rev-parse", "--git-dir"], false)?;
+        Ok(out.status.success())
+    }
+
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError> {
+        // Stderr is always captured here, as it's needed to tell why Git
+        // failed.
+        let git = GitCommand {
+            capture_stderr: true,
+            ..self.clone()
+        };
+        let rev_parse = |args: &[&str]| -> Result<Result<String, RepoState>, LocalRepoError> {
+            let mut cmd = git.command(path, args);
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            let out = self.output(cmd)?;
+            if out.status.success() {
+                Ok(Ok(str::from_utf8(&out.stdout)?.to_string()))
+            } else {
+                Ok(Err(inspect_failure(&String::from_utf8_lossy(&out.stderr))))
+            }
+        };
+        let out = match rev_parse(&[
+            "rev-parse",
+            "--absolute-git-dir",
+            "--is-bare-repository",
+            "--is-inside-git-dir",
+            "--is-inside-work-tree",
+        ])? {
+            Ok(out) => out,
+            Err(state) => return Ok(state),
+        };
+        let mut lines = out.lines();
+        let git_dir = PathBuf::from(lines.next().unwrap_or_default());
+        let mut flags = lines.map(|ln| ln == "true");
+        let (bare, inside_git_dir, inside_work_tree) = (
+            flags.next().unwrap_or_default(),
+            flags.next().unwrap_or_default(),
+            flags.next().unwrap_or_default(),
+        );
+        if bare {
+            Ok(RepoState::Bare { git_dir })
+        } else if inside_git_dir || !inside_work_tree {
+            Ok(RepoState::InsideGitDir { git_dir })
+        } else {
+            match rev_parse(&["rev-parse", "--show-toplevel"])? {
+                Ok(toplevel) => Ok(RepoState::WorkTree {
+                    toplevel: PathBuf::from(toplevel.trim_end_matches('\n')),
+                    git_dir,
+                }),
+                Err(state) => Ok(state),
+            }
+        }
+    }
+
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError> {
+        // Stderr is always captured here so that failures due to `path` not
+        // being in a repository are reported as `NotARepository`.
+        let git = GitCommand {
+            capture_stderr: true,
+            ..self.clone()
+        };
+        let out = git.read(
+            path,
+            &[
+                "rev-parse",
+                "--absolute-git-dir",
+                "--is-inside-work-tree",
+                "--show-prefix",
+            ],
+        )?;
+        let mut lines = out.lines();
+        // The common directory is located via the Git directory's `commondir`
+        // file rather than with `--git-common-dir`, as the latter only
+        // supports `--path-format=absolute` as of Git 2.31.
+        let GitDirs {
+            git_dir,
+            common_dir,
+            ..
+        } = GitDirs::for_git_dir(PathBuf::from(lines.next().unwrap_or_default()), None)?;
+        let inside_work_tree = lines.next() == Some("true");
+        let prefix = PathBuf::from(lines.next().unwrap_or_default().trim_end_matches('/'));
+        let toplevel = if inside_work_tree {
+            Some(PathBuf::from(
+                git.read(path, &["rev-parse", "--show-toplevel"])?,
+            ))
+        } else {
+            None
+        };
+        Ok(RepoLayout {
+            toplevel,
+            git_dir,
+            common_dir,
+            prefix,
+        })
+    }
+
+    fn worktrees(&self, path: &Path) -> Result<Vec<Worktree>, LocalRepoError> {
+        let mut worktrees: Vec<Worktree> = Vec::new();
+        for line in self
+            .read(path, &["worktree", "list", "--porcelain"])?
+            .lines()
+        {
+            if let Some(wtpath) = line.strip_prefix("worktree ") {
+                worktrees.push(Worktree {
+                    path: PathBuf::from(wtpath),
+                    branch: None,
+                    bare: false,
+                    locked: false,
+                });
+            } else if let Some(wt) = worktrees.last_mut() {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "branch" => {
+                        wt.branch = Some(
+                            value
+                                .strip_prefix("refs/heads/")
+                                .unwrap_or(value)
+                                .to_string(),
+                        );
+                    }
+                    "bare" => wt.bare = true,
+                    "locked" => wt.locked = true,
+                    _ => (),
+                }
+            }
+        }
+        // Git reports the main working tree of a submodule as the submodule's
+        // Git directory, so locate it via `core.worktree` instead.
+        if let Some(main) = worktrees.first_mut().filter(|wt| !wt.bare) {
+            if let Some(core_worktree) = self.config_get(path, "core.worktree")? {
+                let common_dir = self.layout(path)?.common_dir;
+                main.path = native::normalize(&common_dir.join(core_worktree));
+            }
+        }
+        Ok(worktrees)
+    }
+
+    fn gitlinks(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        Ok(self
+            .read(
+                path,
+                &["ls-files", "-z", "--stage", "--full-name", "--", ":/"],
+            )?
+            .split_terminator('\0')
+            .filter_map(|entry| {
+                let (info, file) = entry.split_once('\t')?;
+                let mut fields = info.split(' ');
+                (fields.next()? == "160000").then(|| {
+                    (
+                        file.to_string(),
+                        fields.next().unwrap_or_default().to_string(),
+                    )
+                })
+            })
+            .collect())
+    }
+
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError> {
+        self.query(path, GitQuery::current_branch())
+    }
+
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError> {
+        self.query(path, GitQuery::config_get(key))
+    }
+
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        Ok(self
+            .read(path, &["config", "-z", "--list"])?
+            .split_terminator('\0')
+            .map(|entry| match entry.split_once('\n') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (entry.to_string(), String::new()),
+            })
+            .collect())
+    }
+
+    fn config_get_all(&self, path: &Path, key: &str) -> Result<Vec<String>, LocalRepoError> {
+        self.query(path, GitQuery::config_get_all(key))
+    }
+
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError> {
+        Ok(self
+            .read(path, &["remote"])?
+            .lines()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError> {
+        self.query(path, GitQuery::remote_urls(remote, push))
+    }
+
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        self.query(path, GitQuery::symbolic_ref(refname))
+    }
+
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError> {
+        match self.read(path, &["rev-parse", "-q", "--verify", "HEAD^{commit}"]) {
+            Ok(commit) => Ok(Some(commit)),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(1) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn file_status(&self, path: &Path, file: &str) -> Result<FileStatus, LocalRepoError> {
+        let pathspec = format!(":(top,literal){file}");
+        let output = self.read(
+            path,
+            &[
+                "status",
+                "--porcelain",
+                "-z",
+                "--untracked-files=all",
+                "--ignored",
+                "--",
+                &pathspec,
+            ],
+        )?;
+        Ok(match output.get(..2) {
+            None => FileStatus::Unmodified,
+            Some("??" | "!!") => FileStatus::Untracked,
+            Some(_) => FileStatus::Modified,
+        })
+    }
+
+    fn remote_contains(
+        &self,
+        path: &Path,
+        remote: &str,
+        commit: &str,
+    ) -> Result<bool, LocalRepoError> {
+        let prefix = format!("refs/remotes/{remote}/");
+        let refs = self.read(
+            path,
+            &[
+                "for-each-ref",
+                "--contains",
+                commit,
+                "--format=%(refname)",
+                &prefix,
+            ],
+        )?;
+        Ok(!refs.is_empty())
+    }
+
+    fn tags_at(&self, path: &Path, commit: &str) -> Result<Vec<String>, LocalRepoError> {
+        Ok(self
+            .read(path, &["tag", "--list", "--points-at", commit])?
+            .lines()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    fn describe(&self, path: &Path) -> Result<Option<Description>, LocalRepoError> {
+        if self.head_commit(path)?.is_none() {
+            return Err(LocalRepoError::NoHeadCommit);
+        }
+        let output = self.read(
+            path,
+            &["describe", "--tags", "--long", "--abbrev=40", "--always"],
+        )?;
+        Ok(native::parse_describe(&output))
+    }
+
+    fn status(&self, path: &Path) -> Result<WorkTreeStatus, LocalRepoError> {
+        let output = self.read(path, &["status", "--porcelain=v2", "--branch", "-z"])?;
+        Ok(native::parse_status(&output))
+    }
+
+    fn shares_history(
+        &self,
+        path: &Path,
+        remote1: &str,
+        remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError> {
+        let tips = |remote: &str| -> Result<Vec<String>, LocalRepoError> {
+            let prefix = format!("refs/remotes/{remote}/");
+            Ok(self
+                .read(path, &["for-each-ref", "--format=%(objectname)", &prefix])?
+                .lines()
+                .map(ToString::to_string)
+                .collect())
+        };
+        let tips1 = tips(remote1)?;
+        let tips2 = tips(remote2)?;
+        if tips1.is_empty() || tips2.is_empty() {
+            return Ok(None);
+        }
+        for tip in &tips1 {
+            // With more than two commits, `git merge-base` finds a common
+            // ancestor of the first commit and any of the others
+            let mut args = vec!["merge-base", tip.as_str()];
+            args.extend(tips2.iter().map(String::as_str));
+            match self.read(path, &args) {
+                Ok(_) => return Ok(Some(true)),
+                Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(1) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(false))
+    }
+
+    fn set_remote_url(&self, path: &Path, remote: &str, url: &str) -> Result<(), LocalRepoError> {
+        match self.read(path, &["remote", "set-url", "--", remote, url]) {
+            Ok(_) => Ok(()),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(2) => {
+                Err(LocalRepoError::NoSuchRemote(remote.to_string()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn add_remote(&self, path: &Path, name: &str, url: &str) -> Result<(), LocalRepoError> {
+        match self.read(path, &["remote", "add", "--", name, url]) {
+            Ok(_) => Ok(()),
+            Err(LocalRepoError::CommandFailed(r)) if r.status.code() == Some(3) => {
+                Err(LocalRepoError::RemoteExists(name.to_string()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn add_fetch_refspec(
+        &self,
+        path: &Path,
+        remote: &str,
+        refspec: &str,
+    ) -> Result<(), LocalRepoError> {
+        if self.remote_urls(path, remote, false)?.is_empty() {
+            return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+        }
+        let key = format!("remote.{remote}.fetch");
+        self.read(path, &["config", "--add", &key, refspec])?;
+        Ok(())
+    }
+}
+
+/// A [`GitBackend`] that reads the repository's files directly, without
+/// running Git.
+///
+/// This backend locates the repository's Git directory (including via `.git`
+/// files, as used by linked worktrees & submodules) and reads the system,
+/// global, & repository configuration files, honoring `include.path`,
+/// `includeIf` (with `gitdir:`, `gitdir/i:`, & `onbranch:` conditions), and
+/// `url.<base>.insteadOf` & `url.<base>.pushInsteadOf`.  Other Git features
+/// that can affect the results, such as reftable repositories, are not
+/// supported.
+///
+/// Unless `GIT_CONFIG_SYSTEM` is set, the system configuration is read from
+/// `/etc/gitconfig` on Unix and is not read at all on other platforms.  This
+/// differs from Git when Git was built with a different installation prefix
+/// (e.g., Homebrew's Git on macOS, which uses
+/// `$(brew --prefix)/etc/gitconfig`).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NativeGit;
+
+impl NativeGit {
+    /// (Private) Locate the Git directory of the repository containing `path`
+    fn git_dirs(path: &Path) -> Result<GitDirs, LocalRepoError> {
+        GitDirs::discover(path)?.ok_or_else(|| LocalRepoError::NotARepository(path.to_path_buf()))
+    }
+
+    /// (Private) Load the configuration of the repository containing `path`
+    fn load_config(path: &Path) -> Result<Config, LocalRepoError> {
+        NativeGit::git_dirs(path)?.load_config()
+    }
+}
+
+impl GitBackend for NativeGit {
+    fn is_git_repo(&self, path: &Path) -> Result<bool, LocalRepoError> {
+        Ok(GitDirs::discover(path)?.is_some())
+    }
+
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError> {
+        let dirs = match GitDirs::discover(path) {
+            Ok(Some(dirs)) => dirs,
+            Ok(None) => return Ok(RepoState::NotARepository),
+            Err(e @ LocalRepoError::CurdirError(_)) => return Err(e),
+            Err(e) => {
+                return Ok(RepoState::Corrupt {
+                    message: e.to_string(),
+                });
+            }
+        };
+        match dirs.load_config() {
+            Ok(config) => Ok(dirs.state(&config)),
+            Err(e) => Ok(RepoState::Corrupt {
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError> {
+        Ok(NativeGit::git_dirs(path)?.layout(path))
+    }
+
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError> {
+        NativeGit::git_dirs(path)?
+            .current_branch()?
+            .ok_or(LocalRepoError::DetachedHead)
+    }
+
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError> {
+        Ok(NativeGit::load_config(path)?
+            .get(key)
+            .map(ToString::to_string))
+    }
+
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        Ok(NativeGit::load_config(path)?.entries().to_vec())
+    }
+
+    fn config_get_all(&self, path: &Path, key: &str) -> Result<Vec<String>, LocalRepoError> {
+        Ok(NativeGit::load_config(path)?
+            .get_all(key)
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError> {
+        Ok(native::remote_names(&NativeGit::load_config(path)?))
+    }
+
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError> {
+        native::remote_urls(&NativeGit::load_config(path)?, remote, push)
+            .ok_or_else(|| LocalRepoError::NoSuchRemote(remote.to_string()))
+    }
+
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        NativeGit::git_dirs(path)?.symbolic_ref(refname)
+    }
+
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError> {
+        NativeGit::git_dirs(path)?.resolve_ref("HEAD")
+    }
+
+    fn file_status(&self, _path: &Path, _file: &str) -> Result<FileStatus, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "comparing files against HEAD",
+        )))
+    }
+
+    fn remote_contains(
+        &self,
+        _path: &Path,
+        _remote: &str,
+        _commit: &str,
+    ) -> Result<bool, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "walking commit history",
+        )))
+    }
+
+    fn tags_at(&self, _path: &Path, _commit: &str) -> Result<Vec<String>, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "peeling annotated tags",
+        )))
+    }
+
+    fn describe(&self, _path: &Path) -> Result<Option<Description>, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "walking commit history",
+        )))
+    }
+
+    fn status(&self, _path: &Path) -> Result<WorkTreeStatus, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "comparing the working tree against HEAD",
+        )))
+    }
+
+    fn shares_history(
+        &self,
+        _path: &Path,
+        _remote1: &str,
+        _remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "walking commit history",
+        )))
+    }
+
+    fn set_remote_url(
+        &self,
+        _path: &Path,
+        _remote: &str,
+        _url: &str,
+    ) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+
+    fn add_remote(&self, _path: &Path, _name: &str, _url: &str) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+
+    fn add_fetch_refspec(
+        &self,
+        _path: &Path,
+        _remote: &str,
+        _refspec: &str,
+    ) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+}
@@ -0,0 +1,375 @@
+//! An in-memory [`GitBackend`] for testing
+use crate::backend::GitBackend;
+use crate::gitconfig::Config;
+use crate::native;
+use crate::{
+    Description, FileStatus, LocalRepoError, RepoLayout, RepoState, WorkTreeStatus, Worktree,
+};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// An in-memory [`GitBackend`] for testing code that uses [`LocalRepo`]
+/// without creating real repositories.
+///
+/// A `FakeGit` describes a single repository that is considered to contain
+/// every path, and so the path that a [`LocalRepo`] is created with is ignored
+/// (except in error values).  Every file is considered tracked & unmodified,
+/// and, unless set otherwise with [`FakeGit::status()`], the working tree is
+/// considered clean & the current branch even with its upstream.  Config
+/// settings are stored in the order that they are added, and remote URLs are
+/// rewritten according to any `url.<base>.insteadOf` &
+/// `url.<base>.pushInsteadOf` settings, just like with a real repository.
+/// Changes made through the [`GitBackend`] methods that modify the repository
+/// (such as [`GitBackend::set_remote_url()`] and [`GitBackend::add_remote()`])
+/// are applied to the `FakeGit` and are visible to later calls.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// # use ghrepo::{FakeGit, GHRepo, LocalRepo};
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let fake = FakeGit::new()
+///     .branch("trunk")
+///     .remote("origin", "https://github.com/octocat/repository.git")
+///     .config("branch.trunk.remote", "origin");
+/// let lr = LocalRepo::with_backend("/path/to/repo", fake);
+/// assert_eq!(lr.current_branch()?, "trunk");
+/// assert_eq!(lr.branch_upstream("trunk")?, GHRepo::new("octocat", "repository")?);
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`LocalRepo`]: crate::LocalRepo
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FakeGit {
+    is_repo: bool,
+    head: Option<String>,
+    config: RefCell<Config>,
+    symrefs: Vec<(String, String)>,
+    commit: Option<String>,
+    pushed: bool,
+    tags: Vec<String>,
+    status: WorkTreeStatus,
+    related: bool,
+}
+
+impl FakeGit {
+    /// Create a `FakeGit` for a repository with no configuration or remotes
+    /// that has the branch `main` checked out
+    pub fn new() -> Self {
+        FakeGit {
+            is_repo: true,
+            head: Some(String::from("main")),
+            config: RefCell::new(Config::default()),
+            symrefs: Vec::new(),
+            commit: None,
+            pushed: true,
+            tags: Vec::new(),
+            status: WorkTreeStatus::default(),
+            related: true,
+        }
+    }
+
+    /// Create a `FakeGit` for a directory that is not in a Git repository.
+    /// All methods other than [`LocalRepo::is_git_repo()`] will fail with
+    /// [`LocalRepoError::NotARepository`].
+    ///
+    /// [`LocalRepo::is_git_repo()`]: crate::LocalRepo::is_git_repo
+    pub fn not_a_repository() -> Self {
+        FakeGit {
+            is_repo: false,
+            ..FakeGit::new()
+        }
+    }
+
+    /// Set the currently checked-out branch
+    #[must_use]
+    pub fn branch(mut self, name: &str) -> Self {
+        self.head = Some(name.to_string());
+        self
+    }
+
+    /// Put the repository in a detached `HEAD` state
+    #[must_use]
+    pub fn detached(mut self) -> Self {
+        self.head = None;
+        self
+    }
+
+    /// Add a config setting.  If the key is already set, the new value is
+    /// added after the existing values, as with `git config --add`.
+    #[must_use]
+    pub fn config(mut self, key: &str, value: &str) -> Self {
+        self.config.get_mut().push(key, value);
+        self
+    }
+
+    /// Add a remote with the given URL.  This is equivalent to setting
+    /// `remote.<name>.url` with [`FakeGit::config()`].
+    #[must_use]
+    pub fn remote(self, name: &str, url: &str) -> Self {
+        self.config(&format!("remote.{name}.url"), url)
+    }
+
+    /// Make the ref `refname` (e.g., `refs/remotes/origin/HEAD`) a symbolic
+    /// ref pointing to `target` (e.g., `refs/remotes/origin/main`)
+    #[must_use]
+    pub fn symbolic_ref(mut self, refname: &str, target: &str) -> Self {
+        self.symrefs.retain(|(name, _)| name != refname);
+        self.symrefs.push((refname.to_string(), target.to_string()));
+        self
+    }
+
+    /// Set the hash of the commit that `HEAD` points to.  By default, `HEAD`
+    /// is an unborn branch.
+    #[must_use]
+    pub fn head_commit(mut self, commit: &str) -> Self {
+        self.commit = Some(commit.to_string());
+        self
+    }
+
+    /// Make every commit be considered not yet pushed to any remote.  By
+    /// default, every commit is considered to have been pushed to every
+    /// remote.
+    #[must_use]
+    pub fn unpushed(mut self) -> Self {
+        self.pushed = false;
+        self
+    }
+
+    /// Add a tag pointing to the `HEAD` commit
+    #[must_use]
+    pub fn tag(mut self, name: &str) -> Self {
+        self.tags.push(name.to_string());
+        self.tags.sort();
+        self
+    }
+
+    /// Set the working tree status & ahead/behind counts reported by
+    /// [`GitBackend::status()`].  The `branch` & `commit` fields of `status`
+    /// are ignored in favor of the values set with [`FakeGit::branch()`],
+    /// [`FakeGit::detached()`], & [`FakeGit::head_commit()`].
+    #[must_use]
+    pub fn status(mut self, status: WorkTreeStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Make the remote-tracking branches of different remotes be considered
+    /// to share no commit history.  By default, all remotes are considered to
+    /// share history.
+    #[must_use]
+    pub fn unrelated_histories(mut self) -> Self {
+        self.related = false;
+        self
+    }
+
+    /// (Private) Fail if the fake directory is not a repository
+    fn check_repo(&self, path: &Path) -> Result<(), LocalRepoError> {
+        if self.is_repo {
+            Ok(())
+        } else {
+            Err(LocalRepoError::NotARepository(path.to_path_buf()))
+        }
+    }
+}
+
+impl Default for FakeGit {
+    fn default() -> Self {
+        FakeGit::new()
+    }
+}
+
+impl GitBackend for FakeGit {
+    fn is_git_repo(&self, _path: &Path) -> Result<bool, LocalRepoError> {
+        Ok(self.is_repo)
+    }
+
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError> {
+        if self.is_repo {
+            Ok(RepoState::WorkTree {
+                toplevel: path.to_path_buf(),
+                git_dir: path.join(".git"),
+            })
+        } else {
+            Ok(RepoState::NotARepository)
+        }
+    }
+
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(RepoLayout {
+            toplevel: Some(path.to_path_buf()),
+            git_dir: path.join(".git"),
+            common_dir: path.join(".git"),
+            prefix: PathBuf::new(),
+        })
+    }
+
+    fn worktrees(&self, path: &Path) -> Result<Vec<Worktree>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(vec![Worktree {
+            path: path.to_path_buf(),
+            branch: self.head.clone(),
+            bare: false,
+            locked: false,
+        }])
+    }
+
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError> {
+        self.check_repo(path)?;
+        self.head.clone().ok_or(LocalRepoError::DetachedHead)
+    }
+
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(self.config.borrow().get(key).map(ToString::to_string))
+    }
+
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(self.config.borrow().entries().to_vec())
+    }
+
+    fn config_get_all(&self, path: &Path, key: &str) -> Result<Vec<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(self
+            .config
+            .borrow()
+            .get_all(key)
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(native::remote_names(&self.config.borrow()))
+    }
+
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        native::remote_urls(&self.config.borrow(), remote, push)
+            .ok_or_else(|| LocalRepoError::NoSuchRemote(remote.to_string()))
+    }
+
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        if refname == "HEAD" {
+            return Ok(self
+                .head
+                .as_ref()
+                .map(|branch| format!("refs/heads/{branch}")));
+        }
+        Ok(self
+            .symrefs
+            .iter()
+            .find(|(name, _)| name == refname)
+            .map(|(_, target)| target.clone()))
+    }
+
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(self.commit.clone())
+    }
+
+    fn file_status(&self, path: &Path, _file: &str) -> Result<FileStatus, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(FileStatus::Unmodified)
+    }
+
+    fn remote_contains(
+        &self,
+        path: &Path,
+        _remote: &str,
+        _commit: &str,
+    ) -> Result<bool, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(self.pushed)
+    }
+
+    fn tags_at(&self, path: &Path, commit: &str) -> Result<Vec<String>, LocalRepoError> {
+        self.check_repo(path)?;
+        if self.commit.as_deref() == Some(commit) {
+            Ok(self.tags.clone())
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn describe(&self, path: &Path) -> Result<Option<Description>, LocalRepoError> {
+        self.check_repo(path)?;
+        let commit = self.commit.clone().ok_or(LocalRepoError::NoHeadCommit)?;
+        Ok(self.tags.last().map(|tag| Description {
+            tag: tag.clone(),
+            distance: 0,
+            commit,
+        }))
+    }
+
+    fn status(&self, path: &Path) -> Result<WorkTreeStatus, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(WorkTreeStatus {
+            branch: self.head.clone(),
+            commit: self.commit.clone(),
+            ..self.status.clone()
+        })
+    }
+
+    fn shares_history(
+        &self,
+        path: &Path,
+        _remote1: &str,
+        _remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError> {
+        self.check_repo(path)?;
+        Ok(Some(self.related))
+    }
+
+    fn set_remote_url(&self, path: &Path, remote: &str, url: &str) -> Result<(), LocalRepoError> {
+        self.check_repo(path)?;
+        if self
+            .config
+            .borrow_mut()
+            .replace_first(&format!("remote.{remote}.url"), url)
+        {
+            Ok(())
+        } else {
+            Err(LocalRepoError::NoSuchRemote(remote.to_string()))
+        }
+    }
+
+    fn add_remote(&self, path: &Path, name: &str, url: &str) -> Result<(), LocalRepoError> {
+        self.check_repo(path)?;
+        let mut config = self.config.borrow_mut();
+        if native::remote_names(&config).iter().any(|r| r == name) {
+            return Err(LocalRepoError::RemoteExists(name.to_string()));
+        }
+        config.push(&format!("remote.{name}.url"), url);
+        config.push(
+            &format!("remote.{name}.fetch"),
+            &format!("+refs/heads/*:refs/remotes/{name}/*"),
+        );
+        Ok(())
+    }
+
+    fn add_fetch_refspec(
+        &self,
+        path: &Path,
+        remote: &str,
+        refspec: &str,
+    ) -> Result<(), LocalRepoError> {
+        self.check_repo(path)?;
+        let mut config = self.config.borrow_mut();
+        if config.get(&format!("remote.{remote}.url")).is_none() {
+            return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+        }
+        config.push(&format!("remote.{remote}.fetch"), refspec);
+        Ok(())
+    }
+}
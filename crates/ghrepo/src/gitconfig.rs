@@ -0,0 +1,597 @@
+//! A reader for Git configuration files, used by the native backend of
+//! [`LocalRepo`][crate::LocalRepo]
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Maximum depth of nested `include.path` & `includeIf.*.path` directives,
+/// matching the limit used by Git
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// Error returned when a Git configuration file cannot be parsed
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConfigError {
+    path: PathBuf,
+    line: usize,
+}
+
+impl ConfigError {
+    /// (Private) Construct a `ConfigError` for line `line` of the file `path`
+    pub(crate) fn new(path: PathBuf, line: usize) -> Self {
+        ConfigError { path, line }
+    }
+
+    /// The path to the configuration file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The (1-based) line number at which the error occurred
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bad config line {} in file {}",
+            self.line,
+            self.path.display()
+        )
+    }
+}
+
+impl error::Error for ConfigError {}
+
+/// Error returned when loading Git configuration
+#[derive(Debug)]
+pub(crate) enum LoadError {
+    Read(PathBuf, io::Error),
+    Parse(ConfigError),
+}
+
+/// A collection of Git configuration settings, in the order in which they were
+/// read
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Config {
+    /// Pairs of normalized keys and values
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Return the effective (i.e., last) value for the given key
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).last()
+    }
+
+    /// Return all values for the given key
+    pub(crate) fn get_all(&self, key: &str) -> impl Iterator<Item = &str> {
+        let key = normalize_key(key);
+        self.entries
+            .iter()
+            .filter(move |(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Return all keys & values, in order
+    pub(crate) fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// Read the configuration file at `path` and append its settings
+    /// (processing any includes) to `self`.  If the file does not exist,
+    /// nothing is done.
+    pub(crate) fn read_file(&mut self, path: &Path, ctx: &IncludeContext) -> Result<(), LoadError> {
+        self.read_file_at_depth(path, ctx, 0)
+    }
+
+    /// Add a setting that did not come from a file
+    pub(crate) fn push(&mut self, key: &str, value: &str) {
+        self.entries.push((normalize_key(key), value.to_string()));
+    }
+
+    /// Replace the value of the first setting for the given key, returning
+    /// false if the key is not set
+    pub(crate) fn replace_first(&mut self, key: &str, value: &str) -> bool {
+        let key = normalize_key(key);
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => {
+                *v = value.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn read_file_at_depth(
+        &mut self,
+        path: &Path,
+        ctx: &IncludeContext,
+        depth: usize,
+    ) -> Result<(), LoadError> {
+        let src = match fs::read(path) {
+            Ok(src) => src,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(LoadError::Read(path.to_path_buf(), e)),
+        };
+        let src = String::from_utf8_lossy(&src);
+        let entries = parse_config(&src).map_err(|line| {
+            LoadError::Parse(ConfigError {
+                path: path.to_path_buf(),
+                line,
+            })
+        })?;
+        for (key, value) in entries {
+            let include = key == "include.path"
+                || key
+                    .strip_prefix("includeif.")
+                    .and_then(|s| s.strip_suffix(".path"))
+                    .is_some_and(|cond| ctx.condition_holds(cond, path));
+            let included = (include && depth < MAX_INCLUDE_DEPTH && !value.is_empty())
+                .then(|| resolve_include_path(&value, path));
+            self.entries.push((key, value));
+            if let Some(included) = included {
+                self.read_file_at_depth(&included, ctx, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Information used to evaluate `includeIf` conditions
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct IncludeContext {
+    /// The path to the repository's Git directory
+    pub(crate) git_dir: Option<PathBuf>,
+
+    /// The name of the currently checked-out branch
+    pub(crate) branch: Option<String>,
+}
+
+impl IncludeContext {
+    fn condition_holds(&self, cond: &str, config_path: &Path) -> bool {
+        if let Some(pattern) = cond.strip_prefix("gitdir:") {
+            self.gitdir_matches(pattern, config_path, false)
+        } else if let Some(pattern) = cond.strip_prefix("gitdir/i:") {
+            self.gitdir_matches(pattern, config_path, true)
+        } else if let Some(pattern) = cond.strip_prefix("onbranch:") {
+            let Some(branch) = self.branch.as_deref() else {
+                return false;
+            };
+            let mut pattern = pattern.to_string();
+            if pattern.ends_with('/') {
+                pattern.push_str("**");
+            }
+            wildmatch(&pattern, branch, false)
+        } else {
+            // Unsupported conditions (e.g., "hasconfig:") are treated as
+            // false.
+            false
+        }
+    }
+
+    fn gitdir_matches(&self, pattern: &str, config_path: &Path, icase: bool) -> bool {
+        let Some(git_dir) = self.git_dir.as_deref() else {
+            return false;
+        };
+        let mut pat = if let Some(rest) = pattern.strip_prefix("~/") {
+            match home_dir() {
+                Some(home) => format!("{}/{rest}", home.display()),
+                None => return false,
+            }
+        } else if let Some(rest) = pattern.strip_prefix("./") {
+            match config_path.parent() {
+                Some(dir) => format!("{}/{rest}", dir.display()),
+                None => return false,
+            }
+        } else if Path::new(pattern).is_absolute() {
+            pattern.to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        if pat.ends_with('/') {
+            pat.push_str("**");
+        }
+        let mut candidates = vec![git_dir.to_path_buf()];
+        if let Ok(p) = fs::canonicalize(git_dir) {
+            candidates.push(p);
+        }
+        candidates
+            .iter()
+            .any(|p| wildmatch(&pat, &p.to_string_lossy(), icase))
+    }
+}
+
+/// Resolve the value of an `include.path` or `includeIf.*.path` setting
+/// relative to the path of the configuration file in which it occurs
+fn resolve_include_path(value: &str, config_path: &Path) -> PathBuf {
+    if let Some(rest) = value.strip_prefix("~/") {
+        if let Some(home) = home_dir() {
+            return home.join(rest);
+        }
+    }
+    match config_path.parent() {
+        Some(dir) => dir.join(value),
+        None => PathBuf::from(value),
+    }
+}
+
+/// Return the user's home directory
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Normalize a configuration key by lowercasing its section & variable name
+/// while leaving any subsection as-is
+pub(crate) fn normalize_key(key: &str) -> String {
+    match (key.find('.'), key.rfind('.')) {
+        (Some(i), Some(j)) if i < j => format!(
+            "{}{}{}",
+            key[..i].to_ascii_lowercase(),
+            &key[i..j],
+            key[j..].to_ascii_lowercase()
+        ),
+        _ => key.to_ascii_lowercase(),
+    }
+}
+
+/// Parse the contents of a Git configuration file into a list of normalized
+/// keys and values.  On failure, the line number at which the error occurred
+/// is returned.
+///
+/// Keys that are not followed by an `=` (which Git interprets as boolean true)
+/// are given empty values.
+pub(crate) fn parse_config(src: &str) -> Result<Vec<(String, String)>, usize> {
+    let mut parser = ConfigParser {
+        chars: src
+            .strip_prefix('\u{FEFF}')
+            .unwrap_or(src)
+            .chars()
+            .peekable(),
+        line: 1,
+        after_newline: false,
+    };
+    let mut section: Option<String> = None;
+    let mut entries = Vec::new();
+    loop {
+        match parser.peek() {
+            None => return Ok(entries),
+            Some('\n') => {
+                parser.next();
+            }
+            Some(c) if c.is_whitespace() => {
+                parser.next();
+            }
+            Some('#' | ';') => parser.skip_line(),
+            Some('[') => {
+                parser.next();
+                section = Some(parser.parse_section_header()?);
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let Some(sect) = section.as_deref() else {
+                    return Err(parser.line);
+                };
+                let name = parser.parse_name();
+                let value = parser.parse_value()?;
+                entries.push((format!("{sect}.{name}"), value));
+            }
+            Some(_) => return Err(parser.line),
+        }
+    }
+}
+
+struct ConfigParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    /// The line number of the most recently consumed character
+    line: usize,
+    /// Whether the most recently consumed character was a newline
+    after_newline: bool,
+}
+
+impl ConfigParser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if self.after_newline && c.is_some() {
+            self.line += 1;
+        }
+        self.after_newline = c == Some('\n');
+        c
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.next() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// Parse a section header after the opening `[`, returning the
+    /// normalized section name (with subsection, if any)
+    fn parse_section_header(&mut self) -> Result<String, usize> {
+        let mut name = String::new();
+        loop {
+            match self.next() {
+                Some(']') if !name.is_empty() => return Ok(name.to_ascii_lowercase()),
+                Some(c) if c.is_ascii_alphanumeric() || c == '-' || c == '.' => name.push(c),
+                Some(c) if c.is_whitespace() && c != '\n' && !name.is_empty() => {
+                    return self.parse_subsection(name.to_ascii_lowercase());
+                }
+                _ => return Err(self.line),
+            }
+        }
+    }
+
+    fn parse_subsection(&mut self, section: String) -> Result<String, usize> {
+        while self.peek().is_some_and(|c| c.is_whitespace() && c != '\n') {
+            self.next();
+        }
+        if self.next() != Some('"') {
+            return Err(self.line);
+        }
+        let mut subsection = String::new();
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => match self.next() {
+                    Some('\n') | None => return Err(self.line),
+                    Some(c) => subsection.push(c),
+                },
+                Some('\n') | None => return Err(self.line),
+                Some(c) => subsection.push(c),
+            }
+        }
+        if self.next() != Some(']') {
+            return Err(self.line);
+        }
+        Ok(format!("{section}.{subsection}"))
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                name.push(c.to_ascii_lowercase());
+                self.next();
+            } else {
+                break;
+            }
+        }
+        name
+    }
+
+    /// Parse the remainder of a variable line after the name, up to & including
+    /// the terminating newline
+    fn parse_value(&mut self) -> Result<String, usize> {
+        while self.peek().is_some_and(|c| c.is_whitespace() && c != '\n') {
+            self.next();
+        }
+        match self.peek() {
+            None => return Ok(String::new()),
+            Some('\n') => {
+                self.next();
+                return Ok(String::new());
+            }
+            Some('#' | ';') => {
+                self.skip_line();
+                return Ok(String::new());
+            }
+            Some('=') => {
+                self.next();
+            }
+            Some(_) => return Err(self.line),
+        }
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut spaces = 0;
+        loop {
+            let Some(c) = self.next() else {
+                if quoted {
+                    return Err(self.line);
+                }
+                return Ok(value);
+            };
+            match c {
+                '\n' if quoted => return Err(self.line),
+                '\n' => return Ok(value),
+                c if c.is_whitespace() && !quoted => {
+                    if !value.is_empty() {
+                        spaces += 1;
+                    }
+                    continue;
+                }
+                '#' | ';' if !quoted => {
+                    self.skip_line();
+                    return Ok(value);
+                }
+                _ => (),
+            }
+            for _ in 0..spaces {
+                value.push(' ');
+            }
+            spaces = 0;
+            match c {
+                '\\' => match self.next() {
+                    Some('\n') => (),
+                    Some('t') => value.push('\t'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('n') => value.push('\n'),
+                    Some(c @ ('\\' | '"')) => value.push(c),
+                    _ => return Err(self.line),
+                },
+                '"' => quoted = !quoted,
+                c => value.push(c),
+            }
+        }
+    }
+}
+
+/// Test whether `text` matches the glob pattern `pattern`, following the
+/// semantics of Git's `wildmatch()` with the `WM_PATHNAME` flag: `*` matches
+/// any sequence of characters other than `/`, `?` matches any single
+/// character other than `/`, and `**` surrounded by slashes (or at the start
+/// or end of the pattern) matches any sequence of path components.
+/// Bracket expressions are not supported and are matched literally.
+pub(crate) fn wildmatch(pattern: &str, text: &str, icase: bool) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    wildmatch_chars(&pattern, &text, icase)
+}
+
+fn wildmatch_chars(pattern: &[char], text: &[char], icase: bool) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            if let Some(('*', rest2)) = rest.split_first() {
+                // "**"
+                let rest2 = rest2.strip_prefix(&['/']).unwrap_or(rest2);
+                (0..=text.len()).any(|i| {
+                    let (before, after) = text.split_at(i);
+                    (i == 0 || before.last() == Some(&'/')) && wildmatch_chars(rest2, after, icase)
+                }) || wildmatch_chars(rest2, text, icase)
+                    || (rest2.is_empty() && !text.is_empty())
+            } else {
+                (0..=text.len())
+                    .take_while(|&i| i == 0 || text.get(i - 1) != Some(&'/'))
+                    .any(|i| wildmatch_chars(rest, &text[i..], icase))
+            }
+        }
+        Some((&p, rest)) => match text.split_first() {
+            Some((&t, trest)) => {
+                let matched = if p == '?' {
+                    t != '/'
+                } else if icase {
+                    p.to_lowercase().eq(t.to_lowercase())
+                } else {
+                    p == t
+                };
+                matched && wildmatch_chars(rest, trest, icase)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("core.bare", "core.bare")]
+    #[case("Core.Bare", "core.bare")]
+    #[case("Branch.Main.Remote", "branch.Main.remote")]
+    #[case(
+        "URL.https://Example.com/.insteadOf",
+        "url.https://Example.com/.insteadof"
+    )]
+    fn test_normalize_key(#[case] key: &str, #[case] normed: &str) {
+        assert_eq!(normalize_key(key), normed);
+    }
+
+    #[test]
+    fn test_replace_first() {
+        let mut config = Config::default();
+        config.push("remote.origin.url", "one");
+        config.push("Remote.origin.URL", "two");
+        assert!(config.replace_first("remote.origin.URL", "three"));
+        assert_eq!(
+            config.get_all("remote.origin.url").collect::<Vec<_>>(),
+            ["three", "two"]
+        );
+        assert!(!config.replace_first("remote.upstream.url", "four"));
+        assert_eq!(config.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let src = concat!(
+            "# A comment\n",
+            "[core]\n",
+            "\tflag\n",
+            "\ta = \" x  \"  y   # comment\n",
+            "\tb = foo\\\n",
+            "bar\n",
+            "\tc = \"semi;colon\" ; comment\n",
+            "\td = tab\tsep\t  \n",
+            "\te =\n",
+            "\tf = esc\\\\\\\"\\t\\n\n",
+            "[Sec \"Sub \\\"q\\\" \\\\x\"]\n",
+            "\tKey = v\n",
+            "[Legacy.SubSec] k = v2\n",
+        );
+        assert_eq!(
+            parse_config(src).unwrap(),
+            [
+                ("core.flag", ""),
+                ("core.a", " x    y"),
+                ("core.b", "foobar"),
+                ("core.c", "semi;colon"),
+                ("core.d", "tab sep"),
+                ("core.e", ""),
+                ("core.f", "esc\\\"\t\n"),
+                ("sec.Sub \"q\" \\x.key", "v"),
+                ("legacy.subsec.k", "v2"),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case("key = value\n", 1)]
+    #[case("[core]\n\tkey = \"unterminated\n", 2)]
+    #[case("[core\n", 1)]
+    #[case("[core]\n\n\tkey value\n", 3)]
+    #[case("[core]\n\tkey = bad\\escape\n", 2)]
+    #[case("[sec \"sub]\n", 1)]
+    fn test_parse_config_error(#[case] src: &str, #[case] line: usize) {
+        assert_eq!(parse_config(src), Err(line));
+    }
+
+    #[rstest]
+    #[case("**/work/**", "/home/user/work/project/.git", true)]
+    #[case("**/work/**", "/home/user/play/project/.git", false)]
+    #[case("/home/user/work/**", "/home/user/work/project/.git", true)]
+    #[case("/home/user/work/*/.git", "/home/user/work/project/.git", true)]
+    #[case("/home/user/work/*/.git", "/home/user/work/a/b/.git", false)]
+    #[case("/home/user/work/**/.git", "/home/user/work/a/b/.git", true)]
+    #[case("/home/user/work/**/.git", "/home/user/work/.git", true)]
+    #[case("**/.git", "/repo/.git", true)]
+    #[case("**/project/.git", "/home/user/project/.git", true)]
+    #[case("feature/**", "feature/foo/bar", true)]
+    #[case("feature/*", "feature/foo/bar", false)]
+    #[case("feat?re", "feature", true)]
+    #[case("main", "main", true)]
+    #[case("main", "maint", false)]
+    fn test_wildmatch(#[case] pattern: &str, #[case] text: &str, #[case] matches: bool) {
+        assert_eq!(wildmatch(pattern, text, false), matches);
+    }
+
+    #[test]
+    fn test_wildmatch_icase() {
+        assert!(wildmatch(
+            "**/Work/**",
+            "/home/user/work/project/.git",
+            true
+        ));
+        assert!(!wildmatch(
+            "**/Work/**",
+            "/home/user/work/project/.git",
+            false
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+//! A minimal reader for Git index files, used for finding the commits that
+//! submodules are pinned to
+use std::fmt::{self, Write};
+
+/// The file mode of a gitlink (submodule) entry
+const GITLINK_MODE: u32 = 0o160_000;
+
+/// The size of the stat fields (ctime, mtime, dev, ino) preceding an index
+/// entry's mode
+const PRE_MODE_SIZE: usize = 24;
+
+/// The size of the stat fields (uid, gid, size) following an index entry's
+/// mode
+const POST_MODE_SIZE: usize = 12;
+
+/// Flag set on index entries that have an additional 16-bit flags field
+const EXTENDED_FLAG: u16 = 0x4000;
+
+/// Error returned when an index file cannot be parsed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum IndexError {
+    /// The file does not start with the index signature
+    BadSignature,
+
+    /// The index version is not supported
+    UnsupportedVersion(u32),
+
+    /// The file ended in the middle of an entry
+    Truncated,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::BadSignature => write!(f, "bad index file signature"),
+            IndexError::UnsupportedVersion(v) => write!(f, "unsupported index version {v}"),
+            IndexError::Truncated => write!(f, "index file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+/// Parse the contents of an index file and return the path & hex object ID of
+/// each gitlink entry, in index order (i.e., sorted by path).  `hash_len` is
+/// the length in bytes of the repository's object IDs (20 for SHA-1, 32 for
+/// SHA-256).
+pub(crate) fn parse_gitlinks(
+    data: &[u8],
+    hash_len: usize,
+) -> Result<Vec<(String, String)>, IndexError> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.take(4)? != b"DIRC" {
+        return Err(IndexError::BadSignature);
+    }
+    let version = reader.u32()?;
+    if !(2..=4).contains(&version) {
+        return Err(IndexError::UnsupportedVersion(version));
+    }
+    let count = reader.u32()?;
+    let mut gitlinks = Vec::new();
+    let mut prev_path: Vec<u8> = Vec::new();
+    for _ in 0..count {
+        let start = reader.pos;
+        reader.take(PRE_MODE_SIZE)?;
+        let mode = reader.u32()?;
+        reader.take(POST_MODE_SIZE)?;
+        let oid = reader.take(hash_len)?;
+        let flags = reader.u16()?;
+        if version >= 3 && flags & EXTENDED_FLAG != 0 {
+            reader.u16()?;
+        }
+        let path = if version == 4 {
+            // Paths are prefix-compressed against the previous entry's path
+            let strip = reader.varint()?;
+            let keep = prev_path
+                .len()
+                .checked_sub(strip)
+                .ok_or(IndexError::Truncated)?;
+            let mut path = prev_path[..keep].to_vec();
+            path.extend_from_slice(reader.nul_terminated()?);
+            path
+        } else {
+            let path = reader.nul_terminated()?.to_vec();
+            // Entries are padded with NULs to a multiple of eight bytes
+            let len = reader.pos - start;
+            reader.take((8 - len % 8) % 8)?;
+            path
+        };
+        if mode == GITLINK_MODE {
+            gitlinks.push((
+                String::from_utf8_lossy(&path).into_owned(),
+                oid.iter().fold(String::new(), |mut hex, b| {
+                    let _ = write!(hex, "{b:02x}");
+                    hex
+                }),
+            ));
+        }
+        prev_path = path;
+    }
+    Ok(gitlinks)
+}
+
+/// (Private) A cursor over the bytes of an index file
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], IndexError> {
+        let end = self.pos.checked_add(n).ok_or(IndexError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(IndexError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], IndexError> {
+        self.take(N)?.try_into().map_err(|_| IndexError::Truncated)
+    }
+
+    fn u16(&mut self) -> Result<u16, IndexError> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, IndexError> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    /// Read a variable-width integer in the "offset" encoding used by Git
+    fn varint(&mut self) -> Result<usize, IndexError> {
+        let mut byte = self.take(1)?[0];
+        let mut value = usize::from(byte & 0x7F);
+        while byte & 0x80 != 0 {
+            byte = self.take(1)?[0];
+            value = value
+                .checked_add(1)
+                .and_then(|v| v.checked_mul(128))
+                .ok_or(IndexError::Truncated)?
+                | usize::from(byte & 0x7F);
+        }
+        Ok(value)
+    }
+
+    /// Read bytes up to the next NUL, consuming the NUL
+    fn nul_terminated(&mut self) -> Result<&'a [u8], IndexError> {
+        let rest = self.data.get(self.pos..).ok_or(IndexError::Truncated)?;
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(IndexError::Truncated)?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Build an index entry with the given mode, object ID byte, and path
+    fn entry(version: u32, mode: u32, oid_byte: u8, path: &[u8], strip: Option<u8>) -> Vec<u8> {
+        let mut buf = vec![0; 24];
+        buf.extend_from_slice(&mode.to_be_bytes());
+        buf.extend_from_slice(&[0; 12]);
+        buf.extend_from_slice(&[oid_byte; 20]);
+        let namelen = u16::try_from(path.len()).unwrap_or(0xFFF).min(0xFFF);
+        buf.extend_from_slice(&namelen.to_be_bytes());
+        if let Some(strip) = strip {
+            buf.push(strip);
+        }
+        buf.extend_from_slice(path);
+        buf.push(0);
+        if version < 4 {
+            while buf.len() % 8 != 0 {
+                buf.push(0);
+            }
+        }
+        buf
+    }
+
+    fn index(version: u32, entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut buf = b"DIRC".to_vec();
+        buf.extend_from_slice(&version.to_be_bytes());
+        buf.extend_from_slice(&u32::try_from(entries.len()).unwrap().to_be_bytes());
+        for e in entries {
+            buf.extend_from_slice(e);
+        }
+        buf
+    }
+
+    #[rstest]
+    #[case(2)]
+    #[case(3)]
+    fn test_parse_gitlinks(#[case] version: u32) {
+        let data = index(
+            version,
+            &[
+                entry(version, 0o100_644, 0x11, b".gitmodules", None),
+                entry(version, GITLINK_MODE, 0xAB, b"lib/sub", None),
+                entry(version, 0o100_644, 0x22, b"src/main.rs", None),
+                entry(version, GITLINK_MODE, 0xCD, b"vendor/x", None),
+            ],
+        );
+        assert_eq!(
+            parse_gitlinks(&data, 20).unwrap(),
+            [
+                (String::from("lib/sub"), "ab".repeat(20)),
+                (String::from("vendor/x"), "cd".repeat(20)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_gitlinks_v4() {
+        let data = index(
+            4,
+            &[
+                entry(4, 0o100_644, 0x11, b"lib/a.rs", Some(0)),
+                entry(4, GITLINK_MODE, 0xAB, b"sub", Some(4)),
+                entry(4, GITLINK_MODE, 0xCD, b"vendor/x", Some(7)),
+            ],
+        );
+        assert_eq!(
+            parse_gitlinks(&data, 20).unwrap(),
+            [
+                (String::from("lib/sub"), "ab".repeat(20)),
+                (String::from("vendor/x"), "cd".repeat(20)),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(b"DIRX\0\0\0\x02\0\0\0\0", IndexError::BadSignature)]
+    #[case(b"DIRC\0\0\0\x05\0\0\0\0", IndexError::UnsupportedVersion(5))]
+    #[case(b"DIRC\0\0\0\x02\0\0\0\x01\0\0", IndexError::Truncated)]
+    fn test_parse_gitlinks_bad(#[case] data: &[u8], #[case] err: IndexError) {
+        assert_eq!(parse_gitlinks(data, 20), Err(err));
+    }
+}
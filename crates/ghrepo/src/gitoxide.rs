@@ -0,0 +1,284 @@
+//! A [`GitBackend`] built on the [`gix`] crate
+use crate::backend::GitBackend;
+use crate::gitconfig::Config;
+use crate::native;
+use crate::{Description, FileStatus, LocalRepoError, RepoLayout, RepoState, WorkTreeStatus};
+use gix::Repository;
+use gix::discover::upwards;
+use gix::refs::TargetRef;
+use gix::repository::merge_base;
+use gix::sec::trust::Mapping;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A [`GitBackend`] that inspects repositories using [gitoxide] via the
+/// [`gix`] crate, without running Git
+///
+/// [gitoxide]: https://github.com/GitoxideLabs/gitoxide
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gitoxide;
+
+impl Gitoxide {
+    /// (Private) Locate and open the repository containing `path`, not
+    /// searching above any directories listed in `GIT_CEILING_DIRECTORIES`
+    fn open(path: &Path) -> Result<Repository, LocalRepoError> {
+        let options = upwards::Options {
+            ceiling_dirs: native::ceiling_dirs(),
+            match_ceiling_dir_or_error: false,
+            ..upwards::Options::default()
+        };
+        gix::ThreadSafeRepository::discover_opts(path, options, Mapping::default())
+            .map(Repository::from)
+            .map_err(|e| match e {
+                gix::discover::Error::Discover(
+                    upwards::Error::NoGitRepository { .. }
+                    | upwards::Error::NoGitRepositoryWithinCeiling { .. }
+                    | upwards::Error::NoGitRepositoryWithinFs { .. },
+                ) => LocalRepoError::NotARepository(path.to_path_buf()),
+                e => LocalRepoError::BackendError(Box::new(e)),
+            })
+    }
+
+    /// (Private) Load the configuration of the repository containing `path`
+    fn load_config(path: &Path) -> Result<Config, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let snapshot = repo.config_snapshot();
+        let mut config = Config::default();
+        for section in snapshot.plumbing().sections() {
+            let header = section.header();
+            let prefix = match header.subsection_name() {
+                Some(sub) => format!("{}.{sub}", header.name()),
+                None => header.name().to_string(),
+            };
+            for (name, value) in section.body().clone() {
+                config.push(&format!("{prefix}.{name}"), &value.to_string());
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl GitBackend for Gitoxide {
+    fn is_git_repo(&self, path: &Path) -> Result<bool, LocalRepoError> {
+        match Gitoxide::open(path) {
+            Ok(_) => Ok(true),
+            Err(LocalRepoError::NotARepository(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError> {
+        match Gitoxide::open(path) {
+            Ok(repo) => Ok(native::repo_state(
+                path,
+                repo.git_dir(),
+                repo.workdir(),
+                repo.is_bare(),
+            )),
+            Err(LocalRepoError::NotARepository(_)) => Ok(RepoState::NotARepository),
+            Err(e) => Ok(RepoState::Corrupt {
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        Ok(native::repo_layout(
+            path,
+            repo.git_dir(),
+            repo.common_dir(),
+            repo.workdir(),
+            repo.is_bare(),
+        ))
+    }
+
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError> {
+        match self.symbolic_ref(path, "HEAD")? {
+            Some(target) => Ok(target
+                .strip_prefix("refs/heads/")
+                .map_or_else(|| target.clone(), ToString::to_string)),
+            None => Err(LocalRepoError::DetachedHead),
+        }
+    }
+
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError> {
+        Ok(Gitoxide::load_config(path)?
+            .get(key)
+            .map(ToString::to_string))
+    }
+
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        Ok(Gitoxide::load_config(path)?.entries().to_vec())
+    }
+
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError> {
+        Ok(native::remote_names(&Gitoxide::load_config(path)?))
+    }
+
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError> {
+        native::remote_urls(&Gitoxide::load_config(path)?, remote, push)
+            .ok_or_else(|| LocalRepoError::NoSuchRemote(remote.to_string()))
+    }
+
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let reference = repo
+            .try_find_reference(refname)
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))?;
+        Ok(reference.and_then(|r| match r.target() {
+            TargetRef::Symbolic(name) => Some(name.as_bstr().to_string()),
+            TargetRef::Object(_) => None,
+        }))
+    }
+
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let mut head = repo
+            .head()
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))?;
+        if head.is_unborn() {
+            return Ok(None);
+        }
+        let id = head
+            .peel_to_commit()
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))?
+            .id;
+        Ok(Some(id.to_string()))
+    }
+
+    fn file_status(&self, _path: &Path, _file: &str) -> Result<FileStatus, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "comparing files against HEAD",
+        )))
+    }
+
+    fn remote_contains(
+        &self,
+        path: &Path,
+        remote: &str,
+        commit: &str,
+    ) -> Result<bool, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let contains = || -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+            let commit = gix::ObjectId::from_hex(commit.as_bytes())?;
+            let mut tips = Vec::new();
+            for r in repo
+                .references()?
+                .prefixed(format!("refs/remotes/{remote}/").as_str())?
+            {
+                tips.push(r?.peel_to_id()?.detach());
+            }
+            for tip in tips {
+                // `commit` is reachable from `tip` iff it is their merge base
+                match repo.merge_base(commit, tip) {
+                    Ok(base) if base.detach() == commit => return Ok(true),
+                    Ok(_) | Err(merge_base::Error::NotFound { .. }) => (),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Ok(false)
+        };
+        contains().map_err(LocalRepoError::BackendError)
+    }
+
+    fn tags_at(&self, path: &Path, commit: &str) -> Result<Vec<String>, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let tags = || -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+            let commit = gix::ObjectId::from_hex(commit.as_bytes())?;
+            let mut tags = Vec::new();
+            for r in repo.references()?.tags()? {
+                let mut r = r?;
+                let name = r.name().shorten().to_string();
+                if r.peel_to_id().is_ok_and(|id| id == commit) {
+                    tags.push(name);
+                }
+            }
+            tags.sort();
+            Ok(tags)
+        };
+        tags().map_err(LocalRepoError::BackendError)
+    }
+
+    fn describe(&self, _path: &Path) -> Result<Option<Description>, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "walking commit history",
+        )))
+    }
+
+    fn status(&self, _path: &Path) -> Result<WorkTreeStatus, LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "comparing the working tree against HEAD",
+        )))
+    }
+
+    fn shares_history(
+        &self,
+        path: &Path,
+        remote1: &str,
+        remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError> {
+        let repo = Gitoxide::open(path)?;
+        let tips =
+            |remote: &str| -> Result<Vec<gix::ObjectId>, Box<dyn std::error::Error + Send + Sync>> {
+                let mut tips = Vec::new();
+                for r in repo
+                    .references()?
+                    .prefixed(format!("refs/remotes/{remote}/").as_str())?
+                {
+                    tips.push(r?.peel_to_id()?.detach());
+                }
+                Ok(tips)
+            };
+        let shares = || -> Result<Option<bool>, Box<dyn std::error::Error + Send + Sync>> {
+            let (tips1, tips2) = (tips(remote1)?, tips(remote2)?);
+            if tips1.is_empty() || tips2.is_empty() {
+                return Ok(None);
+            }
+            let mut ancestors = HashSet::new();
+            for info in repo.rev_walk(tips1).all()? {
+                ancestors.insert(info?.id);
+            }
+            for info in repo.rev_walk(tips2).all()? {
+                if ancestors.contains(&info?.id) {
+                    return Ok(Some(true));
+                }
+            }
+            Ok(Some(false))
+        };
+        shares().map_err(LocalRepoError::BackendError)
+    }
+
+    fn set_remote_url(
+        &self,
+        _path: &Path,
+        _remote: &str,
+        _url: &str,
+    ) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+
+    fn add_remote(&self, _path: &Path, _name: &str, _url: &str) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+
+    fn add_fetch_refspec(
+        &self,
+        _path: &Path,
+        _remote: &str,
+        _refspec: &str,
+    ) -> Result<(), LocalRepoError> {
+        Err(LocalRepoError::Unsupported(String::from(
+            "modifying configuration",
+        )))
+    }
+}
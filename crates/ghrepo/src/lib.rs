@@ -4,16 +4,27 @@
 //! `REPONAME`), and the resulting object provides properties for going in
 //! reverse to determine the possible URLs.  Also included is a struct for
 //! performing a couple useful inspections on local Git repositories, including
-//! determining the corresponding GitHub owner & repository name.
+//! determining the corresponding GitHub owner & repository name.  These
+//! inspections are performed by running Git by default, but other backends
+//! can be plugged in; see [`GitBackend`].
 //!
 //! Features
 //! ========
 //!
-//! The `ghrepo` crate has the following optional feature:
+//! The `ghrepo` crate has the following optional features:
+//!
+//! - `git2` — Enables the `LibGit2` backend for `LocalRepo`, which uses the
+//!   [`git2`](https://docs.rs/git2) crate
+//!
+//! - `gix` — Enables the `Gitoxide` backend for `LocalRepo`, which uses the
+//!   [`gix`](https://docs.rs/gix) crate
 //!
 //! - `serde` — Enables serializing & deserializing the `GHRepo` type with
 //!   [`serde`]
 //!
+//! - `tokio` — Enables `AsyncLocalRepo`, an asynchronous variant of
+//!   `LocalRepo` that runs Git with [`tokio`](https://docs.rs/tokio)
+//!
 //! Example
 //! =======
 //!
@@ -37,17 +48,55 @@
 //! # }
 //! ```
 
+#[cfg(feature = "tokio")]
+mod async_repo;
+mod backend;
 mod deser;
+mod fake;
+mod gitconfig;
+mod gitindex;
+#[cfg(feature = "gix")]
+mod gitoxide;
+#[cfg(feature = "git2")]
+mod libgit2;
+mod native;
 mod parser;
-use crate::parser::{parse_github_url, split_name, split_owner, split_owner_name};
+mod scan;
+mod sshconfig;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use crate::async_repo::AsyncLocalRepo;
+pub use crate::backend::{GitBackend, GitCommand, NativeGit};
+pub use crate::fake::FakeGit;
+use crate::gitconfig::Config;
+pub use crate::gitconfig::ConfigError;
+#[cfg(feature = "gix")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gix")))]
+pub use crate::gitoxide::Gitoxide;
+#[cfg(feature = "git2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "git2")))]
+pub use crate::libgit2::LibGit2;
+use crate::parser::{
+    parse_any_host_url, parse_github_url, split_name, split_owner, split_owner_name, split_ssh_url,
+};
+pub use crate::scan::{DEFAULT_SKIP_DIRS, ScannedRepo, Scanner};
+pub use crate::sshconfig::{SshConfig, SshConfigError};
 use std::cmp::Ordering;
 use std::env;
 use std::error;
-use std::fmt;
+use std::ffi::OsString;
+use std::fmt::{self, Write};
+use std::fs;
 use std::io;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus, Stdio};
+use std::process::ExitStatus;
 use std::str::{self, FromStr};
+use std::time::Duration;
+
+/// The name of the environment variable used by the official GitHub CLI to
+/// override the current repository; see [`GHRepo::from_gh_repo_env()`]
+pub const GH_REPO_ENV: &str = "GH_REPO";
 
 /// Error returned when trying to construct a [`GHRepo`] with invalid arguments
 /// or parse an invalid repository spec
@@ -67,6 +116,10 @@ pub enum ParseError {
     /// Returned by [`GHRepo::new`] if given an invalid GitHub repository name;
     /// the field is the name in question.
     InvalidName(String),
+
+    /// Returned as the [`Remote::repo`] or [`Submodule::repo`] field for a
+    /// remote or submodule that does not have a URL configured
+    NoUrl,
 }
 
 impl fmt::Display for ParseError {
@@ -75,6 +128,7 @@ impl fmt::Display for ParseError {
             ParseError::InvalidSpec(s) => write!(f, "invalid GitHub repository spec: {s:?}"),
             ParseError::InvalidOwner(s) => write!(f, "invalid GitHub repository owner: {s:?}"),
             ParseError::InvalidName(s) => write!(f, "invalid GitHub repository name: {s:?}"),
+            ParseError::NoUrl => write!(f, "no URL configured"),
         }
     }
 }
@@ -206,6 +260,38 @@ impl GHRepo {
         format!("https://github.com/{}", self.fullname)
     }
 
+    /// Returns the URL for the web view of the repository's file tree at the
+    /// given branch, tag, or commit
+    pub fn tree_url(&self, reference: &str) -> String {
+        format!("https://github.com/{}/tree/{reference}", self.fullname)
+    }
+
+    /// Returns the URL for the web view of the given file in the repository
+    /// at the given branch, tag, or commit.  `path` must be a `/`-separated
+    /// path relative to the root of the repository; characters in it that
+    /// are not allowed in URL paths are percent-encoded.
+    pub fn blob_url(&self, reference: &str, path: &str) -> String {
+        format!(
+            "https://github.com/{}/blob/{reference}/{}",
+            self.fullname,
+            percent_encode_path(path)
+        )
+    }
+
+    /// Returns the URL for the web view of the given commit
+    pub fn commit_url(&self, commit: &str) -> String {
+        format!("https://github.com/{}/commit/{commit}", self.fullname)
+    }
+
+    /// Returns the URL for the release page of the given tag
+    pub fn release_url(&self, tag: &str) -> String {
+        format!(
+            "https://github.com/{}/releases/tag/{}",
+            self.fullname,
+            percent_encode_path(tag)
+        )
+    }
+
     /// Returns the URL for cloning the repository over SSH
     pub fn ssh_url(&self) -> String {
         format!("git@github.com:{}.git", self.fullname)
@@ -219,6 +305,7 @@ impl GHRepo {
     /// - `git://github.com/<owner>/<name>[.git]`
     /// - `git@github.com:<owner>/<name>[.git]`
     /// - `ssh://git@github.com/<owner>/<name>[.git]`
+    /// - `ssh://git@ssh.github.com:443/<owner>/<name>[.git]`
     ///
     /// # Errors
     ///
@@ -230,6 +317,157 @@ impl GHRepo {
             None => Err(ParseError::InvalidSpec(s.to_string())),
         }
     }
+
+    /// Like [`GHRepo::from_url()`], except that SSH URLs whose host is an
+    /// alias for `github.com` (or `ssh.github.com`) in the given SSH
+    /// configuration are also accepted.  For example, if `ssh_config` maps
+    /// the host `github-work` to the hostname `github.com`, then
+    /// `git@github-work:octocat/repository.git` is parsed as
+    /// `octocat/repository`.  The user for the URL must be `git`, either as
+    /// given in the URL or as set with the `User` option in `ssh_config`.
+    ///
+    /// The returned [`ResolvedRepo`] reports the alias used, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the given URL is neither in a format
+    /// accepted by [`GHRepo::from_url()`] nor an SSH URL for an alias of a
+    /// GitHub host
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # use ghrepo::{GHRepo, SshConfig};
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let ssh_config = "Host github-work\n    HostName github.com\n".parse::<SshConfig>()?;
+    /// let resolved = GHRepo::from_url_with_ssh_config(
+    ///     "git@github-work:octocat/repository.git",
+    ///     &ssh_config,
+    /// )?;
+    /// assert_eq!(resolved.repo, GHRepo::new("octocat", "repository")?);
+    /// assert_eq!(resolved.ssh_alias.as_deref(), Some("github-work"));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_url_with_ssh_config(
+        s: &str,
+        ssh_config: &SshConfig,
+    ) -> Result<ResolvedRepo, ParseError> {
+        if let Some((owner, name)) = parse_github_url(s) {
+            return Ok(ResolvedRepo {
+                repo: GHRepo::new(owner, name)?,
+                ssh_alias: None,
+            });
+        }
+        let invalid = || ParseError::InvalidSpec(s.to_string());
+        let url = split_ssh_url(s).ok_or_else(invalid)?;
+        let hostname = ssh_config.hostname(url.host);
+        if !["github.com", "ssh.github.com"]
+            .iter()
+            .any(|h| hostname.eq_ignore_ascii_case(h))
+        {
+            return Err(invalid());
+        }
+        let user = match url.user {
+            Some(user) => Some(user.to_string()),
+            None => ssh_config.user(url.host),
+        };
+        if user.as_deref() != Some("git") {
+            return Err(invalid());
+        }
+        match parse_github_url(&format!("git@github.com:{}", url.path)) {
+            Some((owner, name)) => Ok(ResolvedRepo {
+                repo: GHRepo::new(owner, name)?,
+                ssh_alias: Some(url.host.to_string()),
+            }),
+            None => Err(invalid()),
+        }
+    }
+
+    /// Parse the repository specified by the `GH_REPO` environment variable,
+    /// which the official GitHub CLI uses to override the repository for the
+    /// current directory.
+    ///
+    /// The variable may be set to any string accepted by
+    /// [`GHRepo::from_str()`], including strings of the form
+    /// `[{host}/]{owner}/{name}` where `{host}` is `github.com`.  If the
+    /// variable is unset or empty, `Ok(None)` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the variable is set to a string (or
+    /// non-UTF-8 value) that is not a valid repository specifier
+    pub fn from_gh_repo_env() -> Result<Option<Self>, ParseError> {
+        match env::var_os(GH_REPO_ENV) {
+            Some(value) if !value.is_empty() => match value.to_str() {
+                Some(s) => GHRepo::from_str(s).map(Some),
+                None => Err(ParseError::InvalidSpec(
+                    value.to_string_lossy().into_owned(),
+                )),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Clone the repository into the directory `dir` with `git clone` and
+    /// return a [`LocalRepo`] for the clone.  The `origin` remote's URL is
+    /// the URL for the repository in the style set with
+    /// [`CloneOptions::style()`]; if no style is set, it is determined by the
+    /// `ghrepo.protocol` Git config setting, which may be `https` (the
+    /// default), `ssh`, or `ssh-443`.  Only the system, global, and
+    /// command-line configuration is consulted for this setting, not that of
+    /// any repository containing the current directory.
+    ///
+    /// The returned [`LocalRepo`] runs Git with the options set with
+    /// [`CloneOptions::config()`].
+    ///
+    /// If an upstream repository is set with [`CloneOptions::upstream()`], it
+    /// is added to the clone as the `upstream` remote in the same URL style.
+    /// The new remote is not fetched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::InvalidProtocol`] if `ghrepo.protocol` has
+    /// an unrecognized value, [`LocalRepoError::InvalidHost`] if the style's
+    /// GitHub Enterprise Server host is invalid,
+    /// [`LocalRepoError::CommandFailed`] if `git
+    /// clone` fails (e.g., because `dir` already exists and is not empty), or
+    /// another [`LocalRepoError`] if a Git command fails to execute or the
+    /// upstream remote could not be added
+    pub fn clone_into<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        options: &CloneOptions,
+    ) -> Result<LocalRepo, LocalRepoError> {
+        let dir = dir.as_ref();
+        let abs_dir = std::path::absolute(dir).map_err(LocalRepoError::CurdirError)?;
+        let style = match &options.style {
+            Some(style) => style.clone(),
+            None => match options
+                .git
+                .clone_config_get(&abs_dir, "ghrepo.protocol")?
+                .as_deref()
+            {
+                None | Some("https") => UrlStyle::Https,
+                Some("ssh") => UrlStyle::Ssh,
+                Some("ssh-443") => UrlStyle::Ssh443,
+                Some(value) => return Err(LocalRepoError::InvalidProtocol(value.to_string())),
+            },
+        };
+        style.check_host()?;
+        options.git.clone_repo(
+            &style.url(self),
+            &abs_dir,
+            options.depth,
+            options.branch.as_deref(),
+        )?;
+        let lr = LocalRepo::with_backend(dir, options.git.clone());
+        if let Some(upstream) = &options.upstream {
+            lr.add_github_remote("upstream", upstream, &style)?;
+        }
+        Ok(lr)
+    }
 }
 
 impl From<GHRepo> for String {
@@ -304,7 +542,9 @@ impl FromStr for GHRepo {
 
     /// Parse a GitHub repository specifier.  This can be either a URL (as
     /// accepted by [`GHRepo::from_url()`]) or a string in the form
-    /// `{owner}/{name}`.
+    /// `{owner}/{name}`.  As a special case of the former, strings of the form
+    /// `{host}/{owner}/{name}` (as accepted by the official GitHub CLI) are
+    /// accepted as long as `{host}` is `github.com`.
     ///
     /// # Errors
     ///
@@ -352,12 +592,20 @@ impl TryFrom<String> for GHRepo {
 /// repository, generally with the goal of determining the GitHub repository
 /// that it's a clone of.
 ///
-/// The custom methods all require Git to be installed in order to work.  I am
-/// not certain of the minimal viable Git version, but they should work with
-/// any Git as least as far back as version 1.7.
+/// The repository is inspected via a [`GitBackend`].  By default, this is
+/// [`GitCommand`], which requires Git 2.13 or later to be installed in order
+/// to work.  A different backend can be selected by constructing a
+/// `LocalRepo` with [`LocalRepo::with_backend()`].
+///
+/// A `LocalRepo` created with [`LocalRepo::discover()`] additionally records
+/// the locations of the repository's top-level directory & Git directories and
+/// of the given directory within the working tree.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct LocalRepo {
+pub struct LocalRepo<B = GitCommand> {
     path: PathBuf,
+    backend: B,
+    ssh_config: Option<SshConfig>,
+    layout: Option<RepoLayout>,
 }
 
 impl LocalRepo {
@@ -367,8 +615,17 @@ impl LocalRepo {
     /// No validation is done as to whether `dirpath` is a Git repository or
     /// even an extant directory.
     pub fn new<P: AsRef<Path>>(dirpath: P) -> Self {
-        LocalRepo {
+        LocalRepo::with_backend(dirpath, GitCommand::default())
+    }
+
+    /// Create a [`LocalRepoBuilder`] for constructing a [`LocalRepo`] for the
+    /// repository at or containing the directory `dirpath` that runs Git with
+    /// custom settings
+    pub fn builder<P: AsRef<Path>>(dirpath: P) -> LocalRepoBuilder {
+        LocalRepoBuilder {
             path: dirpath.as_ref().to_path_buf(),
+            git: GitCommand::default(),
+            ssh_config: None,
         }
     }
 
@@ -383,9 +640,66 @@ impl LocalRepo {
     ///
     /// Returns a [`LocalRepoError`] if [`std::env::current_dir()`] failed
     pub fn for_cwd() -> Result<Self, LocalRepoError> {
-        Ok(LocalRepo {
-            path: env::current_dir().map_err(LocalRepoError::CurdirError)?,
-        })
+        Ok(LocalRepo::new(
+            env::current_dir().map_err(LocalRepoError::CurdirError)?,
+        ))
+    }
+
+    /// Locate the repository containing the directory `dirpath` and create a
+    /// [`LocalRepo`] for it that records the repository's layout, which can
+    /// then be queried with [`LocalRepo::toplevel()`],
+    /// [`LocalRepo::git_dir()`], [`LocalRepo::common_dir()`], and
+    /// [`LocalRepo::prefix()`].
+    ///
+    /// As with Git, the search for the repository does not go above any
+    /// directories listed in the `GIT_CEILING_DIRECTORIES` environment
+    /// variable.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ghrepo::LocalRepo;
+    /// let lr = LocalRepo::discover("/path/to/repo/src/bin").unwrap();
+    /// assert_eq!(lr.toplevel().unwrap(), "/path/to/repo");
+    /// assert_eq!(lr.prefix().unwrap(), "src/bin");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NotARepository`] if `dirpath` is not in a Git
+    /// repository, or another [`LocalRepoError`] if the Git command failed to
+    /// execute or the repository could not be inspected
+    pub fn discover<P: AsRef<Path>>(dirpath: P) -> Result<Self, LocalRepoError> {
+        LocalRepo::new(dirpath).discovered()
+    }
+}
+
+impl<B> LocalRepo<B> {
+    /// Create a [`LocalRepo`] for operating on the repository at or containing
+    /// the directory `dirpath` using the given backend.
+    ///
+    /// No validation is done as to whether `dirpath` is a Git repository or
+    /// even an extant directory.
+    pub fn with_backend<P: AsRef<Path>>(dirpath: P, backend: B) -> Self {
+        LocalRepo {
+            path: dirpath.as_ref().to_path_buf(),
+            backend,
+            ssh_config: None,
+            layout: None,
+        }
+    }
+
+    /// Use the given SSH configuration to resolve host aliases in remote URLs,
+    /// so that remotes with URLs like `git@github-work:octocat/repository.git`
+    /// are recognized as GitHub repositories when `github-work` is an alias
+    /// for `github.com`.  See [`GHRepo::from_url_with_ssh_config()`] for
+    /// details.
+    ///
+    /// By default, a `LocalRepo` does not resolve SSH host aliases.
+    #[must_use]
+    pub fn with_ssh_config(mut self, ssh_config: SshConfig) -> Self {
+        self.ssh_config = Some(ssh_config);
+        self
     }
 
     /// Returns the path that was given to [`LocalRepo::new()`] or obtained by
@@ -394,37 +708,355 @@ impl LocalRepo {
         self.path.as_path()
     }
 
+    /// Returns the backend used to inspect the repository
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the layout of the repository as determined when the
+    /// `LocalRepo` was created, or `None` if the `LocalRepo` was not created
+    /// with [`LocalRepo::discover()`] or a similar method
+    pub fn layout(&self) -> Option<&RepoLayout> {
+        self.layout.as_ref()
+    }
+
+    /// Returns the top-level directory of the repository's working tree.
+    /// Returns `None` if the `LocalRepo` was not created with
+    /// [`LocalRepo::discover()`] or a similar method or if the directory is
+    /// not in a working tree (e.g., because the repository is bare).
+    pub fn toplevel(&self) -> Option<&Path> {
+        self.layout.as_ref()?.toplevel.as_deref()
+    }
+
+    /// Returns the repository's Git directory.  Returns `None` if the
+    /// `LocalRepo` was not created with [`LocalRepo::discover()`] or a similar
+    /// method.
+    pub fn git_dir(&self) -> Option<&Path> {
+        Some(&self.layout.as_ref()?.git_dir)
+    }
+
+    /// Returns the directory containing the repository's shared files, which
+    /// differs from [`LocalRepo::git_dir()`] for linked worktrees.  Returns
+    /// `None` if the `LocalRepo` was not created with
+    /// [`LocalRepo::discover()`] or a similar method.
+    pub fn common_dir(&self) -> Option<&Path> {
+        Some(&self.layout.as_ref()?.common_dir)
+    }
+
+    /// Returns the path of the directory that the `LocalRepo` was created for
+    /// relative to the top-level directory of the working tree.  This is empty
+    /// if the directory is the top-level directory or is not in a working
+    /// tree.  Returns `None` if the `LocalRepo` was not created with
+    /// [`LocalRepo::discover()`] or a similar method.
+    pub fn prefix(&self) -> Option<&Path> {
+        Some(&self.layout.as_ref()?.prefix)
+    }
+}
+
+impl<B: GitBackend> LocalRepo<B> {
+    /// Locate the repository containing the directory `dirpath` and create a
+    /// [`LocalRepo`] for it that uses the given backend and records the
+    /// repository's layout; see [`LocalRepo::discover()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NotARepository`] if `dirpath` is not in a Git
+    /// repository, or another [`LocalRepoError`] if the repository could not
+    /// be inspected
+    pub fn discover_with_backend<P: AsRef<Path>>(
+        dirpath: P,
+        backend: B,
+    ) -> Result<Self, LocalRepoError> {
+        LocalRepo::with_backend(dirpath, backend).discovered()
+    }
+
+    /// (Private) Determine the layout of the repository and record it in the
+    /// `LocalRepo`
+    fn discovered(mut self) -> Result<Self, LocalRepoError> {
+        self.layout = Some(self.backend.layout(&self.path)?);
+        Ok(self)
+    }
+
     /// Tests whether the directory is either a Git repository or contained in
     /// one
     ///
     /// # Errors
     ///
     /// Returns a [`LocalRepoError`] if the invoked Git commit fails to execute
+    /// or, when using a backend other than [`GitCommand`], if the repository
+    /// could not be inspected
     pub fn is_git_repo(&self) -> Result<bool, LocalRepoError> {
-        Ok(Command::new("git")
-            .args(["rev-parse", "--git-dir"])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .current_dir(&self.path)
-            .status()
-            .map_err(LocalRepoError::CouldNotExecute)?
-            .success())
-    }
-
-    /// (Private) Run a Git command in the local repository and return the
-    /// trimmed output
-    fn read(&self, args: &[&str]) -> Result<String, LocalRepoError> {
-        let out = Command::new("git")
-            .args(args)
-            .current_dir(&self.path)
-            .stderr(Stdio::inherit())
-            .output()
-            .map_err(LocalRepoError::CouldNotExecute)?;
-        if out.status.success() {
-            Ok(str::from_utf8(&out.stdout)?.trim().to_string())
-        } else {
-            Err(LocalRepoError::CommandFailed(out.status))
+        self.backend.is_git_repo(&self.path)
+    }
+
+    /// Determine whether the directory is in a Git repository and, if so,
+    /// what kind of location it is and where the repository's files are.
+    /// Unlike [`LocalRepo::is_git_repo()`], this distinguishes between
+    /// directories that are not in a repository at all and repositories that
+    /// could not be used due to ownership checks or corruption.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ghrepo::{LocalRepo, RepoState};
+    /// match LocalRepo::for_cwd().unwrap().inspect().unwrap() {
+    ///     RepoState::NotARepository => eprintln!("Not in a Git repository"),
+    ///     RepoState::UnsafeOwnership { path } => eprintln!(
+    ///         "Run `git config --global --add safe.directory {}`",
+    ///         path.display()
+    ///     ),
+    ///     RepoState::Corrupt { message } => eprintln!("Repository is broken: {message}"),
+    ///     state => println!("Git directory: {}", state.git_dir().unwrap().display()),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute
+    pub fn inspect(&self) -> Result<RepoState, LocalRepoError> {
+        self.backend.inspect(&self.path)
+    }
+
+    /// Tests whether the directory is in a bare repository.  Returns `false`
+    /// if the directory is not in a repository; use [`LocalRepo::inspect()`]
+    /// to tell these cases apart.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute
+    pub fn is_bare(&self) -> Result<bool, LocalRepoError> {
+        Ok(matches!(self.inspect()?, RepoState::Bare { .. }))
+    }
+
+    /// Tests whether the directory is in a linked worktree (i.e., one created
+    /// with `git worktree add`) rather than the main working tree or a bare
+    /// repository
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NotARepository`] if the directory is not in a
+    /// Git repository, or another [`LocalRepoError`] if the invoked Git
+    /// command fails to execute or the repository could not be inspected
+    pub fn is_linked_worktree(&self) -> Result<bool, LocalRepoError> {
+        let layout = self.backend.layout(&self.path)?;
+        Ok(layout.git_dir != layout.common_dir)
+    }
+
+    /// Returns the repository's working trees, starting with the main working
+    /// tree (or, for a bare repository, the repository itself) and followed by
+    /// any linked worktrees sorted by path
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute or the repository could not be inspected
+    pub fn worktrees(&self) -> Result<Vec<Worktree>, LocalRepoError> {
+        self.backend.worktrees(&self.path)
+    }
+
+    /// Returns the submodules declared in the `.gitmodules` file at the top
+    /// level of the working tree, sorted by path.  Each submodule's URL is
+    /// parsed as a GitHub repository URL; relative URLs (e.g.,
+    /// `../other.git`) are resolved against the GitHub repository of the
+    /// superproject's default remote (the remote of the current branch, or
+    /// `origin`).  The commit that each submodule is pinned to is read from
+    /// the index.
+    ///
+    /// If the repository does not have a working tree or a `.gitmodules`
+    /// file, an empty list is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to
+    /// execute, the repository could not be inspected, or `.gitmodules` or
+    /// the index could not be read.  If a submodule's URL does not resolve to
+    /// a GitHub repository, the `repo` field of the corresponding
+    /// [`Submodule`] will be an `Err`.
+    pub fn submodules(&self) -> Result<Vec<Submodule>, LocalRepoError> {
+        let Some(toplevel) = self.backend.layout(&self.path)?.toplevel else {
+            return Ok(Vec::new());
+        };
+        let mut decls: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        for (key, value) in native::read_gitmodules(&toplevel.join(".gitmodules"))? {
+            let Some((name, var)) = key
+                .strip_prefix("submodule.")
+                .and_then(|s| s.rsplit_once('.'))
+            else {
+                continue;
+            };
+            match decls.iter_mut().find(|(n, _)| n == name) {
+                Some((_, vars)) => vars.push((var.to_string(), value)),
+                None => decls.push((name.to_string(), vec![(var.to_string(), value)])),
+            }
         }
+        if decls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let gitlinks = self.backend.gitlinks(&self.path)?;
+        let mut superproject = None;
+        let mut submodules = Vec::new();
+        for (name, vars) in decls {
+            let get = |var: &str| {
+                vars.iter()
+                    .rev()
+                    .find(|(v, _)| v == var)
+                    .map(|(_, value)| value.clone())
+            };
+            let Some(path) = get("path") else {
+                continue;
+            };
+            let url = get("url");
+            let repo = match url.as_deref() {
+                Some(u) if u.starts_with("./") || u.starts_with("../") => {
+                    if superproject.is_none() {
+                        superproject = Some(self.superproject_repo()?);
+                    }
+                    match &superproject {
+                        Some(Ok(base)) => resolve_relative_url(base, u),
+                        _ => Err(ParseError::InvalidSpec(u.to_string())),
+                    }
+                }
+                Some(u) => self.parse_url(u).map(|resolved| resolved.repo),
+                None => Err(ParseError::NoUrl),
+            };
+            let commit = gitlinks
+                .iter()
+                .find(|(p, _)| *p == path)
+                .map(|(_, commit)| commit.clone());
+            submodules.push(Submodule {
+                name,
+                path,
+                url,
+                repo,
+                branch: get("branch"),
+                commit,
+            });
+        }
+        submodules.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(submodules)
+    }
+
+    /// (Private) Determine the GitHub repository that relative submodule URLs
+    /// are resolved against
+    fn superproject_repo(&self) -> Result<Result<GHRepo, ParseError>, LocalRepoError> {
+        let remote = self.branch_remote_or_origin()?;
+        match self.remote_urls(&remote, false) {
+            Ok(urls) => Ok(match urls.first() {
+                Some(url) => self.parse_url(url).map(|resolved| resolved.repo),
+                None => Err(ParseError::NoUrl),
+            }),
+            Err(LocalRepoError::NoSuchRemote(_)) => Ok(Err(ParseError::InvalidSpec(remote))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns a permalink to the given file (and, optionally, range of lines)
+    /// on GitHub at the commit that `HEAD` points to, using the GitHub
+    /// repository of the current branch's remote (or `origin`).  A relative
+    /// `file` is resolved against the `LocalRepo`'s path.  See
+    /// [`LocalRepo::remote_permalink()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// See [`LocalRepo::remote_permalink()`].
+    pub fn permalink<P: AsRef<Path>>(
+        &self,
+        file: P,
+        lines: Option<RangeInclusive<usize>>,
+    ) -> Result<Permalink, LocalRepoError> {
+        let remote = self.branch_remote_or_origin()?;
+        self.remote_permalink(&remote, file, lines)
+    }
+
+    /// Returns a permalink to the given file (and, optionally, range of lines)
+    /// on GitHub at the commit that `HEAD` points to, using the GitHub
+    /// repository of the given remote.  A relative `file` is resolved against
+    /// the `LocalRepo`'s path.
+    ///
+    /// The link is returned even if it may not work, in which case the
+    /// returned [`Permalink`] lists the reasons in its `warnings` field: the
+    /// file is untracked, the file has uncommitted changes, or `HEAD` is not
+    /// contained in any of the remote's remote-tracking branches.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::ReadError`] if the file does not exist,
+    /// [`LocalRepoError::NotInWorkTree`] if it is not inside the repository's
+    /// working tree, [`LocalRepoError::NoHeadCommit`] if `HEAD` does not point
+    /// to a commit, [`LocalRepoError::Unsupported`] if the backend cannot
+    /// check the file's status or the remote's branches, or another
+    /// [`LocalRepoError`] if an invoked Git command fails, the remote does not
+    /// exist, or the remote's URL is not a GitHub URL.
+    pub fn remote_permalink<P: AsRef<Path>>(
+        &self,
+        remote: &str,
+        file: P,
+        lines: Option<RangeInclusive<usize>>,
+    ) -> Result<Permalink, LocalRepoError> {
+        let file = self.path.join(file);
+        let abspath =
+            fs::canonicalize(&file).map_err(|e| LocalRepoError::ReadError(file.clone(), e))?;
+        let path = self
+            .backend
+            .layout(&self.path)?
+            .toplevel
+            .and_then(|toplevel| {
+                let toplevel = fs::canonicalize(&toplevel).unwrap_or(toplevel);
+                let relpath = abspath.strip_prefix(toplevel).ok()?;
+                relpath
+                    .iter()
+                    .map(|c| c.to_str())
+                    .collect::<Option<Vec<_>>>()
+                    .map(|parts| parts.join("/"))
+            })
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| LocalRepoError::NotInWorkTree(file.clone()))?;
+        let commit = self
+            .backend
+            .head_commit(&self.path)?
+            .ok_or(LocalRepoError::NoHeadCommit)?;
+        let repo = self.github_remote(remote)?;
+        let mut warnings = Vec::new();
+        match self.backend.file_status(&self.path, &path)? {
+            FileStatus::Unmodified => (),
+            FileStatus::Modified => warnings.push(PermalinkWarning::Modified),
+            FileStatus::Untracked => warnings.push(PermalinkWarning::Untracked),
+        }
+        if !self.backend.remote_contains(&self.path, remote, &commit)? {
+            warnings.push(PermalinkWarning::NotPushed(remote.to_string()));
+        }
+        let fragment = match lines {
+            Some(lines) if lines.start() == lines.end() => format!("#L{}", lines.start()),
+            Some(lines) => format!("#L{}-L{}", lines.start(), lines.end()),
+            None => String::new(),
+        };
+        let url = format!("{}{fragment}", repo.blob_url(&commit, &path));
+        Ok(Permalink {
+            url,
+            repo,
+            commit,
+            path,
+            warnings,
+        })
+    }
+
+    /// (Private) Return the name of the remote of the current branch, or
+    /// `origin` if the branch has no remote or `HEAD` is detached
+    fn branch_remote_or_origin(&self) -> Result<String, LocalRepoError> {
+        let remote = match self.current_branch() {
+            Ok(branch) => self.config_get(&format!("branch.{branch}.remote"))?,
+            Err(LocalRepoError::DetachedHead) => None,
+            Err(e) => return Err(e),
+        };
+        Ok(remote.unwrap_or_else(|| String::from("origin")))
+    }
+
+    /// (Private) Get the value of the given Git config key, returning `None`
+    /// if it is not set
+    fn config_get(&self, key: &str) -> Result<Option<String>, LocalRepoError> {
+        self.backend.config_get(&self.path, key)
     }
 
     /// Get the current branch of the repository
@@ -435,17 +1067,108 @@ impl LocalRepo {
     /// or returns a nonzero status, if the command's output is invalid UTF-8,
     /// or if the repository is in a detached `HEAD` state
     pub fn current_branch(&self) -> Result<String, LocalRepoError> {
-        match self.read(&["symbolic-ref", "--short", "-q", "HEAD"]) {
-            Err(LocalRepoError::CommandFailed(rc)) if rc.code() == Some(1) => {
-                Err(LocalRepoError::DetachedHead)
-            }
-            r => r,
-        }
+        self.backend.current_branch(&self.path)
+    }
+
+    /// Returns whether the repository is in a detached `HEAD` state, i.e.,
+    /// whether `HEAD` points directly to a commit rather than to a branch
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git command fails to
+    /// execute or the repository could not be inspected
+    pub fn is_detached(&self) -> Result<bool, LocalRepoError> {
+        Ok(self.backend.symbolic_ref(&self.path, "HEAD")?.is_none())
+    }
+
+    /// Returns the hash of the commit that `HEAD` points to
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoHeadCommit`] if `HEAD` is an unborn branch
+    /// (as in a repository without any commits), or another
+    /// [`LocalRepoError`] if the invoked Git command fails to execute or the
+    /// repository could not be inspected
+    pub fn head_commit(&self) -> Result<String, LocalRepoError> {
+        self.backend
+            .head_commit(&self.path)?
+            .ok_or(LocalRepoError::NoHeadCommit)
+    }
+
+    /// Returns the names of the tags that point to the `HEAD` commit (either
+    /// directly or via annotated tags), sorted by name
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoHeadCommit`] if `HEAD` is an unborn branch,
+    /// [`LocalRepoError::Unsupported`] if the backend cannot peel annotated
+    /// tags, or another [`LocalRepoError`] if the invoked Git command fails to
+    /// execute or the repository could not be inspected
+    pub fn head_tags(&self) -> Result<Vec<String>, LocalRepoError> {
+        let commit = self.head_commit()?;
+        self.backend.tags_at(&self.path, &commit)
+    }
+
+    /// Describes the `HEAD` commit relative to the most recent tag reachable
+    /// from it, as with `git describe --tags`.  Returns `None` if no tag is
+    /// reachable from `HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoHeadCommit`] if `HEAD` is an unborn branch,
+    /// [`LocalRepoError::Unsupported`] if the backend cannot walk commit
+    /// history, or another [`LocalRepoError`] if the invoked Git command fails
+    /// to execute or the repository could not be inspected
+    pub fn describe(&self) -> Result<Option<Description>, LocalRepoError> {
+        self.backend.describe(&self.path)
+    }
+
+    /// Summarizes the state of the current branch & working tree: the branch
+    /// name, its upstream, how many commits it is ahead of & behind the
+    /// upstream, and the numbers of staged, unstaged, untracked, & conflicted
+    /// files.  This is intended for uses such as shell prompts, and so a
+    /// missing or non-GitHub upstream is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::Unsupported`] if the backend cannot examine
+    /// the working tree, or another [`LocalRepoError`] if an invoked Git
+    /// command fails to execute or the repository could not be inspected
+    pub fn status_summary(&self) -> Result<StatusSummary, LocalRepoError> {
+        let status = self.backend.status(&self.path)?;
+        let upstream = match status.branch.as_deref() {
+            Some(branch) => match self.upstream_from_config(&self.config()?, branch) {
+                Ok(upstream) => Some(upstream),
+                Err(
+                    LocalRepoError::NoUpstream(_)
+                    | LocalRepoError::NoSuchRemote(_)
+                    | LocalRepoError::InvalidRemoteURL(_),
+                ) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        Ok(StatusSummary {
+            branch: status.branch,
+            commit: status.commit,
+            upstream,
+            ahead: status.ahead,
+            behind: status.behind,
+            staged: status.staged,
+            unstaged: status.unstaged,
+            untracked: status.untracked,
+            conflicted: status.conflicted,
+        })
     }
 
     /// Determines the GitHub repository that the local repository is a clone
     /// of by parsing the URL for the specified Git remote
     ///
+    /// The URL is parsed after applying any `url.<base>.insteadOf` rewrites
+    /// configured in Git, so that (for example) a remote URL of
+    /// `gh:octocat/repository` is handled correctly if `gh:` is configured as
+    /// a shorthand for `https://github.com/`.
+    ///
     /// # Errors
     ///
     /// Returns a [`LocalRepoError`] if the invoked Git commit fails to execute
@@ -453,13 +1176,79 @@ impl LocalRepo {
     /// if the given remote does not exist, or if the URL for the given remote
     /// is not a valid GitHub URL
     pub fn github_remote(&self, remote: &str) -> Result<GHRepo, LocalRepoError> {
-        match self.read(&["remote", "get-url", "--", remote]) {
-            Ok(url) => Ok(GHRepo::from_url(&url)?),
-            Err(LocalRepoError::CommandFailed(r)) if r.code() == Some(2) => {
-                Err(LocalRepoError::NoSuchRemote(remote.to_string()))
+        match self.remote_urls(remote, false)?.first() {
+            Some(url) => Ok(self.parse_url(url)?.repo),
+            None => Err(LocalRepoError::NoSuchRemote(remote.to_string())),
+        }
+    }
+
+    /// Determines the GitHub repository that the local repository pushes to
+    /// via the specified Git remote by parsing the remote's push URL.  If the
+    /// remote does not have a push URL (`remote.<name>.pushurl`) configured,
+    /// this is the same as [`LocalRepo::github_remote()`].
+    ///
+    /// If the remote has multiple push URLs, only the first is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git commit fails to execute
+    /// or returns a nonzero status, if the command's output is invalid UTF-8,
+    /// if the given remote does not exist, or if the push URL for the given
+    /// remote is not a valid GitHub URL
+    pub fn github_push_remote(&self, remote: &str) -> Result<GHRepo, LocalRepoError> {
+        match self.remote_urls(remote, true)?.first() {
+            Some(url) => Ok(self.parse_url(url)?.repo),
+            None => Err(LocalRepoError::NoSuchRemote(remote.to_string())),
+        }
+    }
+
+    /// Determines the name of the remote that `git push` pushes the given
+    /// branch to.  This is the value of the first of the following config
+    /// settings that is set:
+    ///
+    /// - `branch.<branch>.pushRemote`
+    /// - `remote.pushDefault`
+    /// - `branch.<branch>.remote`
+    ///
+    /// If none of them are set, `"origin"` is returned, as that is what `git
+    /// push` falls back to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::LocalPushRemote`] if the first setting found
+    /// is `"."`, indicating that the branch is pushed to the local repository
+    /// itself.  Returns another [`LocalRepoError`] if an invoked Git command
+    /// fails to execute or returns a nonzero status or if a command's output
+    /// is invalid UTF-8.
+    pub fn push_remote_for_branch(&self, branch: &str) -> Result<String, LocalRepoError> {
+        for key in [
+            format!("branch.{branch}.pushRemote"),
+            String::from("remote.pushDefault"),
+            format!("branch.{branch}.remote"),
+        ] {
+            match self.config_get(&key)? {
+                Some(remote) if remote == "." => {
+                    return Err(LocalRepoError::LocalPushRemote(branch.to_string()));
+                }
+                Some(remote) => return Ok(remote),
+                None => (),
             }
-            Err(e) => Err(e),
         }
+        Ok(String::from("origin"))
+    }
+
+    /// Determines the GitHub repository that `git push` pushes the given
+    /// branch to by combining [`LocalRepo::push_remote_for_branch()`] and
+    /// [`LocalRepo::github_push_remote()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status, if a command's output is invalid UTF-8, if
+    /// the branch is pushed to the local repository, if the push remote does
+    /// not exist, or if the push URL for the remote is not a valid GitHub URL
+    pub fn branch_push_repo(&self, branch: &str) -> Result<GHRepo, LocalRepoError> {
+        self.github_push_remote(&self.push_remote_for_branch(branch)?)
     }
 
     /// Determines the GitHub repository for the upstream remote of the given
@@ -472,48 +1261,1378 @@ impl LocalRepo {
     /// if the branch does not have a remote configured, if the remote
     /// does not exist, or if the URL for the remote is not a valid GitHub URL
     pub fn branch_upstream(&self, branch: &str) -> Result<GHRepo, LocalRepoError> {
-        match self.read(&["config", "--get", "--", &format!("branch.{branch}.remote")]) {
-            Ok(upstream) => self.github_remote(&upstream),
-            Err(LocalRepoError::CommandFailed(r)) if r.code() == Some(1) => {
-                Err(LocalRepoError::NoUpstream(branch.to_string()))
-            }
-            Err(e) => Err(e),
+        match self.config_get(&format!("branch.{branch}.remote"))? {
+            Some(upstream) => self.github_remote(&upstream),
+            None => Err(LocalRepoError::NoUpstream(branch.to_string())),
         }
     }
-}
-
-/// Error returned when a [`LocalRepo`] method fails
-#[derive(Debug)]
-pub enum LocalRepoError {
-    /// Returned when the Git command could not be executed
-    CouldNotExecute(io::Error),
-
-    /// Returned when the Git command returned nonzero
-    CommandFailed(ExitStatus),
 
-    /// Returned by [`LocalRepo::for_cwd()`] if [`std::env::current_dir()`]
-    /// errored
-    CurdirError(io::Error),
+    /// Returns details on every remote configured for the local repository,
+    /// sorted by name.  The repository's configuration is only read once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status or if a command's output is invalid UTF-8.
+    /// Remotes whose URLs are not GitHub URLs do not cause an error; instead,
+    /// the `repo` field of the corresponding [`Remote`] will be an `Err`.
+    pub fn remotes(&self) -> Result<Vec<Remote>, LocalRepoError> {
+        let config = self.config()?;
+        native::remote_names(&config)
+            .into_iter()
+            .map(|name| self.remote_from_config(&config, &name))
+            .collect()
+    }
 
-    /// Returned by [`LocalRepo::current_branch()`] if the repository is in a
-    /// detached `HEAD` state
-    DetachedHead,
+    /// Returns details on the given remote, including both its URLs as
+    /// configured and its URLs after applying `url.<base>.insteadOf` &
+    /// `url.<base>.pushInsteadOf` rewrites
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status, if a command's output is invalid UTF-8, or
+    /// if the remote does not exist.  If the remote's URL is not a GitHub URL,
+    /// the `repo` field of the returned [`Remote`] will be an `Err`.
+    pub fn remote(&self, name: &str) -> Result<Remote, LocalRepoError> {
+        self.remote_from_config(&self.config()?, name)
+    }
 
-    /// Returned by [`LocalRepo::github_remote()`] if the named remote does not
-    /// exist.  The field is the name of the nonexistent remote.
-    NoSuchRemote(String),
+    /// (Private) Construct the details on the given remote from the
+    /// repository's configuration, as described for [`LocalRepo::remote()`]
+    fn remote_from_config(&self, config: &Config, name: &str) -> Result<Remote, LocalRepoError> {
+        let missing = || LocalRepoError::NoSuchRemote(name.to_string());
+        let fetch_urls = native::remote_urls(config, name, false).ok_or_else(missing)?;
+        let push_urls = native::remote_urls(config, name, true).ok_or_else(missing)?;
+        let raw_fetch_urls = config
+            .get_all(&format!("remote.{name}.url"))
+            .map(ToString::to_string)
+            .collect();
+        let raw_push_urls = config
+            .get_all(&format!("remote.{name}.pushurl"))
+            .map(ToString::to_string)
+            .collect();
+        let (repo, ssh_alias) = match fetch_urls.first().map(|url| self.parse_url(url)) {
+            Some(Ok(resolved)) => (Ok(resolved.repo), resolved.ssh_alias),
+            Some(Err(e)) => (Err(e), None),
+            None => (Err(ParseError::NoUrl), None),
+        };
+        Ok(Remote {
+            name: name.to_string(),
+            fetch_urls,
+            push_urls,
+            raw_fetch_urls,
+            raw_push_urls,
+            repo,
+            ssh_alias,
+        })
+    }
 
-    /// Returned by [`LocalRepo::branch_upstream()`] if the given branch does
-    /// not have an upstream remote configured.  (This includes the situation
-    /// in which the branch does not exist.)  The field is the name of the
-    /// queried branch.
-    NoUpstream(String),
+    /// Determines what the given remote's URL would be if it were rewritten in
+    /// the given style, without changing anything.  The remote's current URL
+    /// (after applying any `url.<base>.insteadOf` rewrites and resolving SSH
+    /// host aliases, if an SSH configuration was supplied) must be either a
+    /// GitHub URL or an HTTPS or SSH URL on any other host whose path is of
+    /// the form `{owner}/{name}[.git]`, so that a remote previously set to a
+    /// GitHub Enterprise Server URL can be rewritten back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoSuchRemote`] if the remote does not exist,
+    /// [`LocalRepoError::InvalidRemoteURL`] if the remote's URL is not a
+    /// repository URL as described above, [`LocalRepoError::InvalidHost`] if
+    /// the style's GitHub Enterprise Server host is invalid, or another
+    /// [`LocalRepoError`] if an invoked Git command fails to execute or the
+    /// repository could not be inspected
+    pub fn remote_url_in_style(
+        &self,
+        remote: &str,
+        style: &UrlStyle,
+    ) -> Result<RemoteUrlChange, LocalRepoError> {
+        style.check_host()?;
+        let Some(url) = self.remote_urls(remote, false)?.into_iter().next() else {
+            return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+        };
+        let repo = match self.parse_url(&url) {
+            Ok(resolved) => resolved.repo,
+            Err(e) => parse_any_host_url(&url)
+                .and_then(|(owner, name)| GHRepo::new(owner, name).ok())
+                .ok_or(LocalRepoError::InvalidRemoteURL(e))?,
+        };
+        let old_url = self
+            .backend
+            .config_get_all(&self.path, &format!("remote.{remote}.url"))?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(RemoteUrlChange {
+            remote: remote.to_string(),
+            old_url,
+            new_url: style.url(&repo),
+        })
+    }
 
-    /// Returned when the output from Git could not be decoded
-    InvalidUtf8(str::Utf8Error),
+    /// Rewrites the given remote's URL in the given style (e.g., to switch
+    /// from HTTPS to SSH) and sets it as the remote's first fetch URL.  The
+    /// remote's push URLs, if any, are not changed.  Nothing is written if
+    /// the URL is already in the requested style.
+    ///
+    /// Note that the new URL is stored as-is, and so any
+    /// `url.<base>.insteadOf` settings that match it will still apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] in the same cases as
+    /// [`LocalRepo::remote_url_in_style()`], or
+    /// [`LocalRepoError::Unsupported`] if the backend cannot modify the
+    /// repository's configuration
+    pub fn set_remote_url_style(
+        &self,
+        remote: &str,
+        style: &UrlStyle,
+    ) -> Result<RemoteUrlChange, LocalRepoError> {
+        let change = self.remote_url_in_style(remote, style)?;
+        if change.is_changed() {
+            self.backend
+                .set_remote_url(&self.path, remote, &change.new_url)?;
+        }
+        Ok(change)
+    }
 
-    /// Returned when the remote URL is not a GitHub URL
-    InvalidRemoteURL(ParseError),
+    /// Adds a remote with the given name whose URL is the URL for `repo` in
+    /// the given style, as with `git remote add`.  If a remote with that name
+    /// already exists and its URL already points to `repo` (comparing owner &
+    /// repository names case-insensitively, in any URL style), nothing is
+    /// changed.  Returns true if the remote was added.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::RemoteExists`] if a remote with the given
+    /// name already exists and points to a different repository (or to a
+    /// non-GitHub URL), [`LocalRepoError::InvalidHost`] if the style's GitHub
+    /// Enterprise Server host is invalid, [`LocalRepoError::Unsupported`] if
+    /// the backend cannot modify the repository's configuration, or another
+    /// [`LocalRepoError`]
+    /// if an invoked Git command fails to execute or the repository could not
+    /// be inspected or modified
+    pub fn add_github_remote(
+        &self,
+        name: &str,
+        repo: &GHRepo,
+        style: &UrlStyle,
+    ) -> Result<bool, LocalRepoError> {
+        style.check_host()?;
+        match self.github_remote(name) {
+            Ok(existing)
+                if existing.owner().eq_ignore_ascii_case(repo.owner())
+                    && existing.name().eq_ignore_ascii_case(repo.name()) =>
+            {
+                return Ok(false);
+            }
+            Ok(_) | Err(LocalRepoError::InvalidRemoteURL(_)) => {
+                return Err(LocalRepoError::RemoteExists(name.to_string()));
+            }
+            Err(LocalRepoError::NoSuchRemote(_)) => (),
+            Err(e) => return Err(e),
+        }
+        self.backend
+            .add_remote(&self.path, name, &style.url(repo))?;
+        Ok(true)
+    }
+
+    /// Configures the given remote to also fetch the heads of the GitHub
+    /// repository's pull requests (`refs/pull/*/head`) as the remote-tracking
+    /// branches `refs/remotes/<remote>/pr/*`.  If the remote already has this
+    /// fetch refspec, nothing is changed.  Returns true if the refspec was
+    /// added.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoSuchRemote`] if the remote does not exist,
+    /// [`LocalRepoError::Unsupported`] if the backend cannot modify the
+    /// repository's configuration, or another [`LocalRepoError`] if an
+    /// invoked Git command fails to execute or the repository could not be
+    /// inspected or modified
+    pub fn fetch_pull_requests(&self, remote: &str) -> Result<bool, LocalRepoError> {
+        if self.remote_urls(remote, false)?.is_empty() {
+            return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+        }
+        let refspec = format!("+refs/pull/*/head:refs/remotes/{remote}/pr/*");
+        if self
+            .backend
+            .config_get_all(&self.path, &format!("remote.{remote}.fetch"))?
+            .contains(&refspec)
+        {
+            return Ok(false);
+        }
+        self.backend
+            .add_fetch_refspec(&self.path, remote, &refspec)?;
+        Ok(true)
+    }
+
+    /// (Private) Parse a remote URL as a GitHub repository URL, resolving SSH
+    /// host aliases if an SSH configuration was supplied
+    fn parse_url(&self, url: &str) -> Result<ResolvedRepo, ParseError> {
+        match &self.ssh_config {
+            Some(ssh_config) => GHRepo::from_url_with_ssh_config(url, ssh_config),
+            None => Ok(ResolvedRepo {
+                repo: GHRepo::from_url(url)?,
+                ssh_alias: None,
+            }),
+        }
+    }
+
+    /// (Private) Get all of the fetch or push URLs for the given remote
+    fn remote_urls(&self, remote: &str, push: bool) -> Result<Vec<String>, LocalRepoError> {
+        self.backend.remote_urls(&self.path, remote, push)
+    }
+
+    /// Determines the upstream of the given branch of the local repository,
+    /// including both the repository and the upstream branch name, as
+    /// configured by the `branch.<branch>.remote` and `branch.<branch>.merge`
+    /// config settings
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status, if a command's output is invalid UTF-8, if
+    /// the branch does not have an upstream configured, if the upstream remote
+    /// does not exist, or if the URL for the remote is not a valid GitHub URL
+    pub fn branch_upstream_details(&self, branch: &str) -> Result<BranchUpstream, LocalRepoError> {
+        self.upstream_from_config(&self.config()?, branch)
+    }
+
+    /// (Private) Read all of the repository's configuration at once
+    fn config(&self) -> Result<Config, LocalRepoError> {
+        let mut config = Config::default();
+        for (key, value) in self.backend.config_list(&self.path)? {
+            config.push(&key, &value);
+        }
+        Ok(config)
+    }
+
+    /// (Private) Determine the upstream of the given branch from the
+    /// repository's configuration, as described for
+    /// [`LocalRepo::branch_upstream_details()`]
+    fn upstream_from_config(
+        &self,
+        config: &Config,
+        branch: &str,
+    ) -> Result<BranchUpstream, LocalRepoError> {
+        let (Some(remote), Some(merge)) = (
+            config.get(&format!("branch.{branch}.remote")),
+            config.get(&format!("branch.{branch}.merge")),
+        ) else {
+            return Err(LocalRepoError::NoUpstream(branch.to_string()));
+        };
+        let upstream_branch = merge
+            .strip_prefix("refs/heads/")
+            .unwrap_or(merge)
+            .to_string();
+        if remote == "." {
+            return Ok(BranchUpstream::Local {
+                branch: upstream_branch,
+            });
+        }
+        let Some(url) =
+            native::remote_urls(config, remote, false).and_then(|urls| urls.into_iter().next())
+        else {
+            return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+        };
+        Ok(BranchUpstream::Remote {
+            remote: remote.to_string(),
+            repo: self.parse_url(&url)?.repo,
+            branch: upstream_branch,
+        })
+    }
+
+    /// Determines the default branch of the given remote from the remote's
+    /// `HEAD` ref (`refs/remotes/<remote>/HEAD`) as recorded in the local
+    /// repository, without contacting the remote.
+    ///
+    /// Note that this ref is only set when a repository is cloned or when
+    /// `git remote set-head` is run, and so it may be missing or out of date.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if the invoked Git commit fails to execute
+    /// or returns a nonzero status, if the command's output is invalid UTF-8,
+    /// or if the remote's `HEAD` ref is not set
+    pub fn default_branch(&self, remote: &str) -> Result<String, LocalRepoError> {
+        let headref = format!("refs/remotes/{remote}/HEAD");
+        match self.backend.symbolic_ref(&self.path, &headref)? {
+            Some(target) => match target.strip_prefix(&format!("refs/remotes/{remote}/")) {
+                Some(branch) => Ok(branch.to_string()),
+                None => Ok(target),
+            },
+            None => Err(LocalRepoError::NoRemoteHead(remote.to_string())),
+        }
+    }
+
+    /// Determines the GitHub repository that tools operating on the local
+    /// repository should treat as the default, following the same rules as
+    /// the official GitHub CLI.  The rules are tried in the following order,
+    /// and the first one that applies is used:
+    ///
+    /// 1. If a remote has been selected with `gh repo set-default` (i.e., it
+    ///    has a `remote.<name>.gh-resolved` config value of `base`), that
+    ///    remote is used.
+    /// 2. If there is a remote named `upstream` with a GitHub URL, it is used.
+    /// 3. If there is a remote named `origin` with a GitHub URL, it is used.
+    /// 4. If exactly one remote has a GitHub URL, it is used.
+    ///
+    /// As with the GitHub CLI, if a remote has a `remote.<name>.gh-resolved`
+    /// config value of the form `{owner}/{name}`, that repository is used as
+    /// the remote's GitHub repository in place of the one in its URL.
+    ///
+    /// The repository's configuration is only read once.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] if an invoked Git command fails to execute
+    /// or returns a nonzero status, if a command's output is invalid UTF-8, if
+    /// the remote selected with `gh repo set-default` does not exist or does
+    /// not have a GitHub URL, or if none of the above rules apply
+    pub fn default_github_repo(&self) -> Result<DefaultRemote, LocalRepoError> {
+        let config = self.config()?;
+        let base = config.entries().iter().find_map(|(key, value)| {
+            if value != "base" {
+                return None;
+            }
+            key.strip_prefix("remote.")?.strip_suffix(".gh-resolved")
+        });
+        if let Some(remote) = base {
+            let Some(url) = native::remote_urls(&config, remote, false)
+                .and_then(|urls| urls.into_iter().next())
+            else {
+                return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+            };
+            return Ok(DefaultRemote {
+                remote: remote.to_string(),
+                repo: self.parse_url(&url)?.repo,
+                rule: DefaultRemoteRule::GhResolved,
+            });
+        }
+        let mut github_remotes = native::remote_names(&config)
+            .into_iter()
+            .filter_map(|name| {
+                let repo = config
+                    .get(&format!("remote.{name}.gh-resolved"))
+                    .and_then(|spec| spec.parse::<GHRepo>().ok())
+                    .or_else(|| {
+                        let url = native::remote_urls(&config, &name, false)?
+                            .into_iter()
+                            .next()?;
+                        Some(self.parse_url(&url).ok()?.repo)
+                    })?;
+                Some((name, repo))
+            })
+            .collect::<Vec<_>>();
+        for (name, rule) in [
+            ("upstream", DefaultRemoteRule::Upstream),
+            ("origin", DefaultRemoteRule::Origin),
+        ] {
+            if let Some(i) = github_remotes.iter().position(|(r, _)| r == name) {
+                let (remote, repo) = github_remotes.swap_remove(i);
+                return Ok(DefaultRemote { remote, repo, rule });
+            }
+        }
+        let mut iter = github_remotes.into_iter();
+        match (iter.next(), iter.next()) {
+            (Some((remote, repo)), None) => Ok(DefaultRemote {
+                remote,
+                repo,
+                rule: DefaultRemoteRule::OnlyGitHubRemote,
+            }),
+            _ => Err(LocalRepoError::NoDefaultRemote),
+        }
+    }
+
+    /// Classifies the relationship between the GitHub repositories of the
+    /// `upstream` and `origin` remotes, following the common convention of
+    /// cloning a fork as `origin` and adding its parent as `upstream`.  This
+    /// is equivalent to `remote_fork_info("upstream", "origin")`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LocalRepoError`] in the same cases as
+    /// [`LocalRepo::remote_fork_info()`]
+    pub fn fork_info(&self) -> Result<ForkInfo, LocalRepoError> {
+        self.remote_fork_info("upstream", "origin")
+    }
+
+    /// Classifies the relationship between the GitHub repositories of the
+    /// remote `parent_remote`, assumed to point to the parent repository, and
+    /// the remote `fork_remote`, assumed to point to the fork.
+    ///
+    /// Repositories with the same name under different owners are considered
+    /// a fork & its parent.  Repositories with different names are considered
+    /// a renamed fork & its parent if any of the remotes' remote-tracking
+    /// branches share a common ancestor.  Owner & repository names are
+    /// compared case-insensitively, as on GitHub.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NoSuchRemote`] if either remote does not
+    /// exist, [`LocalRepoError::InvalidRemoteURL`] if either remote's URL is
+    /// not a valid GitHub URL, [`LocalRepoError::Unsupported`] if the
+    /// repositories have different names and the backend cannot walk commit
+    /// history, or another [`LocalRepoError`] if an invoked Git command fails
+    /// to execute or the repository could not be inspected
+    pub fn remote_fork_info(
+        &self,
+        parent_remote: &str,
+        fork_remote: &str,
+    ) -> Result<ForkInfo, LocalRepoError> {
+        let parent = self.github_remote(parent_remote)?;
+        let fork = self.github_remote(fork_remote)?;
+        let same_owner = parent.owner().eq_ignore_ascii_case(fork.owner());
+        let same_name = parent.name().eq_ignore_ascii_case(fork.name());
+        let relation = if same_name && same_owner {
+            ForkRelation::SameRepository
+        } else if same_name {
+            ForkRelation::SameName
+        } else {
+            match self
+                .backend
+                .shares_history(&self.path, parent_remote, fork_remote)?
+            {
+                Some(true) => ForkRelation::Renamed,
+                Some(false) => ForkRelation::Unrelated,
+                None => ForkRelation::Unknown,
+            }
+        };
+        Ok(ForkInfo {
+            parent_remote: parent_remote.to_string(),
+            parent,
+            fork_remote: fork_remote.to_string(),
+            fork,
+            relation,
+        })
+    }
+}
+
+/// A builder for a [`LocalRepo`] that runs Git with custom settings, created
+/// with [`LocalRepo::builder()`]
+///
+/// # Example
+///
+/// ```
+/// # use ghrepo::LocalRepo;
+/// # use std::time::Duration;
+/// let lr = LocalRepo::builder("/path/to/repo")
+///     .git_path("/usr/local/bin/git")
+///     .env("GIT_TERMINAL_PROMPT", "0")
+///     .env("GIT_CONFIG_NOSYSTEM", "1")
+///     .config("core.fsmonitor", "false")
+///     .timeout(Duration::from_secs(5))
+///     .build();
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LocalRepoBuilder {
+    path: PathBuf,
+    git: GitCommand,
+    ssh_config: Option<SshConfig>,
+}
+
+impl LocalRepoBuilder {
+    /// Set the Git executable to run.  If this is a bare filename, it is
+    /// looked up in `PATH`.  The default is `git`.
+    #[must_use]
+    pub fn git_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.git.git = path.into();
+        self
+    }
+
+    /// Set an environment variable for Git commands, such as
+    /// `GIT_TERMINAL_PROMPT=0` to prevent credential prompts,
+    /// `GIT_CONFIG_NOSYSTEM=1` to ignore the system config file, or `GIT_DIR`
+    /// & `GIT_WORK_TREE` to operate on a repository in a nonstandard location.
+    /// Variables that are not set with this method are inherited from the
+    /// current process.
+    #[must_use]
+    pub fn env<K: Into<OsString>, V: Into<OsString>>(mut self, key: K, value: V) -> Self {
+        self.git.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Unset an environment variable for Git commands
+    #[must_use]
+    pub fn env_remove<K: Into<OsString>>(mut self, key: K) -> Self {
+        self.git.env.push((key.into(), None));
+        self
+    }
+
+    /// Set a Git config option for Git commands by passing `-c <key>=<value>`
+    #[must_use]
+    pub fn config(mut self, key: &str, value: &str) -> Self {
+        self.git.config.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the maximum amount of time that each Git command may run.  If a
+    /// command runs for longer than this, it is killed, and the method that
+    /// ran it fails with [`LocalRepoError::Timeout`].
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.git.timeout = Some(timeout);
+        self
+    }
+
+    /// Capture the stderr output of Git commands instead of letting it pass
+    /// through to the current process's stderr.  Captured output is attached
+    /// to [`LocalRepoError::CommandFailed`] errors, and commands that fail
+    /// with recognized messages result in [`LocalRepoError::NotARepository`],
+    /// [`LocalRepoError::DubiousOwnership`], or
+    /// [`LocalRepoError::InvalidConfig`] instead.  So that these messages can
+    /// be recognized, Git is run in the C locale when its stderr is captured.
+    #[must_use]
+    pub fn capture_stderr(mut self, yes: bool) -> Self {
+        self.git.capture_stderr = yes;
+        self
+    }
+
+    /// Use the given SSH configuration to resolve host aliases in remote URLs;
+    /// see [`LocalRepo::with_ssh_config()`]
+    #[must_use]
+    pub fn ssh_config(mut self, ssh_config: SshConfig) -> Self {
+        self.ssh_config = Some(ssh_config);
+        self
+    }
+
+    /// Construct the [`LocalRepo`]
+    pub fn build(self) -> LocalRepo {
+        LocalRepo {
+            path: self.path,
+            backend: self.git,
+            ssh_config: self.ssh_config,
+            layout: None,
+        }
+    }
+
+    /// Construct an [`AsyncLocalRepo`] that runs Git with the configured
+    /// settings
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub fn build_async(self) -> AsyncLocalRepo {
+        AsyncLocalRepo::with_git(self.path, self.git, self.ssh_config)
+    }
+
+    /// Construct the [`LocalRepo`], locating the repository and recording its
+    /// layout as with [`LocalRepo::discover()`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::NotARepository`] if the directory is not in a
+    /// Git repository, or another [`LocalRepoError`] if the Git command failed
+    /// to execute or the repository could not be inspected
+    pub fn discover(self) -> Result<LocalRepo, LocalRepoError> {
+        self.build().discovered()
+    }
+}
+
+/// The upstream of a local branch, as returned by
+/// [`LocalRepo::branch_upstream_details()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BranchUpstream {
+    /// The branch tracks a branch of a remote GitHub repository
+    Remote {
+        /// The name of the upstream remote
+        remote: String,
+
+        /// The GitHub repository that the upstream remote's URL points to
+        repo: GHRepo,
+
+        /// The name of the upstream branch in the remote repository
+        branch: String,
+    },
+
+    /// The branch tracks another branch of the local repository (i.e., its
+    /// `branch.<branch>.remote` config setting is `.`)
+    Local {
+        /// The name of the upstream branch in the local repository
+        branch: String,
+    },
+}
+
+/// A Git remote of a local repository, as returned by [`LocalRepo::remotes()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Remote {
+    /// The name of the remote
+    pub name: String,
+
+    /// The URLs that the remote fetches from, after applying
+    /// `url.<base>.insteadOf` rewrites
+    pub fetch_urls: Vec<String>,
+
+    /// The URLs that the remote pushes to, after applying
+    /// `url.<base>.insteadOf` & `url.<base>.pushInsteadOf` rewrites.  If the
+    /// remote does not have any push URLs configured, these are derived from
+    /// the fetch URLs.
+    pub push_urls: Vec<String>,
+
+    /// The fetch URLs as configured with `remote.<name>.url`, before any
+    /// rewrites are applied
+    pub raw_fetch_urls: Vec<String>,
+
+    /// The push URLs as configured with `remote.<name>.pushurl`, before any
+    /// rewrites are applied.  This is empty if the remote does not have any
+    /// push URLs configured.
+    pub raw_push_urls: Vec<String>,
+
+    /// The result of parsing the first fetch URL as a GitHub repository URL
+    pub repo: Result<GHRepo, ParseError>,
+
+    /// If the first fetch URL was recognized as a GitHub URL by resolving an
+    /// SSH host alias (see [`LocalRepo::with_ssh_config()`]), this is the
+    /// alias
+    pub ssh_alias: Option<String>,
+}
+
+/// A GitHub repository parsed from a URL by
+/// [`GHRepo::from_url_with_ssh_config()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ResolvedRepo {
+    /// The GitHub repository
+    pub repo: GHRepo,
+
+    /// The SSH host alias that was resolved to a GitHub host in order to
+    /// parse the URL, or `None` if the URL did not use an alias
+    pub ssh_alias: Option<String>,
+}
+
+/// A GitHub repository selected by [`LocalRepo::default_github_repo()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DefaultRemote {
+    /// The name of the selected remote
+    pub remote: String,
+
+    /// The GitHub repository that the remote's URL points to
+    pub repo: GHRepo,
+
+    /// The rule by which the remote was selected
+    pub rule: DefaultRemoteRule,
+}
+
+/// The rule by which [`LocalRepo::default_github_repo()`] selected a remote
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DefaultRemoteRule {
+    /// The remote was selected with `gh repo set-default`
+    GhResolved,
+
+    /// The remote is named `upstream`
+    Upstream,
+
+    /// The remote is named `origin`
+    Origin,
+
+    /// The remote is the only remote with a GitHub URL
+    OnlyGitHubRemote,
+}
+
+/// The relationship between two remotes' GitHub repositories, as returned by
+/// [`LocalRepo::fork_info()`] and [`LocalRepo::remote_fork_info()`]
+///
+/// The `Display` implementation formats a `ForkRelation` as a kebab-case
+/// string, e.g., `same-name`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ForkRelation {
+    /// Both remotes point to the same repository
+    SameRepository,
+
+    /// The repositories have the same name under different owners, as with a
+    /// fork that kept its parent's name
+    SameName,
+
+    /// The repositories have different names but share commit history, as
+    /// with a fork that was renamed
+    Renamed,
+
+    /// The repositories have different names and do not share any commit
+    /// history
+    Unrelated,
+
+    /// The repositories have different names, and whether they share commit
+    /// history could not be determined because one of the remotes has no
+    /// remote-tracking branches (e.g., because it has not been fetched)
+    Unknown,
+}
+
+impl fmt::Display for ForkRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ForkRelation::SameRepository => "same-repository",
+            ForkRelation::SameName => "same-name",
+            ForkRelation::Renamed => "renamed",
+            ForkRelation::Unrelated => "unrelated",
+            ForkRelation::Unknown => "unknown",
+        };
+        f.pad(s)
+    }
+}
+
+/// The GitHub repositories of a pair of remotes that may be a fork and its
+/// parent, as returned by [`LocalRepo::fork_info()`] and
+/// [`LocalRepo::remote_fork_info()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ForkInfo {
+    /// The name of the remote for the likely parent repository
+    pub parent_remote: String,
+
+    /// The GitHub repository that the parent remote's URL points to
+    pub parent: GHRepo,
+
+    /// The name of the remote for the likely fork
+    pub fork_remote: String,
+
+    /// The GitHub repository that the fork remote's URL points to
+    pub fork: GHRepo,
+
+    /// How the two repositories are related
+    pub relation: ForkRelation,
+}
+
+impl ForkInfo {
+    /// Returns true if the fork repository appears to be a fork of the parent
+    /// repository, i.e., if [`ForkInfo::relation`] is
+    /// [`ForkRelation::SameName`] or [`ForkRelation::Renamed`]
+    pub fn is_fork(&self) -> bool {
+        matches!(
+            self.relation,
+            ForkRelation::SameName | ForkRelation::Renamed
+        )
+    }
+}
+
+/// A style of URL for a GitHub repository, used by
+/// [`LocalRepo::set_remote_url_style()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum UrlStyle {
+    /// An HTTPS URL of the form `https://github.com/{owner}/{name}.git`, as
+    /// returned by [`GHRepo::clone_url()`]
+    Https,
+
+    /// An SSH URL of the form `git@github.com:{owner}/{name}.git`, as
+    /// returned by [`GHRepo::ssh_url()`]
+    Ssh,
+
+    /// An SSH URL of the form
+    /// `ssh://git@ssh.github.com:443/{owner}/{name}.git`, for connecting over
+    /// the HTTPS port when port 22 is blocked
+    Ssh443,
+
+    /// An HTTPS URL for a GitHub Enterprise Server instance at the given
+    /// host, of the form `https://{host}/{owner}/{name}.git`.  The host must
+    /// be nonempty and must not contain whitespace, `/`, `@`, or `:`.
+    EnterpriseHttps(String),
+
+    /// An SSH URL for a GitHub Enterprise Server instance at the given host,
+    /// of the form `git@{host}:{owner}/{name}.git`.  The host must be
+    /// nonempty and must not contain whitespace, `/`, `@`, or `:`.
+    EnterpriseSsh(String),
+}
+
+impl UrlStyle {
+    /// Returns the URL in this style for the given repository
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ghrepo::{GHRepo, UrlStyle};
+    /// let repo = GHRepo::new("octocat", "repository").unwrap();
+    /// assert_eq!(
+    ///     UrlStyle::Ssh443.url(&repo),
+    ///     "ssh://git@ssh.github.com:443/octocat/repository.git"
+    /// );
+    /// ```
+    pub fn url(&self, repo: &GHRepo) -> String {
+        match self {
+            UrlStyle::Https => repo.clone_url(),
+            UrlStyle::Ssh => repo.ssh_url(),
+            UrlStyle::Ssh443 => format!("ssh://git@ssh.github.com:443/{repo}.git"),
+            UrlStyle::EnterpriseHttps(host) => format!("https://{host}/{repo}.git"),
+            UrlStyle::EnterpriseSsh(host) => format!("git@{host}:{repo}.git"),
+        }
+    }
+
+    /// (Private) Check that the GitHub Enterprise Server host of the style,
+    /// if any, can be inserted into a URL as-is
+    fn check_host(&self) -> Result<(), LocalRepoError> {
+        match self {
+            UrlStyle::EnterpriseHttps(host) | UrlStyle::EnterpriseSsh(host)
+                if host.is_empty()
+                    || host.contains(|c: char| c.is_whitespace() || "/@:".contains(c)) =>
+            {
+                Err(LocalRepoError::InvalidHost(host.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A change to a remote's URL, as returned by
+/// [`LocalRepo::remote_url_in_style()`] and
+/// [`LocalRepo::set_remote_url_style()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RemoteUrlChange {
+    /// The name of the remote
+    pub remote: String,
+
+    /// The remote's first fetch URL as configured, before
+    /// `url.<base>.insteadOf` rewrites are applied
+    pub old_url: String,
+
+    /// The new URL for the remote
+    pub new_url: String,
+}
+
+impl RemoteUrlChange {
+    /// Returns true if the new URL differs from the old URL
+    pub fn is_changed(&self) -> bool {
+        self.old_url != self.new_url
+    }
+}
+
+/// Options for [`GHRepo::clone_into()`]
+///
+/// # Example
+///
+/// ```
+/// # use ghrepo::{CloneOptions, GHRepo, UrlStyle};
+/// let options = CloneOptions::new()
+///     .style(UrlStyle::Ssh)
+///     .depth(1)
+///     .branch("main")
+///     .upstream(GHRepo::new("octocat", "repository").unwrap());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CloneOptions {
+    style: Option<UrlStyle>,
+    depth: Option<u32>,
+    branch: Option<String>,
+    upstream: Option<GHRepo>,
+    git: GitCommand,
+}
+
+impl CloneOptions {
+    /// Create a `CloneOptions` with the default settings
+    pub fn new() -> Self {
+        CloneOptions::default()
+    }
+
+    /// Set the style of URL to use for the repository's remotes.  The default
+    /// is determined by the `ghrepo.protocol` Git config setting.
+    #[must_use]
+    pub fn style(mut self, style: UrlStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Create a shallow clone with a history truncated to the given number of
+    /// commits, as with `git clone --depth`
+    #[must_use]
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Check out the given branch instead of the remote's default branch, as
+    /// with `git clone --branch`
+    #[must_use]
+    pub fn branch(mut self, branch: &str) -> Self {
+        self.branch = Some(branch.to_string());
+        self
+    }
+
+    /// Add a remote named `upstream` for the given repository, typically the
+    /// parent of the repository being cloned if it is a fork
+    #[must_use]
+    pub fn upstream(mut self, repo: GHRepo) -> Self {
+        self.upstream = Some(repo);
+        self
+    }
+
+    /// Set a Git config option for the `git clone` command and for the Git
+    /// commands run by the returned [`LocalRepo`] (but not in the clone's
+    /// configuration) by passing `-c <key>=<value>`.  This can be used to
+    /// clone from a mirror by setting `url.<base>.insteadOf`.
+    #[must_use]
+    pub fn config(mut self, key: &str, value: &str) -> Self {
+        self.git.config.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// The state of a directory with respect to Git, as returned by
+/// [`LocalRepo::inspect()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RepoState {
+    /// The directory is not in a Git repository
+    NotARepository,
+
+    /// The directory is in the working tree of a non-bare repository
+    WorkTree {
+        /// The top-level directory of the working tree
+        toplevel: PathBuf,
+
+        /// The repository's Git directory
+        git_dir: PathBuf,
+    },
+
+    /// The directory is inside the Git directory (e.g., `.git`) of a non-bare
+    /// repository
+    InsideGitDir {
+        /// The repository's Git directory
+        git_dir: PathBuf,
+    },
+
+    /// The directory is in a bare repository
+    Bare {
+        /// The repository's Git directory
+        git_dir: PathBuf,
+    },
+
+    /// The directory is in a repository owned by another user that Git
+    /// refuses to operate on because it is not listed in the `safe.directory`
+    /// config setting.  Only backends that check repository ownership report
+    /// this state.
+    UnsafeOwnership {
+        /// The path to the repository as reported by the backend
+        path: PathBuf,
+    },
+
+    /// The directory appears to be in a Git repository, but the repository
+    /// could not be opened, e.g., because a `.git` file is invalid, a
+    /// configuration file could not be parsed, or a file could not be read
+    Corrupt {
+        /// A description of the problem
+        message: String,
+    },
+}
+
+impl RepoState {
+    /// Returns true if the directory is in a usable Git repository, i.e., if
+    /// the state is `WorkTree`, `InsideGitDir`, or `Bare`
+    pub fn is_git_repo(&self) -> bool {
+        matches!(
+            self,
+            RepoState::WorkTree { .. } | RepoState::InsideGitDir { .. } | RepoState::Bare { .. }
+        )
+    }
+
+    /// Returns the top-level directory of the working tree, if any
+    pub fn toplevel(&self) -> Option<&Path> {
+        match self {
+            RepoState::WorkTree { toplevel, .. } => Some(toplevel),
+            _ => None,
+        }
+    }
+
+    /// Returns the repository's Git directory, if one was found
+    pub fn git_dir(&self) -> Option<&Path> {
+        match self {
+            RepoState::WorkTree { git_dir, .. }
+            | RepoState::InsideGitDir { git_dir }
+            | RepoState::Bare { git_dir } => Some(git_dir),
+            _ => None,
+        }
+    }
+}
+
+/// The locations of a repository's files and of a directory within the
+/// repository, as recorded by [`LocalRepo::discover()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RepoLayout {
+    /// The top-level directory of the working tree, or `None` if the directory
+    /// is not in a working tree (e.g., because the repository is bare)
+    pub toplevel: Option<PathBuf>,
+
+    /// The repository's Git directory (the `.git` directory for a non-bare
+    /// repository, or the worktree-specific directory under `.git/worktrees/`
+    /// for a linked worktree)
+    pub git_dir: PathBuf,
+
+    /// The directory containing the repository's shared files (config, refs,
+    /// objects, etc.); this is the same as `git_dir` except for linked
+    /// worktrees
+    pub common_dir: PathBuf,
+
+    /// The path of the directory relative to `toplevel`.  This is empty if the
+    /// directory is `toplevel` or is not in a working tree.
+    pub prefix: PathBuf,
+}
+
+/// A working tree of a repository, as returned by [`LocalRepo::worktrees()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Worktree {
+    /// The top-level directory of the working tree.  For a bare repository,
+    /// this is the repository itself.
+    pub path: PathBuf,
+
+    /// The branch checked out in the working tree, or `None` if it has a
+    /// detached `HEAD` or is a bare repository
+    pub branch: Option<String>,
+
+    /// True if this entry is a bare repository rather than a working tree
+    pub bare: bool,
+
+    /// True if the worktree has been locked with `git worktree lock`
+    pub locked: bool,
+}
+
+/// A link to a file on GitHub at a specific commit, as returned by
+/// [`LocalRepo::permalink()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Permalink {
+    /// The URL of the file on GitHub, including a fragment selecting the
+    /// requested lines, if any
+    pub url: String,
+
+    /// The GitHub repository that the link points to
+    pub repo: GHRepo,
+
+    /// The hash of the commit that the link points to
+    pub commit: String,
+
+    /// The `/`-separated path of the file relative to the top level of the
+    /// working tree
+    pub path: String,
+
+    /// Reasons why the link may not show the file as it is locally
+    pub warnings: Vec<PermalinkWarning>,
+}
+
+/// A reason why a [`Permalink`] may not show a file as it is locally
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PermalinkWarning {
+    /// The file is not tracked by Git
+    Untracked,
+
+    /// The file has uncommitted changes
+    Modified,
+
+    /// The `HEAD` commit is not contained in any of the remote's
+    /// remote-tracking branches.  The field is the name of the remote.
+    NotPushed(String),
+}
+
+impl fmt::Display for PermalinkWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermalinkWarning::Untracked => write!(f, "file is not tracked by Git"),
+            PermalinkWarning::Modified => write!(f, "file has uncommitted changes"),
+            PermalinkWarning::NotPushed(remote) => {
+                write!(f, "HEAD has not been pushed to remote {remote:?}")
+            }
+        }
+    }
+}
+
+/// A description of a commit relative to the most recent tag reachable from
+/// it, as returned by [`LocalRepo::describe()`]
+///
+/// The `Display` implementation formats a `Description` in the same way as
+/// `git describe --tags`, though with the commit hash always abbreviated to
+/// seven characters: just the tag if the commit is tagged, or
+/// `{tag}-{distance}-g{abbreviated hash}` otherwise.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Description {
+    /// The name of the tag
+    pub tag: String,
+
+    /// The number of commits since the tag
+    pub distance: usize,
+
+    /// The full hash of the described commit
+    pub commit: String,
+}
+
+impl Description {
+    /// Returns true if the described commit is the tagged commit itself
+    pub fn is_exact(&self) -> bool {
+        self.distance == 0
+    }
+}
+
+impl fmt::Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_exact() {
+            write!(f, "{}", self.tag)
+        } else {
+            let abbrev = self.commit.get(..7).unwrap_or(&self.commit);
+            write!(f, "{}-{}-g{abbrev}", self.tag, self.distance)
+        }
+    }
+}
+
+/// The state of a file in a working tree relative to the `HEAD` commit, as
+/// returned by [`GitBackend::file_status()`]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FileStatus {
+    /// The file is tracked and matches `HEAD` in both the index and the
+    /// working tree
+    Unmodified,
+
+    /// The file is tracked, but it has staged or unstaged changes
+    Modified,
+
+    /// The file is not tracked (including when it is ignored)
+    Untracked,
+}
+
+/// The current branch & commit of a working tree, along with counts of the
+/// changes in the working tree and of how far the current branch has diverged
+/// from its upstream, as returned by [`GitBackend::status()`]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WorkTreeStatus {
+    /// The currently checked-out branch, or `None` if `HEAD` is detached
+    pub branch: Option<String>,
+
+    /// The hash of the commit that `HEAD` points to, or `None` if `HEAD` is
+    /// an unborn branch
+    pub commit: Option<String>,
+
+    /// The number of commits on the current branch that are not on its
+    /// upstream
+    pub ahead: usize,
+
+    /// The number of commits on the current branch's upstream that are not on
+    /// the branch
+    pub behind: usize,
+
+    /// The number of files with changes in the index relative to `HEAD`
+    pub staged: usize,
+
+    /// The number of tracked files with changes in the working tree relative
+    /// to the index
+    pub unstaged: usize,
+
+    /// The number of untracked files & directories (excluding ignored ones)
+    pub untracked: usize,
+
+    /// The number of files with unresolved merge conflicts
+    pub conflicted: usize,
+}
+
+/// A summary of the state of a repository's current branch & working tree,
+/// as returned by [`LocalRepo::status_summary()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusSummary {
+    /// The currently checked-out branch, or `None` if `HEAD` is detached
+    pub branch: Option<String>,
+
+    /// The hash of the commit that `HEAD` points to, or `None` if `HEAD` is
+    /// an unborn branch
+    pub commit: Option<String>,
+
+    /// The upstream of the current branch, or `None` if `HEAD` is detached,
+    /// the branch has no upstream, or the upstream remote does not exist or
+    /// does not have a GitHub URL
+    pub upstream: Option<BranchUpstream>,
+
+    /// The number of commits on the current branch that are not on its
+    /// upstream
+    pub ahead: usize,
+
+    /// The number of commits on the current branch's upstream that are not on
+    /// the branch
+    pub behind: usize,
+
+    /// The number of files with staged changes
+    pub staged: usize,
+
+    /// The number of tracked files with unstaged changes
+    pub unstaged: usize,
+
+    /// The number of untracked files & directories
+    pub untracked: usize,
+
+    /// The number of files with unresolved merge conflicts
+    pub conflicted: usize,
+}
+
+impl StatusSummary {
+    /// Returns true if the working tree has no staged, unstaged, untracked, or
+    /// conflicted files
+    pub fn is_clean(&self) -> bool {
+        self.staged == 0 && self.unstaged == 0 && self.untracked == 0 && self.conflicted == 0
+    }
+}
+
+/// A submodule of a local repository, as returned by
+/// [`LocalRepo::submodules()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Submodule {
+    /// The name of the submodule as declared in `.gitmodules`
+    pub name: String,
+
+    /// The path of the submodule relative to the top level of the
+    /// superproject's working tree
+    pub path: String,
+
+    /// The submodule's URL as declared in `.gitmodules`
+    pub url: Option<String>,
+
+    /// The result of resolving the submodule's URL to a GitHub repository
+    pub repo: Result<GHRepo, ParseError>,
+
+    /// The branch that the submodule tracks, as declared in `.gitmodules`
+    pub branch: Option<String>,
+
+    /// The commit that the submodule is pinned to in the superproject's
+    /// index, or `None` if the index has no entry for the submodule's path
+    pub commit: Option<String>,
+}
+
+/// (Private) Percent-encode the characters in a `/`-separated path that are
+/// not allowed in URL paths
+fn percent_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/!$&'()*+,;=:@".contains(&b) {
+            encoded.push(char::from(b));
+        } else {
+            let _ = write!(encoded, "%{b:02X}");
+        }
+    }
+    encoded
+}
+
+/// (Private) Resolve a relative submodule URL (one starting with `./` or
+/// `../`) against the superproject's GitHub repository.  As with Git, the
+/// superproject's URL is treated as a directory, so `../other.git` refers to
+/// a sibling repository under the same owner.
+fn resolve_relative_url(base: &GHRepo, url: &str) -> Result<GHRepo, ParseError> {
+    let mut segments = vec![base.owner(), base.name()];
+    for seg in url.trim_end_matches('/').split('/') {
+        match seg {
+            "." | "" => (),
+            ".." => {
+                if segments.pop().is_none() {
+                    return Err(ParseError::InvalidSpec(url.to_string()));
+                }
+            }
+            _ => segments.push(seg),
+        }
+    }
+    match segments[..] {
+        [owner, name] => GHRepo::new(owner, name.strip_suffix(".git").unwrap_or(name))
+            .map_err(|_| ParseError::InvalidSpec(url.to_string())),
+        _ => Err(ParseError::InvalidSpec(url.to_string())),
+    }
+}
+
+/// Error returned when a [`LocalRepo`] method fails
+#[derive(Debug)]
+pub enum LocalRepoError {
+    /// Returned when the Git command could not be executed
+    CouldNotExecute(io::Error),
+
+    /// Returned when the Git command returned nonzero
+    CommandFailed(CommandError),
+
+    /// Returned when a Git command ran for longer than the timeout set with
+    /// [`LocalRepoBuilder::timeout()`] and was killed.  The field is the
+    /// timeout.
+    Timeout(Duration),
+
+    /// Returned by [`LocalRepo::for_cwd()`] if [`std::env::current_dir()`]
+    /// errored
+    CurdirError(io::Error),
+
+    /// Returned by [`LocalRepo::current_branch()`] if the repository is in a
+    /// detached `HEAD` state
+    DetachedHead,
+
+    /// Returned by [`LocalRepo::github_remote()`] if the named remote does not
+    /// exist.  The field is the name of the nonexistent remote.
+    NoSuchRemote(String),
+
+    /// Returned by [`LocalRepo::add_github_remote()`] if a remote with the
+    /// given name already exists and does not point to the requested
+    /// repository.  The field is the name of the remote.
+    RemoteExists(String),
+
+    /// Returned by [`LocalRepo::branch_upstream()`] if the given branch does
+    /// not have an upstream remote configured.  (This includes the situation
+    /// in which the branch does not exist.)  The field is the name of the
+    /// queried branch.
+    NoUpstream(String),
+
+    /// Returned by [`LocalRepo::push_remote_for_branch()`] and
+    /// [`LocalRepo::branch_push_repo()`] if the given branch is configured to
+    /// push to the local repository (i.e., the remote `"."`) rather than to a
+    /// remote.  The field is the name of the queried branch.
+    LocalPushRemote(String),
+
+    /// Returned by [`LocalRepo::default_branch()`] if the remote's `HEAD` ref
+    /// is not set.  The field is the name of the remote.
+    NoRemoteHead(String),
+
+    /// Returned by [`LocalRepo::head_commit()`], [`LocalRepo::permalink()`],
+    /// and similar methods if `HEAD` does not point to a commit, as in a
+    /// repository without any commits
+    NoHeadCommit,
+
+    /// Returned by [`LocalRepo::permalink()`] if the given file is not inside
+    /// the repository's working tree (including when the repository is bare).
+    /// The field is the path of the file.
+    NotInWorkTree(PathBuf),
+
+    /// Returned when a [`GitBackend`] does not support the requested
+    /// operation.  The field is a description of the operation.
+    Unsupported(String),
+
+    /// Returned by [`LocalRepo::default_github_repo()`] if no remote was
+    /// selected with `gh repo set-default`, there are no remotes named
+    /// `upstream` or `origin` with GitHub URLs, and the number of remotes with
+    /// GitHub URLs is not exactly one
+    NoDefaultRemote,
+
+    /// Returned by methods of a [`LocalRepo`] if the directory is not in a Git
+    /// repository.  The field is the path of the directory or invalid `.git`
+    /// file.
+    ///
+    /// The [`GitCommand`] backend only returns this error if it was configured
+    /// to capture stderr with [`LocalRepoBuilder::capture_stderr()`];
+    /// otherwise, it returns [`LocalRepoError::CommandFailed`].
+    NotARepository(PathBuf),
+
+    /// Returned by methods of a [`LocalRepo`] using [`GitCommand`] with
+    /// captured stderr if Git refused to operate on a repository owned by
+    /// another user that is not listed in the `safe.directory` config
+    /// setting.  The field is the path to the repository as reported by Git.
+    DubiousOwnership(PathBuf),
+
+    /// Returned by methods of a [`LocalRepo`] if a Git configuration file
+    /// could not be parsed.  The [`GitCommand`] backend only returns this
+    /// error if it was configured to capture stderr.
+    InvalidConfig(ConfigError),
+
+    /// Returned by [`GHRepo::clone_into()`] if no URL style was given and the
+    /// `ghrepo.protocol` Git config setting has an unrecognized value.  The
+    /// field is the value of the setting.
+    InvalidProtocol(String),
+
+    /// Returned by methods of a [`LocalRepo`] using [`NativeGit`] if a file in
+    /// the repository or a configuration file could not be read
+    ReadError(PathBuf, io::Error),
+
+    /// Returned when a [`GitBackend`] fails for a reason specific to that
+    /// backend, such as an error from an underlying Git library
+    BackendError(Box<dyn error::Error + Send + Sync + 'static>),
+
+    /// Returned when the output from Git could not be decoded
+    InvalidUtf8(str::Utf8Error),
+
+    /// Returned when the remote URL is not a GitHub URL
+    InvalidRemoteURL(ParseError),
+
+    /// Returned by [`LocalRepo::set_remote_url_style()`] and other methods
+    /// taking a [`UrlStyle`] if the style's GitHub Enterprise Server host is
+    /// empty or contains whitespace, `/`, `@`, or `:`.  The field is the
+    /// host.
+    InvalidHost(String),
 }
 
 impl fmt::Display for LocalRepoError {
@@ -522,8 +2641,11 @@ impl fmt::Display for LocalRepoError {
             LocalRepoError::CouldNotExecute(e) => {
                 write!(f, "failed to execute Git command: {e}")
             }
-            LocalRepoError::CommandFailed(r) => {
-                write!(f, "Git command exited unsuccessfully: {r}")
+            LocalRepoError::CommandFailed(e) => {
+                write!(f, "Git command exited unsuccessfully: {e}")
+            }
+            LocalRepoError::Timeout(timeout) => {
+                write!(f, "Git command timed out after {timeout:?}")
             }
             LocalRepoError::CurdirError(e) => {
                 write!(f, "could not determine current directory: {e}")
@@ -534,18 +2656,75 @@ impl fmt::Display for LocalRepoError {
             LocalRepoError::NoSuchRemote(remote) => {
                 write!(f, "no such remote in Git repository: {remote:?}")
             }
+            LocalRepoError::RemoteExists(remote) => {
+                write!(
+                    f,
+                    "remote already exists in Git repository with a different URL: {remote:?}"
+                )
+            }
             LocalRepoError::NoUpstream(branch) => {
                 write!(
                     f,
                     "no upstream remote configured for Git branch: {branch:?}"
                 )
             }
+            LocalRepoError::LocalPushRemote(branch) => {
+                write!(
+                    f,
+                    "Git branch is pushed to the local repository: {branch:?}"
+                )
+            }
+            LocalRepoError::NoRemoteHead(remote) => {
+                write!(f, "HEAD ref not set for Git remote: {remote:?}")
+            }
+            LocalRepoError::NoHeadCommit => {
+                write!(f, "HEAD does not point to a commit")
+            }
+            LocalRepoError::NotInWorkTree(path) => {
+                write!(
+                    f,
+                    "path is not inside the repository's working tree: {}",
+                    path.display()
+                )
+            }
+            LocalRepoError::Unsupported(op) => {
+                write!(f, "operation not supported by Git backend: {op}")
+            }
+            LocalRepoError::NoDefaultRemote => {
+                write!(
+                    f,
+                    "could not determine default GitHub remote for Git repository"
+                )
+            }
+            LocalRepoError::NotARepository(path) => {
+                write!(f, "not a Git repository: {}", path.display())
+            }
+            LocalRepoError::DubiousOwnership(path) => {
+                write!(
+                    f,
+                    "Git repository is owned by another user and not listed in safe.directory: {}",
+                    path.display()
+                )
+            }
+            LocalRepoError::InvalidConfig(e) => {
+                write!(f, "invalid Git configuration: {e}")
+            }
+            LocalRepoError::InvalidProtocol(value) => {
+                write!(f, "invalid ghrepo.protocol setting: {value:?}")
+            }
+            LocalRepoError::ReadError(path, e) => {
+                write!(f, "failed to read {}: {e}", path.display())
+            }
+            LocalRepoError::BackendError(e) => write!(f, "Git backend failed: {e}"),
             LocalRepoError::InvalidUtf8(e) => {
                 write!(f, "failed to decode output from Git command: {e}")
             }
             LocalRepoError::InvalidRemoteURL(e) => {
                 write!(f, "repository remote URL is not a GitHub URL: {e}")
             }
+            LocalRepoError::InvalidHost(host) => {
+                write!(f, "invalid GitHub Enterprise Server host: {host:?}")
+            }
         }
     }
 }
@@ -554,17 +2733,72 @@ impl error::Error for LocalRepoError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             LocalRepoError::CouldNotExecute(e) => Some(e),
-            LocalRepoError::CommandFailed(_) => None,
+            LocalRepoError::CommandFailed(e) => Some(e),
+            LocalRepoError::Timeout(_) => None,
             LocalRepoError::CurdirError(e) => Some(e),
             LocalRepoError::DetachedHead => None,
             LocalRepoError::NoSuchRemote(_) => None,
+            LocalRepoError::RemoteExists(_) => None,
             LocalRepoError::NoUpstream(_) => None,
+            LocalRepoError::LocalPushRemote(_) => None,
+            LocalRepoError::NoRemoteHead(_) => None,
+            LocalRepoError::NoHeadCommit => None,
+            LocalRepoError::NotInWorkTree(_) => None,
+            LocalRepoError::Unsupported(_) => None,
+            LocalRepoError::NoDefaultRemote => None,
+            LocalRepoError::NotARepository(_) => None,
+            LocalRepoError::DubiousOwnership(_) => None,
+            LocalRepoError::InvalidConfig(e) => Some(e),
+            LocalRepoError::InvalidProtocol(_) => None,
+            LocalRepoError::ReadError(_, e) => Some(e),
+            LocalRepoError::BackendError(e) => Some(&**e),
             LocalRepoError::InvalidUtf8(e) => Some(e),
             LocalRepoError::InvalidRemoteURL(e) => Some(e),
+            LocalRepoError::InvalidHost(_) => None,
         }
     }
 }
 
+/// Details on a Git command that exited unsuccessfully, as contained in
+/// [`LocalRepoError::CommandFailed`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandError {
+    argv: Vec<OsString>,
+    status: ExitStatus,
+    stderr: Option<String>,
+}
+
+impl CommandError {
+    /// The command that was run, including the path to the Git executable
+    pub fn argv(&self) -> &[OsString] {
+        &self.argv
+    }
+
+    /// The command's exit status
+    pub fn status(&self) -> ExitStatus {
+        self.status
+    }
+
+    /// The command's stderr output, with trailing whitespace removed.  This is
+    /// `None` unless the [`LocalRepo`] was configured to capture stderr with
+    /// [`LocalRepoBuilder::capture_stderr()`].
+    pub fn stderr(&self) -> Option<&str> {
+        self.stderr.as_deref()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        match self.stderr.as_deref() {
+            Some(stderr) if !stderr.is_empty() => write!(f, ": {stderr}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl error::Error for CommandError {}
+
 impl From<str::Utf8Error> for LocalRepoError {
     fn from(e: str::Utf8Error) -> LocalRepoError {
         LocalRepoError::InvalidUtf8(e)
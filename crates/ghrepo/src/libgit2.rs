@@ -0,0 +1,377 @@
+//! A [`GitBackend`] built on the [`git2`] crate
+use crate::backend::GitBackend;
+use crate::gitconfig::Config;
+use crate::native;
+use crate::{Description, FileStatus, LocalRepoError, RepoLayout, RepoState, WorkTreeStatus};
+use git2::{
+    Branch, DescribeFormatOptions, DescribeOptions, ErrorClass, ErrorCode, ObjectType, Oid,
+    Repository, RepositoryOpenFlags, Status, StatusOptions,
+};
+use std::path::{Path, PathBuf};
+
+/// A [`GitBackend`] that inspects repositories using [libgit2] via the
+/// [`git2`] crate, without running Git
+///
+/// [libgit2]: https://libgit2.org
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LibGit2;
+
+impl LibGit2 {
+    /// (Private) Locate and open the repository containing `path`, not
+    /// searching above any directories listed in `GIT_CEILING_DIRECTORIES`
+    fn discover(path: &Path) -> Result<Repository, git2::Error> {
+        Repository::open_ext(path, RepositoryOpenFlags::empty(), native::ceiling_dirs())
+    }
+
+    /// (Private) Open the repository containing `path`
+    fn open(path: &Path) -> Result<Repository, LocalRepoError> {
+        LibGit2::discover(path).map_err(|e| {
+            if e.code() == ErrorCode::NotFound {
+                LocalRepoError::NotARepository(path.to_path_buf())
+            } else {
+                LocalRepoError::BackendError(Box::new(e))
+            }
+        })
+    }
+
+    /// (Private) Determine the path to report for the repository containing
+    /// `path` when it fails libgit2's ownership check: the top level of its
+    /// working tree, or its Git directory if it has no working tree, as Git
+    /// itself reports.  If the repository cannot be located, `path` is
+    /// returned instead.
+    fn unsafe_repo_path(path: &Path) -> PathBuf {
+        match native::GitDirs::discover(path) {
+            Ok(Some(dirs)) => dirs.toplevel.unwrap_or(dirs.git_dir),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// (Private) Load the configuration of the repository containing `path`
+    fn load_config(path: &Path) -> Result<Config, LocalRepoError> {
+        let mut config = Config::default();
+        LibGit2::open(path)?
+            .config()
+            .and_then(|cfg| {
+                cfg.entries(None)?.for_each(|entry| {
+                    if let Some(name) = entry.name() {
+                        config.push(name, entry.value().unwrap_or_default());
+                    }
+                })
+            })
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))?;
+        Ok(config)
+    }
+}
+
+impl GitBackend for LibGit2 {
+    fn is_git_repo(&self, path: &Path) -> Result<bool, LocalRepoError> {
+        match LibGit2::open(path) {
+            Ok(_) => Ok(true),
+            Err(LocalRepoError::NotARepository(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn inspect(&self, path: &Path) -> Result<RepoState, LocalRepoError> {
+        match LibGit2::discover(path) {
+            Ok(repo) => Ok(native::repo_state(
+                path,
+                repo.path(),
+                repo.workdir(),
+                repo.is_bare(),
+            )),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(RepoState::NotARepository),
+            Err(e) if e.code() == ErrorCode::Owner => Ok(RepoState::UnsafeOwnership {
+                path: LibGit2::unsafe_repo_path(path),
+            }),
+            Err(e) => Ok(RepoState::Corrupt {
+                message: e.message().to_string(),
+            }),
+        }
+    }
+
+    fn layout(&self, path: &Path) -> Result<RepoLayout, LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        Ok(native::repo_layout(
+            path,
+            repo.path(),
+            repo.commondir(),
+            repo.workdir(),
+            repo.is_bare(),
+        ))
+    }
+
+    fn current_branch(&self, path: &Path) -> Result<String, LocalRepoError> {
+        match self.symbolic_ref(path, "HEAD")? {
+            Some(target) => Ok(target
+                .strip_prefix("refs/heads/")
+                .map_or_else(|| target.clone(), ToString::to_string)),
+            None => Err(LocalRepoError::DetachedHead),
+        }
+    }
+
+    fn config_get(&self, path: &Path, key: &str) -> Result<Option<String>, LocalRepoError> {
+        Ok(LibGit2::load_config(path)?
+            .get(key)
+            .map(ToString::to_string))
+    }
+
+    fn config_list(&self, path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+        Ok(LibGit2::load_config(path)?.entries().to_vec())
+    }
+
+    fn remote_names(&self, path: &Path) -> Result<Vec<String>, LocalRepoError> {
+        Ok(native::remote_names(&LibGit2::load_config(path)?))
+    }
+
+    fn remote_urls(
+        &self,
+        path: &Path,
+        remote: &str,
+        push: bool,
+    ) -> Result<Vec<String>, LocalRepoError> {
+        native::remote_urls(&LibGit2::load_config(path)?, remote, push)
+            .ok_or_else(|| LocalRepoError::NoSuchRemote(remote.to_string()))
+    }
+
+    fn symbolic_ref(&self, path: &Path, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        match LibGit2::open(path)?.find_reference(refname) {
+            Ok(r) => Ok(r.symbolic_target().map(ToString::to_string)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+    }
+
+    fn head_commit(&self, path: &Path) -> Result<Option<String>, LocalRepoError> {
+        match LibGit2::open(path)?.head().and_then(|r| r.peel_to_commit()) {
+            Ok(commit) => Ok(Some(commit.id().to_string())),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+    }
+
+    fn file_status(&self, path: &Path, file: &str) -> Result<FileStatus, LocalRepoError> {
+        match LibGit2::open(path)?.status_file(Path::new(file)) {
+            Ok(status) if status.intersects(Status::WT_NEW | Status::IGNORED) => {
+                Ok(FileStatus::Untracked)
+            }
+            Ok(status) if status.is_empty() => Ok(FileStatus::Unmodified),
+            Ok(_) => Ok(FileStatus::Modified),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(FileStatus::Untracked),
+            Err(e) => Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+    }
+
+    fn remote_contains(
+        &self,
+        path: &Path,
+        remote: &str,
+        commit: &str,
+    ) -> Result<bool, LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        let contains = || -> Result<bool, git2::Error> {
+            let commit = Oid::from_str(commit)?;
+            for r in repo.references_glob(&format!("refs/remotes/{remote}/*"))? {
+                if let Some(tip) = r?.resolve()?.target() {
+                    if tip == commit || repo.graph_descendant_of(tip, commit)? {
+                        return Ok(true);
+                    }
+                }
+            }
+            Ok(false)
+        };
+        contains().map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+
+    fn tags_at(&self, path: &Path, commit: &str) -> Result<Vec<String>, LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        let tags = || -> Result<Vec<String>, git2::Error> {
+            let commit = Oid::from_str(commit)?;
+            let mut tags = Vec::new();
+            for r in repo.references_glob("refs/tags/*")? {
+                let r = r?;
+                let Some(name) = r.shorthand().map(ToString::to_string) else {
+                    continue;
+                };
+                if r.peel(ObjectType::Commit)
+                    .is_ok_and(|obj| obj.id() == commit)
+                {
+                    tags.push(name);
+                }
+            }
+            tags.sort();
+            Ok(tags)
+        };
+        tags().map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+
+    fn describe(&self, path: &Path) -> Result<Option<Description>, LocalRepoError> {
+        if self.head_commit(path)?.is_none() {
+            return Err(LocalRepoError::NoHeadCommit);
+        }
+        let repo = LibGit2::open(path)?;
+        let described = repo
+            .describe(DescribeOptions::new().describe_tags())
+            .and_then(|d| {
+                d.format(Some(
+                    DescribeFormatOptions::new()
+                        .abbreviated_size(40)
+                        .always_use_long_format(true),
+                ))
+            });
+        match described {
+            Ok(output) => Ok(native::parse_describe(&output)),
+            // libgit2 reports a generic describe error when no tags are found
+            Err(e) if e.code() == ErrorCode::NotFound || e.class() == ErrorClass::Describe => {
+                Ok(None)
+            }
+            Err(e) => Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+    }
+
+    fn status(&self, path: &Path) -> Result<WorkTreeStatus, LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        let status = || -> Result<WorkTreeStatus, git2::Error> {
+            let mut status = WorkTreeStatus::default();
+            let mut opts = StatusOptions::new();
+            opts.include_untracked(true)
+                .recurse_untracked_dirs(false)
+                .renames_head_to_index(true);
+            for entry in repo.statuses(Some(&mut opts))?.iter() {
+                let flags = entry.status();
+                if flags.contains(Status::CONFLICTED) {
+                    status.conflicted += 1;
+                    continue;
+                }
+                if flags.contains(Status::WT_NEW) {
+                    status.untracked += 1;
+                }
+                if flags.intersects(
+                    Status::INDEX_NEW
+                        | Status::INDEX_MODIFIED
+                        | Status::INDEX_DELETED
+                        | Status::INDEX_RENAMED
+                        | Status::INDEX_TYPECHANGE,
+                ) {
+                    status.staged += 1;
+                }
+                if flags.intersects(
+                    Status::WT_MODIFIED
+                        | Status::WT_DELETED
+                        | Status::WT_RENAMED
+                        | Status::WT_TYPECHANGE,
+                ) {
+                    status.unstaged += 1;
+                }
+            }
+            status.branch = repo
+                .find_reference("HEAD")?
+                .symbolic_target()
+                .map(|target| {
+                    target
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(target)
+                        .to_string()
+                });
+            let head = match repo.head() {
+                Ok(head) => {
+                    status.commit = head.target().map(|oid| oid.to_string());
+                    if !head.is_branch() {
+                        return Ok(status);
+                    }
+                    Branch::wrap(head)
+                }
+                Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+                    return Ok(status);
+                }
+                Err(e) => return Err(e),
+            };
+            let upstream = match head.upstream() {
+                Ok(upstream) => upstream,
+                Err(e) if e.code() == ErrorCode::NotFound => return Ok(status),
+                Err(e) => return Err(e),
+            };
+            if let (Some(local), Some(upstream)) = (head.get().target(), upstream.get().target()) {
+                (status.ahead, status.behind) = repo.graph_ahead_behind(local, upstream)?;
+            }
+            Ok(status)
+        };
+        status().map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+
+    fn shares_history(
+        &self,
+        path: &Path,
+        remote1: &str,
+        remote2: &str,
+    ) -> Result<Option<bool>, LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        let tips = |remote: &str| -> Result<Vec<Oid>, git2::Error> {
+            let mut tips = Vec::new();
+            for r in repo.references_glob(&format!("refs/remotes/{remote}/*"))? {
+                if let Some(tip) = r?.resolve()?.target() {
+                    tips.push(tip);
+                }
+            }
+            Ok(tips)
+        };
+        let shares = || -> Result<Option<bool>, git2::Error> {
+            let (tips1, tips2) = (tips(remote1)?, tips(remote2)?);
+            if tips1.is_empty() || tips2.is_empty() {
+                return Ok(None);
+            }
+            for &tip1 in &tips1 {
+                for &tip2 in &tips2 {
+                    match repo.merge_base(tip1, tip2) {
+                        Ok(_) => return Ok(Some(true)),
+                        Err(e) if e.code() == ErrorCode::NotFound => (),
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Ok(Some(false))
+        };
+        shares().map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+
+    fn set_remote_url(&self, path: &Path, remote: &str, url: &str) -> Result<(), LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        match repo.find_remote(remote) {
+            Ok(_) => (),
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+            }
+            Err(e) => return Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+        repo.remote_set_url(remote, url)
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+
+    fn add_remote(&self, path: &Path, name: &str, url: &str) -> Result<(), LocalRepoError> {
+        match LibGit2::open(path)?.remote(name, url) {
+            Ok(_) => Ok(()),
+            Err(e) if e.code() == ErrorCode::Exists => {
+                Err(LocalRepoError::RemoteExists(name.to_string()))
+            }
+            Err(e) => Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+    }
+
+    fn add_fetch_refspec(
+        &self,
+        path: &Path,
+        remote: &str,
+        refspec: &str,
+    ) -> Result<(), LocalRepoError> {
+        let repo = LibGit2::open(path)?;
+        match repo.find_remote(remote) {
+            Ok(_) => (),
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                return Err(LocalRepoError::NoSuchRemote(remote.to_string()));
+            }
+            Err(e) => return Err(LocalRepoError::BackendError(Box::new(e))),
+        }
+        repo.remote_add_fetch(remote, refspec)
+            .map_err(|e| LocalRepoError::BackendError(Box::new(e)))
+    }
+}
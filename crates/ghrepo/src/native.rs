@@ -0,0 +1,833 @@
+//! Implementation of the [`NativeGit`][crate::NativeGit] backend for
+//! [`LocalRepo`][crate::LocalRepo], which reads a repository's files directly
+//! instead of running Git
+use crate::gitconfig::{Config, ConfigError, IncludeContext, LoadError, home_dir, parse_config};
+use crate::gitindex::parse_gitlinks;
+use crate::{Description, LocalRepoError, RepoLayout, RepoState, WorkTreeStatus, Worktree};
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Maximum number of symbolic refs to follow when resolving a ref, matching
+/// the limit used by Git
+const MAX_SYMREF_DEPTH: usize = 5;
+
+/// The locations of a repository's files
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct GitDirs {
+    /// The repository's Git directory (the `.git` directory for a non-bare
+    /// repository, or the worktree-specific directory under `.git/worktrees/`
+    /// for a linked worktree)
+    pub(crate) git_dir: PathBuf,
+
+    /// The directory containing the repository's shared files (config, refs,
+    /// objects, etc.); this is the same as `git_dir` except for linked
+    /// worktrees
+    pub(crate) common_dir: PathBuf,
+
+    /// The top-level directory of the working tree, if the repository was
+    /// found via a `.git` directory or file in that directory
+    pub(crate) toplevel: Option<PathBuf>,
+}
+
+impl GitDirs {
+    /// Locate the Git directory for the repository at or containing `path`,
+    /// not searching above any directories listed in
+    /// `GIT_CEILING_DIRECTORIES`.  Returns `None` if no repository is found.
+    pub(crate) fn discover(path: &Path) -> Result<Option<GitDirs>, LocalRepoError> {
+        let path = absolute(path)?;
+        let ceilings = ceiling_dirs();
+        let mut dir = Some(path.as_path());
+        while let Some(d) = dir {
+            let dotgit = d.join(".git");
+            if dotgit.is_file() {
+                let git_dir = read_gitfile(&dotgit)?;
+                if is_git_dir(&git_dir) {
+                    return Ok(Some(GitDirs::for_git_dir(git_dir, Some(d))?));
+                }
+            } else if is_git_dir(&dotgit) {
+                return Ok(Some(GitDirs::for_git_dir(dotgit, Some(d))?));
+            } else if is_git_dir(d) {
+                return Ok(Some(GitDirs::for_git_dir(d.to_path_buf(), None)?));
+            }
+            dir = d
+                .parent()
+                .filter(|p| !ceilings.iter().any(|c| c == p || canonical(p) == *c));
+        }
+        Ok(None)
+    }
+
+    /// Locate the common directory of the repository with the Git directory
+    /// `git_dir`.  As with the paths reported by Git, all of the directories
+    /// have their symbolic links resolved.
+    pub(crate) fn for_git_dir(
+        git_dir: PathBuf,
+        toplevel: Option<&Path>,
+    ) -> Result<GitDirs, LocalRepoError> {
+        let common_dir = match read_file(&git_dir.join("commondir"))? {
+            Some(s) => normalize(&git_dir.join(s.trim_end_matches(['\n', '\r']))),
+            None => git_dir.clone(),
+        };
+        Ok(GitDirs {
+            git_dir: canonical(&git_dir),
+            common_dir: canonical(&common_dir),
+            toplevel: toplevel.map(canonical),
+        })
+    }
+
+    /// Return the locations of the repository's files and of the directory
+    /// `path` within its working tree
+    pub(crate) fn layout(self, path: &Path) -> RepoLayout {
+        let prefix = match self.toplevel {
+            Some(ref toplevel) => relative_to(path, toplevel),
+            None => PathBuf::new(),
+        };
+        RepoLayout {
+            toplevel: self.toplevel,
+            git_dir: self.git_dir,
+            common_dir: self.common_dir,
+            prefix,
+        }
+    }
+
+    /// Determine the [`RepoState`] of a directory in the repository, given the
+    /// repository's configuration
+    pub(crate) fn state(self, config: &Config) -> RepoState {
+        if let Some(toplevel) = self.toplevel {
+            return RepoState::WorkTree {
+                toplevel,
+                git_dir: self.git_dir,
+            };
+        }
+        // When `core.bare` is unset, Git guesses based on whether the Git
+        // directory is named `.git`.
+        let bare = match config.get("core.bare") {
+            Some(value) => is_true(value),
+            None => self.git_dir.file_name() != Some(OsStr::new(".git")),
+        };
+        if bare {
+            RepoState::Bare {
+                git_dir: self.git_dir,
+            }
+        } else {
+            RepoState::InsideGitDir {
+                git_dir: self.git_dir,
+            }
+        }
+    }
+
+    /// Read the contents of `HEAD`
+    pub(crate) fn head(&self) -> Result<Head, LocalRepoError> {
+        match read_ref_file(&self.git_dir.join("HEAD"))? {
+            Some(Head::Symbolic(target)) => Ok(Head::Symbolic(target)),
+            Some(Head::Detached(oid)) => Ok(Head::Detached(oid)),
+            None => Err(LocalRepoError::NotARepository(self.git_dir.clone())),
+        }
+    }
+
+    /// Return the name of the currently checked-out branch, if any
+    pub(crate) fn current_branch(&self) -> Result<Option<String>, LocalRepoError> {
+        match self.head()? {
+            Head::Symbolic(target) => Ok(Some(
+                target
+                    .strip_prefix("refs/heads/")
+                    .map_or_else(|| target.clone(), ToString::to_string),
+            )),
+            Head::Detached(_) => Ok(None),
+        }
+    }
+
+    /// If the ref `refname` is a symbolic ref, return its target.  Only loose
+    /// refs are examined, as packed refs cannot be symbolic.
+    pub(crate) fn symbolic_ref(&self, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        let base = if refname == "HEAD" {
+            &self.git_dir
+        } else {
+            &self.common_dir
+        };
+        match read_ref_file(&base.join(refname))? {
+            Some(Head::Symbolic(target)) => Ok(Some(target)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Return the object ID that the ref `refname` resolves to after following
+    /// any symbolic refs, or `None` if the ref (or a ref it points to) does not
+    /// exist
+    pub(crate) fn resolve_ref(&self, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        let mut refname = refname.to_string();
+        for _ in 0..MAX_SYMREF_DEPTH {
+            let base = if refname == "HEAD" {
+                &self.git_dir
+            } else {
+                &self.common_dir
+            };
+            match read_ref_file(&base.join(&refname))? {
+                Some(Head::Symbolic(target)) => refname = target,
+                Some(Head::Detached(oid)) => return Ok(Some(oid)),
+                None => return self.packed_ref(&refname),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up `refname` in the repository's `packed-refs` file
+    fn packed_ref(&self, refname: &str) -> Result<Option<String>, LocalRepoError> {
+        let Some(packed) = read_file(&self.common_dir.join("packed-refs"))? else {
+            return Ok(None);
+        };
+        Ok(packed
+            .lines()
+            .filter(|line| !line.starts_with(['#', '^']))
+            .filter_map(|line| line.split_once(' '))
+            .find(|&(_, name)| name == refname)
+            .map(|(oid, _)| oid.to_string()))
+    }
+
+    /// Load the configuration that applies to the repository, including the
+    /// system & global configuration files
+    pub(crate) fn load_config(&self) -> Result<Config, LocalRepoError> {
+        let ctx = IncludeContext {
+            git_dir: Some(self.git_dir.clone()),
+            branch: self.current_branch().ok().flatten(),
+        };
+        let mut config = Config::default();
+        if !env_flag("GIT_CONFIG_NOSYSTEM") {
+            match env::var_os("GIT_CONFIG_SYSTEM") {
+                Some(p) => read_config(&mut config, Path::new(&p), &ctx)?,
+                // Git's actual system config lives under its installation
+                // prefix, which can't be determined without running Git.
+                None if cfg!(unix) => {
+                    read_config(&mut config, Path::new("/etc/gitconfig"), &ctx)?;
+                }
+                None => (),
+            }
+        }
+        if let Some(p) = env::var_os("GIT_CONFIG_GLOBAL") {
+            read_config(&mut config, Path::new(&p), &ctx)?;
+        } else {
+            let xdg = env::var_os("XDG_CONFIG_HOME")
+                .filter(|s| !s.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home_dir().map(|home| home.join(".config")));
+            if let Some(xdg) = xdg {
+                read_config(&mut config, &xdg.join("git").join("config"), &ctx)?;
+            }
+            if let Some(home) = home_dir() {
+                read_config(&mut config, &home.join(".gitconfig"), &ctx)?;
+            }
+        }
+        read_config(&mut config, &self.common_dir.join("config"), &ctx)?;
+        if config.get("extensions.worktreeConfig").is_some_and(is_true) {
+            read_config(&mut config, &self.git_dir.join("config.worktree"), &ctx)?;
+        }
+        if let Some(count) = env::var("GIT_CONFIG_COUNT")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+        {
+            for i in 0..count {
+                if let (Ok(key), Ok(value)) = (
+                    env::var(format!("GIT_CONFIG_KEY_{i}")),
+                    env::var(format!("GIT_CONFIG_VALUE_{i}")),
+                ) {
+                    config.push(&key, &value);
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The contents of a `HEAD` or other ref file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Head {
+    /// The ref points to another ref
+    Symbolic(String),
+
+    /// The ref points directly to a commit
+    Detached(String),
+}
+
+/// Return the working trees of the repository whose common directory is
+/// `common_dir`, starting with the main working tree and followed by any
+/// linked worktrees sorted by path.  If `bare` is true, the main "working
+/// tree" is the bare repository itself.  `core_worktree` is the value of the
+/// `core.worktree` config setting, which submodules use to locate their
+/// working trees.
+pub(crate) fn worktrees(
+    common_dir: &Path,
+    bare: bool,
+    core_worktree: Option<&str>,
+) -> Result<Vec<Worktree>, LocalRepoError> {
+    let main = if bare {
+        Worktree {
+            path: common_dir.to_path_buf(),
+            branch: None,
+            bare: true,
+            locked: false,
+        }
+    } else {
+        Worktree {
+            path: match core_worktree {
+                Some(wt) => normalize(&common_dir.join(wt)),
+                None => common_dir.parent().unwrap_or(common_dir).to_path_buf(),
+            },
+            branch: head_branch(&common_dir.join("HEAD"))?,
+            bare: false,
+            locked: false,
+        }
+    };
+    let admin_dir = common_dir.join("worktrees");
+    let entries = match fs::read_dir(&admin_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![main]),
+        Err(e) => return Err(LocalRepoError::ReadError(admin_dir, e)),
+    };
+    let mut linked = Vec::new();
+    for entry in entries {
+        let dir = entry
+            .map_err(|e| LocalRepoError::ReadError(admin_dir.clone(), e))?
+            .path();
+        let Some(gitfile) = read_file(&dir.join("gitdir"))? else {
+            continue;
+        };
+        let gitfile = normalize(&dir.join(gitfile.trim_end_matches(['\n', '\r'])));
+        linked.push(Worktree {
+            path: gitfile.parent().unwrap_or(&gitfile).to_path_buf(),
+            branch: head_branch(&dir.join("HEAD"))?,
+            bare: false,
+            locked: dir.join("locked").exists(),
+        });
+    }
+    linked.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut worktrees = vec![main];
+    worktrees.extend(linked);
+    Ok(worktrees)
+}
+
+/// Read the index file at `path` and return the path & commit hash of each
+/// gitlink entry.  If the file does not exist, an empty list is returned.
+pub(crate) fn read_gitlinks(
+    path: &Path,
+    hash_len: usize,
+) -> Result<Vec<(String, String)>, LocalRepoError> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(LocalRepoError::ReadError(path.to_path_buf(), e)),
+    };
+    parse_gitlinks(&data, hash_len).map_err(|e| {
+        LocalRepoError::ReadError(
+            path.to_path_buf(),
+            io::Error::new(io::ErrorKind::InvalidData, e),
+        )
+    })
+}
+
+/// Read the `.gitmodules` file at `path` and return its settings.  If the
+/// file does not exist, an empty list is returned.
+pub(crate) fn read_gitmodules(path: &Path) -> Result<Vec<(String, String)>, LocalRepoError> {
+    let Some(src) = read_file(path)? else {
+        return Ok(Vec::new());
+    };
+    parse_config(&src)
+        .map_err(|line| LocalRepoError::InvalidConfig(ConfigError::new(path.to_path_buf(), line)))
+}
+
+/// Return the short name of the branch that the `HEAD` file at `path` points
+/// to, or `None` if it is detached or does not exist
+fn head_branch(path: &Path) -> Result<Option<String>, LocalRepoError> {
+    match read_ref_file(path)? {
+        Some(Head::Symbolic(target)) => Ok(Some(
+            target
+                .strip_prefix("refs/heads/")
+                .map_or_else(|| target.clone(), ToString::to_string),
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Parse the output of `git describe --tags --long --abbrev=40 --always` (or
+/// the equivalent output from another backend).  Returns `None` if the output
+/// is just a commit hash, i.e., no tag was found.
+pub(crate) fn parse_describe(output: &str) -> Option<Description> {
+    let (rest, commit) = output.rsplit_once("-g")?;
+    let (tag, distance) = rest.rsplit_once('-')?;
+    if tag.is_empty() || !commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(Description {
+        tag: tag.to_string(),
+        distance: distance.parse().ok()?,
+        commit: commit.to_string(),
+    })
+}
+
+/// Parse the output of `git status --porcelain=v2 --branch -z`
+pub(crate) fn parse_status(output: &str) -> WorkTreeStatus {
+    let mut status = WorkTreeStatus::default();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let mut fields = record.split(' ');
+        match fields.next() {
+            Some("#") => match (fields.next(), fields.next()) {
+                (Some("branch.head"), Some(head)) if head != "(detached)" => {
+                    status.branch = Some(head.to_string());
+                }
+                (Some("branch.oid"), Some(oid)) if oid != "(initial)" => {
+                    status.commit = Some(oid.to_string());
+                }
+                (Some("branch.ab"), Some(ahead)) => {
+                    status.ahead = ahead
+                        .get(1..)
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or_default();
+                    status.behind = fields
+                        .next()
+                        .and_then(|s| s.get(1..)?.parse().ok())
+                        .unwrap_or_default();
+                }
+                _ => (),
+            },
+            Some(kind @ ("1" | "2")) => {
+                let xy = fields.next().unwrap_or_default().as_bytes();
+                if xy.first().is_some_and(|&x| x != b'.') {
+                    status.staged += 1;
+                }
+                if xy.get(1).is_some_and(|&y| y != b'.') {
+                    status.unstaged += 1;
+                }
+                if kind == "2" {
+                    // Renames & copies are followed by a record containing
+                    // the original path
+                    records.next();
+                }
+            }
+            Some("u") => status.conflicted += 1,
+            Some("?") => status.untracked += 1,
+            _ => (),
+        }
+    }
+    status
+}
+
+/// Return the names of all remotes configured in `config`, sorted by name
+pub(crate) fn remote_names(config: &Config) -> Vec<String> {
+    let mut names = Vec::new();
+    for (key, _) in config.entries() {
+        if let Some(name) = key
+            .strip_prefix("remote.")
+            .and_then(|s| s.rsplit_once('.'))
+            .map(|(name, _)| name)
+        {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    names
+}
+
+/// Return the fetch or push URLs of the given remote after applying
+/// `url.<base>.insteadOf` and `url.<base>.pushInsteadOf` rewrites, or `None`
+/// if the remote is not configured
+pub(crate) fn remote_urls(config: &Config, remote: &str, push: bool) -> Option<Vec<String>> {
+    if !remote_names(config).iter().any(|n| n == remote) {
+        return None;
+    }
+    let urls = config
+        .get_all(&format!("remote.{remote}.url"))
+        .collect::<Vec<_>>();
+    if !push {
+        return Some(
+            urls.into_iter()
+                .map(|u| rewrite_url(config, u, false))
+                .collect(),
+        );
+    }
+    let pushurls = config
+        .get_all(&format!("remote.{remote}.pushurl"))
+        .collect::<Vec<_>>();
+    if !pushurls.is_empty() {
+        return Some(
+            pushurls
+                .into_iter()
+                .map(|u| rewrite_url(config, u, false))
+                .collect(),
+        );
+    }
+    Some(
+        urls.into_iter()
+            .map(|u| rewrite_url(config, u, true))
+            .collect(),
+    )
+}
+
+/// Apply the longest-matching `url.<base>.insteadOf` (or, if `push` is true
+/// and one matches, `url.<base>.pushInsteadOf`) rewrite to `url`
+pub(crate) fn rewrite_url(config: &Config, url: &str, push: bool) -> String {
+    if push {
+        if let Some(rewritten) = apply_rewrites(config, url, ".pushinsteadof") {
+            return rewritten;
+        }
+    }
+    apply_rewrites(config, url, ".insteadof").unwrap_or_else(|| url.to_string())
+}
+
+fn apply_rewrites(config: &Config, url: &str, suffix: &str) -> Option<String> {
+    let mut best: Option<(&str, &str)> = None;
+    for (key, value) in config.entries() {
+        if let Some(base) = key
+            .strip_prefix("url.")
+            .and_then(|s| s.strip_suffix(suffix))
+        {
+            if url.starts_with(value.as_str())
+                && best.is_none_or(|(prefix, _)| value.len() > prefix.len())
+            {
+                best = Some((value, base));
+            }
+        }
+    }
+    best.map(|(prefix, base)| format!("{base}{}", &url[prefix.len()..]))
+}
+
+/// Determine the [`RepoLayout`] of a repository opened by a Git library and
+/// the directory `path` within it, given the repository's Git directory,
+/// common directory, & working tree and whether it's bare.  Symbolic links in
+/// the reported directories are resolved, as Git does.
+#[cfg(any(feature = "git2", feature = "gix"))]
+pub(crate) fn repo_layout(
+    path: &Path,
+    git_dir: &Path,
+    common_dir: &Path,
+    workdir: Option<&Path>,
+    bare: bool,
+) -> RepoLayout {
+    let toplevel = match workdir {
+        Some(toplevel) if !bare && !is_within(path, git_dir) => Some(canonical(toplevel)),
+        _ => None,
+    };
+    let prefix = match toplevel {
+        Some(ref toplevel) => relative_to(path, toplevel),
+        None => PathBuf::new(),
+    };
+    RepoLayout {
+        toplevel,
+        git_dir: canonical(&normalize(git_dir)),
+        common_dir: canonical(&normalize(common_dir)),
+        prefix,
+    }
+}
+
+/// Determine the [`RepoState`] of the directory `path` in a repository opened
+/// by a Git library, given the repository's Git directory & working tree and
+/// whether it's bare.  Symbolic links in the reported directories are
+/// resolved, as Git does.
+#[cfg(any(feature = "git2", feature = "gix"))]
+pub(crate) fn repo_state(
+    path: &Path,
+    git_dir: &Path,
+    workdir: Option<&Path>,
+    bare: bool,
+) -> RepoState {
+    let git_dir = canonical(&normalize(git_dir));
+    match workdir {
+        Some(toplevel) if !bare && !is_within(path, &git_dir) => RepoState::WorkTree {
+            toplevel: canonical(toplevel),
+            git_dir,
+        },
+        _ if bare => RepoState::Bare { git_dir },
+        _ => RepoState::InsideGitDir { git_dir },
+    }
+}
+
+/// Test whether `path` is `dir` or a descendant of it, after resolving
+/// symbolic links
+#[cfg(any(feature = "git2", feature = "gix"))]
+fn is_within(path: &Path, dir: &Path) -> bool {
+    canonical(path).starts_with(canonical(dir))
+}
+
+/// Return the path of `path` relative to its ancestor `dir`, resolving
+/// symbolic links if necessary.  Returns an empty path if `path` is not
+/// within `dir`.
+fn relative_to(path: &Path, dir: &Path) -> PathBuf {
+    if let Ok(rel) = path.strip_prefix(dir) {
+        return rel.to_path_buf();
+    }
+    canonical(path)
+        .strip_prefix(canonical(dir))
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Resolve symbolic links in `path`, returning it unchanged if it cannot be
+/// resolved
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Lexically remove `.` components from `path` and resolve `..` components
+/// against the preceding components, as Git does for the paths it reports
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normed = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => (),
+            Component::ParentDir if normed.file_name().is_some() => {
+                normed.pop();
+            }
+            c => normed.push(c),
+        }
+    }
+    normed
+}
+
+/// Make `path` absolute by joining it to the current directory if necessary
+fn absolute(path: &Path) -> Result<PathBuf, LocalRepoError> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(env::current_dir()
+            .map_err(LocalRepoError::CurdirError)?
+            .join(path))
+    }
+}
+
+/// Return the directories listed in `GIT_CEILING_DIRECTORIES`, resolving
+/// symbolic links.  As with Git, relative paths are ignored.
+pub(crate) fn ceiling_dirs() -> Vec<PathBuf> {
+    env::var_os("GIT_CEILING_DIRECTORIES")
+        .map(|dirs| {
+            env::split_paths(&dirs)
+                .filter(|p| p.is_absolute())
+                .map(|p| canonical(&p))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Test whether `path` looks like a Git directory
+pub(crate) fn is_git_dir(path: &Path) -> bool {
+    path.join("HEAD").is_file()
+        && (path.join("commondir").is_file()
+            || (path.join("objects").is_dir() && path.join("refs").is_dir()))
+}
+
+/// Read a `.git` file and return the path to the Git directory that it points
+/// to
+fn read_gitfile(path: &Path) -> Result<PathBuf, LocalRepoError> {
+    let contents = read_file(path)?.unwrap_or_default();
+    match contents
+        .trim_end_matches(['\n', '\r'])
+        .strip_prefix("gitdir: ")
+    {
+        Some(target) => Ok(match path.parent() {
+            Some(dir) => normalize(&dir.join(target)),
+            None => PathBuf::from(target),
+        }),
+        None => Err(LocalRepoError::NotARepository(path.to_path_buf())),
+    }
+}
+
+/// Read a `HEAD` or loose ref file.  Returns `None` if the file does not
+/// exist.
+fn read_ref_file(path: &Path) -> Result<Option<Head>, LocalRepoError> {
+    let Some(contents) = read_file(path)? else {
+        return Ok(None);
+    };
+    let contents = contents.trim_end();
+    match contents.strip_prefix("ref:") {
+        Some(target) => Ok(Some(Head::Symbolic(target.trim_start().to_string()))),
+        None => Ok(Some(Head::Detached(contents.to_string()))),
+    }
+}
+
+/// Read a file as a string, returning `None` if it does not exist
+fn read_file(path: &Path) -> Result<Option<String>, LocalRepoError> {
+    match fs::read(path) {
+        Ok(bs) => Ok(Some(String::from_utf8(bs).map_err(|e| e.utf8_error())?)),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(LocalRepoError::ReadError(path.to_path_buf(), e)),
+    }
+}
+
+/// Read the configuration file at `path` into `config`
+fn read_config(
+    config: &mut Config,
+    path: &Path,
+    ctx: &IncludeContext,
+) -> Result<(), LocalRepoError> {
+    config.read_file(path, ctx).map_err(|e| match e {
+        LoadError::Read(p, e) => LocalRepoError::ReadError(p, e),
+        LoadError::Parse(e) => LocalRepoError::InvalidConfig(e),
+    })
+}
+
+/// Test whether a Git config value is a true boolean
+pub(crate) fn is_true(value: &str) -> bool {
+    value.is_empty()
+        || ["true", "yes", "on", "1"]
+            .iter()
+            .any(|t| value.eq_ignore_ascii_case(t))
+}
+
+/// Test whether an environment variable is set to a true boolean
+fn env_flag(name: &str) -> bool {
+    env::var_os(name)
+        .as_deref()
+        .and_then(OsStr::to_str)
+        .is_some_and(|s| !s.is_empty() && is_true(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gitconfig::parse_config;
+
+    fn config(src: &str) -> Config {
+        let mut config = Config::default();
+        for (k, v) in parse_config(src).unwrap() {
+            config.push(&k, &v);
+        }
+        config
+    }
+
+    #[test]
+    fn test_remote_names() {
+        let cfg = config(concat!(
+            "[remote \"origin\"]\n",
+            "\turl = https://github.com/octocat/repository.git\n",
+            "[remote]\n",
+            "\tpushDefault = fork\n",
+            "[remote \"a.b\"]\n",
+            "\tgh-resolved = base\n",
+            "[branch \"main\"]\n",
+            "\tremote = origin\n",
+        ));
+        assert_eq!(remote_names(&cfg), ["a.b", "origin"]);
+    }
+
+    #[test]
+    fn test_remote_urls() {
+        let cfg = config(concat!(
+            "[url \"git@github.com:\"]\n",
+            "\tinsteadOf = gh:\n",
+            "\tpushInsteadOf = https://github.com/\n",
+            "[url \"https://github.com/octocat/\"]\n",
+            "\tinsteadOf = gh:octocat/\n",
+            "[remote \"origin\"]\n",
+            "\turl = gh:sourcedog/repository.git\n",
+            "[remote \"mine\"]\n",
+            "\turl = gh:octocat/repository.git\n",
+            "\tpushurl = gh:octocat/fork.git\n",
+            "[remote \"web\"]\n",
+            "\turl = https://github.com/octocat/repository.git\n",
+        ));
+        assert_eq!(
+            remote_urls(&cfg, "origin", false).unwrap(),
+            ["git@github.com:sourcedog/repository.git"]
+        );
+        assert_eq!(
+            remote_urls(&cfg, "origin", true).unwrap(),
+            ["git@github.com:sourcedog/repository.git"]
+        );
+        assert_eq!(
+            remote_urls(&cfg, "mine", false).unwrap(),
+            ["https://github.com/octocat/repository.git"]
+        );
+        assert_eq!(
+            remote_urls(&cfg, "mine", true).unwrap(),
+            ["https://github.com/octocat/fork.git"]
+        );
+        assert_eq!(
+            remote_urls(&cfg, "web", false).unwrap(),
+            ["https://github.com/octocat/repository.git"]
+        );
+        assert_eq!(
+            remote_urls(&cfg, "web", true).unwrap(),
+            ["git@github.com:octocat/repository.git"]
+        );
+        assert_eq!(remote_urls(&cfg, "upstream", false), None);
+    }
+
+    #[test]
+    fn test_parse_describe() {
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            parse_describe(&format!("v1.0.0-3-g{commit}")),
+            Some(Description {
+                tag: String::from("v1.0.0"),
+                distance: 3,
+                commit: commit.to_string(),
+            })
+        );
+        assert_eq!(
+            parse_describe(&format!("release-2024-01-0-g{commit}")),
+            Some(Description {
+                tag: String::from("release-2024-01"),
+                distance: 0,
+                commit: commit.to_string(),
+            })
+        );
+        assert_eq!(parse_describe(commit), None);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let output = [
+            "# branch.oid 0123456789abcdef0123456789abcdef01234567",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 M. N... 100644 100644 100644 aaaa bbbb staged.txt",
+            "1 .M N... 100644 100644 100644 aaaa aaaa unstaged.txt",
+            "1 MM N... 100644 100644 100644 aaaa bbbb both.txt",
+            "2 R. N... 100644 100644 100644 aaaa aaaa R100 new.txt",
+            "? old.txt",
+            "u UU N... 100644 100644 100644 100644 aaaa bbbb cccc conflict.txt",
+            "? untracked/",
+            "? other.txt",
+        ]
+        .join("\0");
+        assert_eq!(
+            parse_status(&output),
+            WorkTreeStatus {
+                branch: Some(String::from("main")),
+                commit: Some(String::from("0123456789abcdef0123456789abcdef01234567")),
+                ahead: 2,
+                behind: 1,
+                staged: 3,
+                unstaged: 2,
+                untracked: 2,
+                conflicted: 1,
+            }
+        );
+        assert_eq!(
+            parse_status("# branch.oid (initial)\0# branch.head main"),
+            WorkTreeStatus {
+                branch: Some(String::from("main")),
+                ..WorkTreeStatus::default()
+            }
+        );
+        assert_eq!(
+            parse_status(
+                "# branch.oid 0123456789abcdef0123456789abcdef01234567\0# branch.head (detached)"
+            ),
+            WorkTreeStatus {
+                commit: Some(String::from("0123456789abcdef0123456789abcdef01234567")),
+                ..WorkTreeStatus::default()
+            }
+        );
+    }
+}
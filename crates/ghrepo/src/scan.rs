@@ -0,0 +1,256 @@
+//! Finding & inspecting the Git repositories in a directory tree
+use crate::backend::{GitBackend, GitCommand};
+use crate::native;
+use crate::{LocalRepo, LocalRepoError, Remote};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+
+/// The names of directories that are skipped by default when scanning
+pub const DEFAULT_SKIP_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// A scanner for finding the Git repositories in a directory tree and
+/// inspecting each one's current branch & GitHub remotes in parallel
+///
+/// A directory is considered to be a Git repository if it contains a `.git`
+/// entry (either a directory or a gitfile, as used by linked worktrees &
+/// submodules) or if it is itself a Git directory, as with bare repositories
+/// and mirrors.  The scanner does not descend into repositories it finds, nor
+/// into symbolic links to directories or directories whose names are in the
+/// skip list (by default, [`DEFAULT_SKIP_DIRS`]).  Directories that cannot be
+/// read are silently skipped.
+///
+/// By default, repositories are inspected with a [`GitCommand`] backend that
+/// captures Git's stderr, so that errors are reported in the results rather
+/// than printed.  A different backend can be selected with
+/// [`Scanner::with_backend()`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use ghrepo::Scanner;
+/// let scanner = Scanner::new("/home/me/src").max_depth(3).skip_dir("vendor");
+/// for repo in scanner.scan().unwrap() {
+///     println!("{}: {:?}", repo.path.display(), repo.branch);
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scanner<B = GitCommand> {
+    root: PathBuf,
+    backend: B,
+    max_depth: Option<usize>,
+    skip_dirs: Vec<OsString>,
+    jobs: Option<NonZeroUsize>,
+}
+
+impl Scanner {
+    /// Create a `Scanner` for the directory tree rooted at `root`
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        let backend = GitCommand {
+            capture_stderr: true,
+            ..GitCommand::default()
+        };
+        Scanner::with_backend(root, backend)
+    }
+}
+
+impl<B> Scanner<B> {
+    /// Create a `Scanner` for the directory tree rooted at `root` that
+    /// inspects repositories using the given backend
+    pub fn with_backend<P: AsRef<Path>>(root: P, backend: B) -> Self {
+        Scanner {
+            root: root.as_ref().to_path_buf(),
+            backend,
+            max_depth: None,
+            skip_dirs: DEFAULT_SKIP_DIRS.iter().map(OsString::from).collect(),
+            jobs: None,
+        }
+    }
+
+    /// Only look for repositories at most `depth` levels below the root
+    /// directory.  A depth of 0 means that only the root itself is checked.
+    /// By default, there is no limit.
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Add a directory name to the list of names of directories to skip
+    #[must_use]
+    pub fn skip_dir<S: Into<OsString>>(mut self, name: S) -> Self {
+        self.skip_dirs.push(name.into());
+        self
+    }
+
+    /// Clear the list of names of directories to skip, including the
+    /// defaults
+    #[must_use]
+    pub fn no_skip_dirs(mut self) -> Self {
+        self.skip_dirs.clear();
+        self
+    }
+
+    /// Set the number of repositories to inspect at once.  The default is the
+    /// amount of available parallelism reported by
+    /// [`std::thread::available_parallelism()`].
+    #[must_use]
+    pub fn jobs(mut self, jobs: NonZeroUsize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Returns the paths of all Git repositories in the directory tree, sorted
+    /// by path
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::ReadError`] if the root directory could not
+    /// be read
+    pub fn find_repos(&self) -> Result<Vec<PathBuf>, LocalRepoError> {
+        let mut repos = Vec::new();
+        let mut stack = vec![(self.root.clone(), 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            if is_repo(&dir) {
+                repos.push(dir);
+                continue;
+            }
+            if self.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if depth == 0 => return Err(LocalRepoError::ReadError(dir, e)),
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|ft| ft.is_dir())
+                    && !self.skipped(&entry.file_name())
+                {
+                    stack.push((entry.path(), depth + 1));
+                }
+            }
+        }
+        repos.sort();
+        Ok(repos)
+    }
+
+    /// (Private) Test whether a directory with the given name should be
+    /// skipped
+    fn skipped(&self, name: &OsStr) -> bool {
+        name == ".git" || self.skip_dirs.iter().any(|s| s == name)
+    }
+}
+
+impl<B: GitBackend + Clone + Send + Sync> Scanner<B> {
+    /// Find all Git repositories in the directory tree and inspect them in
+    /// parallel, calling `f` on the result for each repository as soon as it
+    /// is available.  Results are thus passed to `f` in no particular order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::ReadError`] if the root directory could not
+    /// be read.  Errors encountered while inspecting individual repositories
+    /// are reported in [`ScannedRepo::errors`].
+    pub fn scan_each<F: FnMut(ScannedRepo)>(&self, mut f: F) -> Result<(), LocalRepoError> {
+        let repos = self.find_repos()?;
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+            .min(repos.len());
+        let queue = Mutex::new(repos.into_iter());
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|s| {
+            for _ in 0..jobs {
+                let sender = sender.clone();
+                let queue = &queue;
+                s.spawn(move || {
+                    loop {
+                        // Bind the next path in its own statement so that the
+                        // lock is released before inspecting the repository
+                        let next = queue.lock().ok().and_then(|mut q| q.next());
+                        let Some(path) = next else {
+                            break;
+                        };
+                        if sender.send(self.inspect(path)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+            for scanned in receiver {
+                f(scanned);
+            }
+        });
+        Ok(())
+    }
+
+    /// Find all Git repositories in the directory tree, inspect them in
+    /// parallel, and return the results sorted by path
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LocalRepoError::ReadError`] if the root directory could not
+    /// be read.  Errors encountered while inspecting individual repositories
+    /// are reported in [`ScannedRepo::errors`].
+    pub fn scan(&self) -> Result<Vec<ScannedRepo>, LocalRepoError> {
+        let mut results = Vec::new();
+        self.scan_each(|scanned| results.push(scanned))?;
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(results)
+    }
+
+    /// (Private) Inspect a single repository
+    fn inspect(&self, path: PathBuf) -> ScannedRepo {
+        let lr = LocalRepo::with_backend(&path, self.backend.clone());
+        let mut errors = Vec::new();
+        let branch = match lr.current_branch() {
+            Ok(branch) => Some(branch),
+            Err(LocalRepoError::DetachedHead) => None,
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        };
+        let remotes = lr.remotes().unwrap_or_else(|e| {
+            errors.push(e);
+            Vec::new()
+        });
+        ScannedRepo {
+            path,
+            branch,
+            remotes,
+            errors,
+        }
+    }
+}
+
+/// A Git repository found by a [`Scanner`], along with the results of
+/// inspecting it
+#[derive(Debug)]
+pub struct ScannedRepo {
+    /// The path to the repository's top-level directory
+    pub path: PathBuf,
+
+    /// The repository's current branch, or `None` if `HEAD` is detached or
+    /// the branch could not be determined
+    pub branch: Option<String>,
+
+    /// The repository's remotes.  Remotes whose URLs are not GitHub URLs are
+    /// included with an `Err` in their `repo` field.
+    pub remotes: Vec<Remote>,
+
+    /// Any errors that occurred while inspecting the repository
+    pub errors: Vec<LocalRepoError>,
+}
+
+/// (Private) Test whether a directory is the top level of a Git repository or
+/// a bare repository
+fn is_repo(path: &Path) -> bool {
+    path.join(".git").exists() || native::is_git_dir(path)
+}
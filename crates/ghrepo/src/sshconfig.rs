@@ -0,0 +1,441 @@
+//! A reader for OpenSSH client configuration files, used for resolving SSH
+//! host aliases in remote URLs
+use crate::gitconfig::{home_dir, wildmatch};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Maximum depth of nested `Include` directives, matching the limit used by
+/// OpenSSH
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A parsed OpenSSH client configuration (e.g., `~/.ssh/config`), used to
+/// resolve SSH host aliases like `github-work` in remote URLs like
+/// `git@github-work:octocat/repository.git` to their real hostnames.
+///
+/// The following configuration features are supported:
+///
+/// - `Host` blocks, including patterns with `*` & `?` wildcards and negated
+///   patterns
+/// - The `HostName` & `User` keywords; the `%h` & `%%` tokens are expanded in
+///   `HostName` values
+/// - `Include` directives, including relative paths (which are resolved
+///   relative to `~/.ssh`) and wildcards in file names
+///
+/// `Match` blocks are supported only in the form `Match all`; any other
+/// `Match` block is treated as never matching.  All other keywords are
+/// ignored.
+///
+/// As with OpenSSH, the first value obtained for each keyword is the one used.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// # use ghrepo::SshConfig;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let config = "Host github-work\n    HostName github.com\n".parse::<SshConfig>()?;
+/// assert_eq!(config.hostname("github-work"), "github.com");
+/// assert_eq!(config.hostname("example.com"), "example.com");
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SshConfig {
+    items: Vec<Item>,
+}
+
+/// A directive in an SSH config file
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Item {
+    /// A `Host` line, listing the host patterns that subsequent options apply
+    /// to
+    Host(Vec<String>),
+
+    /// A `Match` line; the field is true iff the criteria are just `all`
+    Match(bool),
+
+    /// A keyword (lowercased) and its arguments
+    Option(String, Vec<String>),
+
+    /// The contents of the files named by an `Include` line
+    Include(Vec<Item>),
+}
+
+impl SshConfig {
+    /// Load the current user's SSH configuration from `~/.ssh/config`.  If the
+    /// file does not exist or the home directory cannot be determined, an
+    /// empty configuration is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`SshConfigError`] if the file or a file that it includes
+    /// could not be read
+    pub fn load() -> Result<SshConfig, SshConfigError> {
+        let Some(home) = home_dir() else {
+            return Ok(SshConfig::default());
+        };
+        let path = home.join(".ssh").join("config");
+        match fs::read(&path) {
+            Ok(src) => Ok(SshConfig {
+                items: parse_items(&String::from_utf8_lossy(&src), 0)?,
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SshConfig::default()),
+            Err(source) => Err(SshConfigError { path, source }),
+        }
+    }
+
+    /// Load an SSH configuration from the given file.  Relative paths in
+    /// `Include` directives are resolved relative to `~/.ssh`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`SshConfigError`] if the file or a file that it includes
+    /// could not be read
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<SshConfig, SshConfigError> {
+        let path = path.as_ref();
+        let src = fs::read(path).map_err(|source| SshConfigError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(SshConfig {
+            items: parse_items(&String::from_utf8_lossy(&src), 0)?,
+        })
+    }
+
+    /// Returns the hostname that SSH connects to for the given host (as it
+    /// appears in a URL), i.e., the value of the first `HostName` option that
+    /// applies to the host, or the host itself if there is none
+    pub fn hostname(&self, host: &str) -> String {
+        match self.get(host, "hostname") {
+            Some(hostname) => expand_hostname(&hostname, host),
+            None => host.to_string(),
+        }
+    }
+
+    /// Returns the value of the first `User` option that applies to the given
+    /// host, if any
+    pub fn user(&self, host: &str) -> Option<String> {
+        self.get(host, "user")
+    }
+
+    /// (Private) Return the first argument of the first occurrence of
+    /// `keyword` that applies to `host`
+    fn get(&self, host: &str, keyword: &str) -> Option<String> {
+        lookup(&self.items, host, keyword, &mut true)
+    }
+}
+
+impl FromStr for SshConfig {
+    type Err = SshConfigError;
+
+    /// Parse an SSH configuration from a string.  Relative paths in `Include`
+    /// directives are resolved relative to `~/.ssh`.
+    fn from_str(s: &str) -> Result<SshConfig, SshConfigError> {
+        Ok(SshConfig {
+            items: parse_items(s, 0)?,
+        })
+    }
+}
+
+/// Error returned when an SSH configuration file cannot be read
+#[derive(Debug)]
+pub struct SshConfigError {
+    path: PathBuf,
+    source: io::Error,
+}
+
+impl SshConfigError {
+    /// The path to the file that could not be read
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Display for SshConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to read SSH config file {}: {}",
+            self.path.display(),
+            self.source
+        )
+    }
+}
+
+impl error::Error for SshConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Search `items` for the first occurrence of `keyword` that applies to
+/// `host`.  `active` tracks whether the current `Host` or `Match` block
+/// applies to `host`.
+fn lookup(items: &[Item], host: &str, keyword: &str, active: &mut bool) -> Option<String> {
+    for item in items {
+        match item {
+            Item::Host(patterns) => *active = host_matches(patterns, host),
+            Item::Match(all) => *active = *all,
+            Item::Option(kw, args) => {
+                if *active && kw == keyword {
+                    if let Some(value) = args.first() {
+                        return Some(value.clone());
+                    }
+                }
+            }
+            Item::Include(included) => {
+                if *active {
+                    let mut inner_active = true;
+                    if let Some(value) = lookup(included, host, keyword, &mut inner_active) {
+                        return Some(value);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Test whether a `Host` line with the given patterns applies to `host`: at
+/// least one non-negated pattern must match, and no negated pattern may match
+fn host_matches(patterns: &[String], host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(pattern) = pattern.strip_prefix('!') {
+            if wildmatch(pattern, host, true) {
+                return false;
+            }
+        } else if wildmatch(pattern, host, true) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// Expand the `%h` & `%%` tokens in a `HostName` value
+fn expand_hostname(hostname: &str, host: &str) -> String {
+    let mut s = String::with_capacity(hostname.len());
+    let mut chars = hostname.chars();
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            match chars.next() {
+                Some('h') => s.push_str(host),
+                Some('%') => s.push('%'),
+                Some(c2) => {
+                    s.push('%');
+                    s.push(c2);
+                }
+                None => s.push('%'),
+            }
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+/// Parse the contents of an SSH config file, reading any included files
+fn parse_items(src: &str, depth: usize) -> Result<Vec<Item>, SshConfigError> {
+    let mut items = Vec::new();
+    for line in src.lines() {
+        let Some((keyword, args)) = split_line(line) else {
+            continue;
+        };
+        let item = match keyword.as_str() {
+            "host" => Item::Host(args),
+            "match" => Item::Match(matches!(args.as_slice(), [a] if a.eq_ignore_ascii_case("all"))),
+            "include" => {
+                let mut included = Vec::new();
+                if depth < MAX_INCLUDE_DEPTH {
+                    for arg in &args {
+                        for path in expand_include(arg) {
+                            match fs::read(&path) {
+                                Ok(src) => included.extend(parse_items(
+                                    &String::from_utf8_lossy(&src),
+                                    depth + 1,
+                                )?),
+                                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                                Err(source) => return Err(SshConfigError { path, source }),
+                            }
+                        }
+                    }
+                }
+                Item::Include(included)
+            }
+            _ => Item::Option(keyword, args),
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Split a line of an SSH config file into a lowercased keyword and a list of
+/// arguments.  Returns `None` for blank lines & comments.
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+    Some((keyword.to_ascii_lowercase(), split_args(rest)))
+}
+
+/// Split the arguments of an SSH config line on whitespace, treating
+/// double-quoted strings as single arguments
+fn split_args(s: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    for c in s.chars() {
+        if c == '"' {
+            quoted = !quoted;
+            current.get_or_insert_with(String::new);
+        } else if c.is_whitespace() && !quoted {
+            if let Some(arg) = current.take() {
+                args.push(arg);
+            }
+        } else if c == '#' && !quoted && current.is_none() {
+            break;
+        } else {
+            current.get_or_insert_with(String::new).push(c);
+        }
+    }
+    args.extend(current);
+    args
+}
+
+/// Resolve an `Include` argument to a list of file paths, expanding a leading
+/// `~/`, resolving relative paths relative to `~/.ssh`, and expanding
+/// wildcards in the final path component
+fn expand_include(arg: &str) -> Vec<PathBuf> {
+    let path = if let Some(rest) = arg.strip_prefix("~/") {
+        match home_dir() {
+            Some(home) => home.join(rest),
+            None => return Vec::new(),
+        }
+    } else if Path::new(arg).is_absolute() {
+        PathBuf::from(arg)
+    } else {
+        match home_dir() {
+            Some(home) => home.join(".ssh").join(arg),
+            None => return Vec::new(),
+        }
+    };
+    let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+        return vec![path];
+    };
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|s| wildmatch(name, s, false))
+        })
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("Host foo", Some(("host", vec!["foo"])))]
+    #[case("  HostName=github.com", Some(("hostname", vec!["github.com"])))]
+    #[case("HostName = github.com", Some(("hostname", vec!["github.com"])))]
+    #[case("Host foo bar\t!baz", Some(("host", vec!["foo", "bar", "!baz"])))]
+    #[case("Include \"my file\" other", Some(("include", vec!["my file", "other"])))]
+    #[case("User git # comment", Some(("user", vec!["git"])))]
+    #[case("# comment", None)]
+    #[case("   ", None)]
+    fn test_split_line(#[case] line: &str, #[case] expected: Option<(&str, Vec<&str>)>) {
+        let expected = expected.map(|(kw, args)| {
+            (
+                kw.to_string(),
+                args.into_iter().map(String::from).collect::<Vec<_>>(),
+            )
+        });
+        assert_eq!(split_line(line), expected);
+    }
+
+    #[rstest]
+    #[case(&["github-work"], "github-work", true)]
+    #[case(&["GitHub-Work"], "github-work", true)]
+    #[case(&["github-*"], "github-work", true)]
+    #[case(&["github-?"], "github-work", false)]
+    #[case(&["*", "!github-work"], "github-work", false)]
+    #[case(&["*", "!github-work"], "github-home", true)]
+    #[case(&["!github-work"], "github-home", false)]
+    #[case(&["foo", "bar"], "bar", true)]
+    fn test_host_matches(#[case] patterns: &[&str], #[case] host: &str, #[case] expected: bool) {
+        let patterns = patterns.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(host_matches(&patterns, host), expected);
+    }
+
+    #[rstest]
+    #[case("github.com", "github.com")]
+    #[case("%h.example.com", "alias.example.com")]
+    #[case("100%%", "100%")]
+    #[case("%x%", "%x%")]
+    fn test_expand_hostname(#[case] hostname: &str, #[case] expected: &str) {
+        assert_eq!(expand_hostname(hostname, "alias"), expected);
+    }
+
+    #[test]
+    fn test_first_value_wins() {
+        let config = concat!(
+            "Host github-work\n",
+            "    HostName github.com\n",
+            "    User git\n",
+            "\n",
+            "Host github-*\n",
+            "    HostName ghe.example.com\n",
+            "    User someone\n",
+            "\n",
+            "Match exec \"true\"\n",
+            "    User nobody\n",
+            "\n",
+            "Match all\n",
+            "    User everyone\n",
+        )
+        .parse::<SshConfig>()
+        .unwrap();
+        assert_eq!(config.hostname("github-work"), "github.com");
+        assert_eq!(config.user("github-work").as_deref(), Some("git"));
+        assert_eq!(config.hostname("github-home"), "ghe.example.com");
+        assert_eq!(config.user("github-home").as_deref(), Some("someone"));
+        assert_eq!(config.hostname("example.com"), "example.com");
+        assert_eq!(config.user("example.com").as_deref(), Some("everyone"));
+    }
+
+    #[test]
+    fn test_options_before_host() {
+        let config = "User git\nHost foo\nUser bar\n"
+            .parse::<SshConfig>()
+            .unwrap();
+        assert_eq!(config.user("foo").as_deref(), Some("git"));
+        assert_eq!(config.user("baz").as_deref(), Some("git"));
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "tokio")]
+use ghrepo::{AsyncLocalRepo, GHRepo, LocalRepo, LocalRepoError};
+use repomaker::RepoMaker;
+use tempfile::tempdir;
+use which::which;
+
+#[tokio::test]
+async fn test_async_is_git_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    assert!(
+        !AsyncLocalRepo::new(tmp_path.path())
+            .is_git_repo()
+            .await
+            .unwrap()
+    );
+    let maker = RepoMaker::new().unwrap();
+    maker.init("main").unwrap();
+    assert!(
+        AsyncLocalRepo::new(maker.path())
+            .is_git_repo()
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_async_current_branch() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = AsyncLocalRepo::new(maker.path());
+    assert_eq!(lr.current_branch().await.unwrap(), "trunk");
+    maker.detach().unwrap();
+    match lr.current_branch().await {
+        Err(LocalRepoError::DetachedHead) => (),
+        e => panic!("current_branch() did not fail with DetachedHead; got: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_async_current_branch_not_a_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    match AsyncLocalRepo::new(tmp_path.path()).current_branch().await {
+        Err(LocalRepoError::CommandFailed(_)) => (),
+        e => panic!("Git command did not fail; got: {e:?}"),
+    }
+    let lr = LocalRepo::builder(tmp_path.path())
+        .capture_stderr(true)
+        .build_async();
+    match lr.current_branch().await {
+        Err(LocalRepoError::NotARepository(_)) => (),
+        e => panic!("current_branch() did not fail with NotARepository; got: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_async_github_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", "gh:octocat/repository").unwrap();
+    maker
+        .set_config("url.https://github.com/.insteadOf", "gh:")
+        .unwrap();
+    maker
+        .add_remote("other", "https://git.example.com/octocat/repository.git")
+        .unwrap();
+    let lr = AsyncLocalRepo::new(maker.path());
+    assert_eq!(
+        lr.github_remote("origin").await.unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    match lr.github_remote("upstream").await {
+        Err(LocalRepoError::NoSuchRemote(remote)) => assert_eq!(remote, "upstream"),
+        e => panic!("github_remote() did not fail with NoSuchRemote; got: {e:?}"),
+    }
+    match lr.github_remote("other").await {
+        Err(LocalRepoError::InvalidRemoteURL(_)) => (),
+        e => panic!("github_remote() did not fail with InvalidRemoteURL; got: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_async_branch_upstream() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("upstream", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.set_upstream("trunk", "upstream").unwrap();
+    maker
+        .set_config("branch.feature.merge", "refs/heads/feature")
+        .unwrap();
+    let lr = AsyncLocalRepo::new(maker.path());
+    assert_eq!(
+        lr.branch_upstream("trunk").await.unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    match lr.branch_upstream("feature").await {
+        Err(LocalRepoError::NoUpstream(branch)) => assert_eq!(branch, "feature"),
+        e => panic!("branch_upstream() did not fail with NoUpstream; got: {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_async_concurrent() {
+    if which("git").is_err() {
+        return;
+    }
+    let makers = (0..4)
+        .map(|i| {
+            let maker = RepoMaker::new().unwrap();
+            maker.init("main").unwrap();
+            maker
+                .add_remote("origin", format!("https://github.com/octocat/repo{i}.git"))
+                .unwrap();
+            maker
+        })
+        .collect::<Vec<_>>();
+    let repos = makers
+        .iter()
+        .map(|maker| AsyncLocalRepo::new(maker.path()))
+        .collect::<Vec<_>>();
+    let (r0, r1, r2, r3) = tokio::join!(
+        repos[0].github_remote("origin"),
+        repos[1].github_remote("origin"),
+        repos[2].github_remote("origin"),
+        repos[3].github_remote("origin"),
+    );
+    assert_eq!(r0.unwrap(), "octocat/repo0");
+    assert_eq!(r1.unwrap(), "octocat/repo1");
+    assert_eq!(r2.unwrap(), "octocat/repo2");
+    assert_eq!(r3.unwrap(), "octocat/repo3");
+}
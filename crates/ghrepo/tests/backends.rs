@@ -0,0 +1,1366 @@
+//! Tests that the non-default backends of `LocalRepo` agree with the
+//! `GitCommand` backend
+#[cfg(feature = "gix")]
+use ghrepo::Gitoxide;
+#[cfg(feature = "git2")]
+use ghrepo::LibGit2;
+use ghrepo::{
+    Description, FileStatus, GHRepo, GitBackend, GitCommand, LocalRepo, LocalRepoError, NativeGit,
+    RepoLayout, RepoState, Submodule, WorkTreeStatus, Worktree,
+};
+use repomaker::RepoMaker;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+use which::which;
+
+/// Runs `$body` with `$lr` bound to a `LocalRepo` for `$path` using each
+/// backend other than `GitCommand` in turn
+macro_rules! each_alt_backend {
+    ($path:expr, |$lr:ident| $body:expr) => {{
+        let path: &Path = $path.as_ref();
+        {
+            let $lr = LocalRepo::with_backend(path, NativeGit);
+            $body;
+        }
+        #[cfg(feature = "git2")]
+        {
+            let $lr = LocalRepo::with_backend(path, LibGit2);
+            $body;
+        }
+        #[cfg(feature = "gix")]
+        {
+            let $lr = LocalRepo::with_backend(path, Gitoxide);
+            $body;
+        }
+    }};
+}
+
+/// Runs `$body` with `$lr` bound to a `LocalRepo` for `$path` using each
+/// backend in turn
+macro_rules! each_backend {
+    ($path:expr, |$lr:ident| $body:expr) => {{
+        {
+            let $lr = LocalRepo::new($path);
+            $body;
+        }
+        each_alt_backend!($path, |$lr| $body);
+    }};
+}
+
+/// Asserts that every backend returns `Ok` with the same value for the given
+/// method call, and returns that value
+macro_rules! assert_agree {
+    ($path:expr, |$lr:ident| $call:expr) => {{
+        let expected = {
+            let $lr = LocalRepo::new($path);
+            $call.unwrap()
+        };
+        each_alt_backend!($path, |$lr| assert_eq!(
+            $call.unwrap(),
+            expected,
+            "{:?}",
+            $lr.backend()
+        ));
+        expected
+    }};
+}
+
+fn origin_contains<B: GitBackend>(lr: &LocalRepo<B>, commit: &str) -> bool {
+    lr.backend()
+        .remote_contains(lr.path(), "origin", commit)
+        .expect("remote_contains() should succeed")
+}
+
+fn append_config(maker: &RepoMaker, text: &str) {
+    let path = maker.path().join(".git").join("config");
+    let mut config = fs::read_to_string(&path).expect("config should be readable");
+    config.push_str(text);
+    fs::write(path, config).expect("config should be writable");
+}
+
+#[test]
+fn test_backend_getter() {
+    assert_eq!(
+        LocalRepo::new("/path/to/repo").backend(),
+        &GitCommand::default()
+    );
+    assert_eq!(
+        LocalRepo::with_backend("/path/to/repo", NativeGit).backend(),
+        &NativeGit
+    );
+}
+
+#[test]
+fn test_backends_not_a_repo() {
+    let tmp_path = tempdir().unwrap();
+    each_alt_backend!(tmp_path.path(), |lr| {
+        assert!(!lr.is_git_repo().unwrap());
+        match lr.current_branch() {
+            Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+            r => panic!("{:?} did not fail; got: {r:?}", lr.backend()),
+        }
+    });
+}
+
+#[test]
+fn test_backends_basic() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker
+        .add_remote("upstream", "git@github.com:sourcedog/repository.git")
+        .unwrap();
+    maker
+        .add_remote("other", "https://example.com/repo.git")
+        .unwrap();
+    maker
+        .add_push_url("origin", "git@github.com:octocat/fork.git")
+        .unwrap();
+    maker.set_upstream("trunk", "upstream").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/main")
+        .unwrap();
+    maker.set_remote_head("upstream", "main").unwrap();
+    assert!(assert_agree!(maker.path(), |lr| lr.is_git_repo()));
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.current_branch()),
+        "trunk"
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.github_push_remote("origin")),
+        GHRepo::new("octocat", "fork").unwrap()
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.branch_upstream("trunk")),
+        GHRepo::new("sourcedog", "repository").unwrap()
+    );
+    assert_agree!(maker.path(), |lr| lr.branch_upstream_details("trunk"));
+    assert_agree!(maker.path(), |lr| lr.branch_push_repo("trunk"));
+    assert_agree!(maker.path(), |lr| lr.remotes());
+    assert_agree!(maker.path(), |lr| lr.default_github_repo());
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.default_branch("upstream")),
+        "main"
+    );
+    each_backend!(maker.path(), |lr| {
+        match lr.default_branch("origin") {
+            Err(LocalRepoError::NoRemoteHead(r)) => assert_eq!(r, "origin"),
+            r => panic!("default_branch() did not fail; got: {r:?}"),
+        }
+        match lr.github_remote("nowhere") {
+            Err(LocalRepoError::NoSuchRemote(r)) => assert_eq!(r, "nowhere"),
+            r => panic!("github_remote() did not fail; got: {r:?}"),
+        }
+        match lr.branch_upstream("main") {
+            Err(LocalRepoError::NoUpstream(b)) => assert_eq!(b, "main"),
+            r => panic!("branch_upstream() did not fail; got: {r:?}"),
+        }
+    });
+}
+
+#[test]
+fn test_backends_subdirectory() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let subdir = maker.path().join("foo").join("bar");
+    fs::create_dir_all(&subdir).unwrap();
+    assert_eq!(assert_agree!(&subdir, |lr| lr.current_branch()), "trunk");
+    assert_agree!(&subdir, |lr| lr.github_remote("origin"));
+}
+
+#[test]
+fn test_backends_detached() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.detach().unwrap();
+    each_backend!(maker.path(), |lr| match lr.current_branch() {
+        Err(LocalRepoError::DetachedHead) => (),
+        r => panic!("current_branch() did not fail; got: {r:?}"),
+    });
+}
+
+#[test]
+fn test_backends_includes() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    fs::write(
+        maker.path().join("remotes.cfg"),
+        "[remote \"origin\"]\n\turl = https://github.com/octocat/repository.git\n",
+    )
+    .unwrap();
+    fs::write(
+        maker.path().join("gitdir.cfg"),
+        "[branch \"trunk\"]\n\tremote = origin\n",
+    )
+    .unwrap();
+    fs::write(
+        maker.path().join("onbranch.cfg"),
+        "[remote \"upstream\"]\n\turl = https://github.com/sourcedog/repository.git\n",
+    )
+    .unwrap();
+    fs::write(
+        maker.path().join("unused.cfg"),
+        "[remote \"origin\"]\n\turl = https://github.com/wrong/repository.git\n",
+    )
+    .unwrap();
+    append_config(
+        &maker,
+        concat!(
+            "[include]\n",
+            "\tpath = ../remotes.cfg\n",
+            "[includeIf \"gitdir:**/\"]\n",
+            "\tpath = ../gitdir.cfg\n",
+            "[includeIf \"onbranch:tr*\"]\n",
+            "\tpath = ../onbranch.cfg\n",
+            "[includeIf \"onbranch:main\"]\n",
+            "\tpath = ../unused.cfg\n",
+            "[includeIf \"gitdir:/nonexistent/\"]\n",
+            "\tpath = ../unused.cfg\n",
+        ),
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.branch_upstream("trunk")),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.github_remote("upstream")),
+        GHRepo::new("sourcedog", "repository").unwrap()
+    );
+    assert_agree!(maker.path(), |lr| lr.remotes());
+}
+
+#[test]
+fn test_backends_instead_of() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    append_config(
+        &maker,
+        concat!(
+            "[url \"https://github.com/\"]\n",
+            "\tinsteadOf = gh:\n",
+            "[url \"https://github.com/octocat/\"]\n",
+            "\tinsteadOf = gh:me/\n",
+            "[url \"git@github.com:\"]\n",
+            "\tpushInsteadOf = https://github.com/\n",
+            "[remote \"origin\"]\n",
+            "\turl = gh:me/repository.git\n",
+            "[remote \"upstream\"]\n",
+            "\turl = gh:sourcedog/repository.git\n",
+            "\tpushurl = gh:octocat/fork.git\n",
+            "[remote \"web\"]\n",
+            "\turl = https://github.com/octocat/repository.git\n",
+        ),
+    );
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    let remotes = assert_agree!(maker.path(), |lr| lr.remotes());
+    assert_eq!(remotes[0].raw_fetch_urls, ["gh:me/repository.git"]);
+    assert_eq!(remotes[1].raw_push_urls, ["gh:octocat/fork.git"]);
+    assert_eq!(
+        remotes[0].push_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+    assert_eq!(
+        remotes[1].push_urls,
+        ["https://github.com/octocat/fork.git"]
+    );
+    assert_eq!(
+        remotes[2].push_urls,
+        ["git@github.com:octocat/repository.git"]
+    );
+}
+
+#[test]
+fn test_backends_worktree() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.detach().unwrap();
+    let tmp_path = tempdir().unwrap();
+    let wt = tmp_path.path().join("wt");
+    maker.add_worktree(&wt, "feature").unwrap();
+    assert_eq!(assert_agree!(&wt, |lr| lr.current_branch()), "feature");
+    assert_eq!(
+        assert_agree!(&wt, |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+}
+
+#[test]
+fn test_backends_gitfile() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let tmp_path = tempdir().unwrap();
+    let gitdir = maker.path().join(".git");
+    fs::write(
+        tmp_path.path().join(".git"),
+        format!("gitdir: {}\n", gitdir.display()),
+    )
+    .unwrap();
+    assert_eq!(
+        assert_agree!(tmp_path.path(), |lr| lr.current_branch()),
+        "trunk"
+    );
+    assert_agree!(tmp_path.path(), |lr| lr.github_remote("origin"));
+}
+
+#[test]
+fn test_backends_inspect_not_a_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    assert_eq!(
+        assert_agree!(tmp_path.path(), |lr| lr.inspect()),
+        RepoState::NotARepository
+    );
+}
+
+#[test]
+fn test_backends_inspect_work_tree() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let root = fs::canonicalize(maker.path()).unwrap();
+    let expected = RepoState::WorkTree {
+        toplevel: root.clone(),
+        git_dir: root.join(".git"),
+    };
+    assert_eq!(assert_agree!(maker.path(), |lr| lr.inspect()), expected);
+    let subdir = maker.path().join("foo").join("bar");
+    fs::create_dir_all(&subdir).unwrap();
+    assert_eq!(assert_agree!(&subdir, |lr| lr.inspect()), expected);
+}
+
+#[test]
+fn test_backends_inspect_git_dir() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let git_dir = maker.path().join(".git");
+    let expected = RepoState::InsideGitDir {
+        git_dir: fs::canonicalize(&git_dir).unwrap(),
+    };
+    assert_eq!(assert_agree!(&git_dir, |lr| lr.inspect()), expected);
+    let refs_dir = git_dir.join("refs");
+    assert_eq!(assert_agree!(&refs_dir, |lr| lr.inspect()), expected);
+}
+
+#[test]
+fn test_backends_inspect_bare() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init_bare("trunk").unwrap();
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.inspect()),
+        RepoState::Bare {
+            git_dir: fs::canonicalize(maker.path()).unwrap()
+        }
+    );
+}
+
+#[test]
+fn test_backends_inspect_worktree() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.detach().unwrap();
+    let tmp_path = tempdir().unwrap();
+    let wt = tmp_path.path().join("wt");
+    maker.add_worktree(&wt, "feature").unwrap();
+    let state = assert_agree!(&wt, |lr| lr.inspect());
+    assert_eq!(
+        state.toplevel(),
+        Some(fs::canonicalize(&wt).unwrap().as_path())
+    );
+    assert_eq!(
+        state.git_dir(),
+        Some(
+            fs::canonicalize(maker.path())
+                .unwrap()
+                .join(".git")
+                .join("worktrees")
+                .join("wt")
+                .as_path()
+        )
+    );
+}
+
+#[test]
+fn test_backends_inspect_corrupt() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    fs::write(tmp_path.path().join(".git"), "garbage\n").unwrap();
+    let lr = LocalRepo::new(tmp_path.path());
+    assert!(matches!(lr.inspect().unwrap(), RepoState::Corrupt { .. }));
+    let lr = LocalRepo::with_backend(tmp_path.path(), NativeGit);
+    assert!(matches!(lr.inspect().unwrap(), RepoState::Corrupt { .. }));
+}
+
+#[test]
+fn test_backends_inspect_invalid_config() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    append_config(&maker, "[remote \"origin\"\n");
+    let lr = LocalRepo::new(maker.path());
+    match lr.inspect().unwrap() {
+        RepoState::Corrupt { message } => assert!(message.starts_with("bad config line")),
+        r => panic!("inspect() did not report corruption; got: {r:?}"),
+    }
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    match lr.inspect().unwrap() {
+        RepoState::Corrupt { message } => assert!(message.contains("bad config line")),
+        r => panic!("inspect() did not report corruption; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_backends_layout() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let root = fs::canonicalize(maker.path()).unwrap();
+    let git_dir = root.join(".git");
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: Some(root.clone()),
+            git_dir: git_dir.clone(),
+            common_dir: git_dir.clone(),
+            prefix: PathBuf::new(),
+        }
+    );
+    let subdir = maker.path().join("foo").join("bar");
+    fs::create_dir_all(&subdir).unwrap();
+    assert_eq!(
+        assert_agree!(&subdir, |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: Some(root),
+            git_dir: git_dir.clone(),
+            common_dir: git_dir.clone(),
+            prefix: PathBuf::from("foo/bar"),
+        }
+    );
+    let refs_dir = maker.path().join(".git").join("refs");
+    assert_eq!(
+        assert_agree!(&refs_dir, |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: None,
+            git_dir: git_dir.clone(),
+            common_dir: git_dir,
+            prefix: PathBuf::new(),
+        }
+    );
+}
+
+#[test]
+fn test_backends_layout_bare() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init_bare("trunk").unwrap();
+    let root = fs::canonicalize(maker.path()).unwrap();
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: None,
+            git_dir: root.clone(),
+            common_dir: root,
+            prefix: PathBuf::new(),
+        }
+    );
+}
+
+#[test]
+fn test_backends_layout_worktree() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.detach().unwrap();
+    let tmp_path = tempdir().unwrap();
+    let wt = tmp_path.path().join("wt");
+    maker.add_worktree(&wt, "feature").unwrap();
+    let subdir = wt.join("sub");
+    fs::create_dir(&subdir).unwrap();
+    let common_dir = fs::canonicalize(maker.path()).unwrap().join(".git");
+    assert_eq!(
+        assert_agree!(&subdir, |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: Some(fs::canonicalize(&wt).unwrap()),
+            git_dir: common_dir.join("worktrees").join("wt"),
+            common_dir,
+            prefix: PathBuf::from("sub"),
+        }
+    );
+}
+
+#[test]
+fn test_backends_layout_not_a_repo() {
+    let tmp_path = tempdir().unwrap();
+    each_alt_backend!(tmp_path.path(), |lr| {
+        match lr.backend().layout(lr.path()) {
+            Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+            r => panic!("{:?} did not fail; got: {r:?}", lr.backend()),
+        }
+    });
+}
+
+#[test]
+fn test_backends_worktree_list() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.commit_file("README.md", "Hello\n").unwrap();
+    let tmp_path = tempdir().unwrap();
+    let wt_a = tmp_path.path().join("a");
+    let wt_b = tmp_path.path().join("b");
+    maker.add_worktree(&wt_a, "feature").unwrap();
+    maker.add_detached_worktree(&wt_b).unwrap();
+    maker.lock_worktree(&wt_b).unwrap();
+    let expected = vec![
+        Worktree {
+            path: fs::canonicalize(maker.path()).unwrap(),
+            branch: Some(String::from("trunk")),
+            bare: false,
+            locked: false,
+        },
+        Worktree {
+            path: fs::canonicalize(&wt_a).unwrap(),
+            branch: Some(String::from("feature")),
+            bare: false,
+            locked: false,
+        },
+        Worktree {
+            path: fs::canonicalize(&wt_b).unwrap(),
+            branch: None,
+            bare: false,
+            locked: true,
+        },
+    ];
+    assert_eq!(assert_agree!(maker.path(), |lr| lr.worktrees()), expected);
+    assert_eq!(assert_agree!(&wt_b, |lr| lr.worktrees()), expected);
+    assert!(!assert_agree!(maker.path(), |lr| lr.is_linked_worktree()));
+    assert!(assert_agree!(&wt_a, |lr| lr.is_linked_worktree()));
+    assert!(!assert_agree!(&wt_a, |lr| lr.is_bare()));
+}
+
+#[test]
+fn test_backends_bare_mirror() {
+    if which("git").is_err() {
+        return;
+    }
+    let source = RepoMaker::new().unwrap();
+    source.init("trunk").unwrap();
+    source.commit_file("README.md", "Hello\n").unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    mirror.clone_mirror(source.path()).unwrap();
+    mirror
+        .set_config(
+            "remote.origin.url",
+            "https://github.com/octocat/repository.git",
+        )
+        .unwrap();
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    assert!(assert_agree!(mirror.path(), |lr| lr.is_bare()));
+    assert!(!assert_agree!(mirror.path(), |lr| lr.is_linked_worktree()));
+    assert_eq!(
+        assert_agree!(mirror.path(), |lr| lr.current_branch()),
+        "trunk"
+    );
+    assert_eq!(
+        assert_agree!(mirror.path(), |lr| lr.github_remote("origin")),
+        repo
+    );
+    let tmp_path = tempdir().unwrap();
+    let wt = tmp_path.path().join("wt");
+    mirror.add_worktree(&wt, "feature").unwrap();
+    assert!(!assert_agree!(&wt, |lr| lr.is_bare()));
+    assert!(assert_agree!(&wt, |lr| lr.is_linked_worktree()));
+    assert_eq!(assert_agree!(&wt, |lr| lr.current_branch()), "feature");
+    assert_eq!(assert_agree!(&wt, |lr| lr.github_remote("origin")), repo);
+    assert_eq!(
+        assert_agree!(&wt, |lr| lr.worktrees()),
+        [
+            Worktree {
+                path: fs::canonicalize(mirror.path()).unwrap(),
+                branch: None,
+                bare: true,
+                locked: false,
+            },
+            Worktree {
+                path: fs::canonicalize(&wt).unwrap(),
+                branch: Some(String::from("feature")),
+                bare: false,
+                locked: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_backends_submodule() {
+    if which("git").is_err() {
+        return;
+    }
+    let sub = RepoMaker::new().unwrap();
+    sub.init("trunk").unwrap();
+    sub.commit_file("README.md", "Hello\n").unwrap();
+    let superproject = RepoMaker::new().unwrap();
+    superproject.init("main").unwrap();
+    superproject
+        .add_remote("origin", "https://github.com/octocat/superproject.git")
+        .unwrap();
+    superproject.add_submodule(sub.path(), "sub").unwrap();
+    let subdir = superproject.path().join("sub");
+    let r = Command::new("git")
+        .args([
+            "remote",
+            "set-url",
+            "origin",
+            "https://github.com/octocat/submodule.git",
+        ])
+        .current_dir(&subdir)
+        .status()
+        .unwrap();
+    assert!(r.success());
+    assert_eq!(assert_agree!(&subdir, |lr| lr.current_branch()), "trunk");
+    assert_eq!(
+        assert_agree!(&subdir, |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "submodule").unwrap()
+    );
+    assert_eq!(
+        assert_agree!(superproject.path(), |lr| lr.github_remote("origin")),
+        GHRepo::new("octocat", "superproject").unwrap()
+    );
+    let root = fs::canonicalize(superproject.path()).unwrap();
+    let git_dir = root.join(".git").join("modules").join("sub");
+    assert_eq!(
+        assert_agree!(&subdir, |lr| lr.backend().layout(lr.path())),
+        RepoLayout {
+            toplevel: Some(root.join("sub")),
+            git_dir: git_dir.clone(),
+            common_dir: git_dir,
+            prefix: PathBuf::new(),
+        }
+    );
+    assert!(!assert_agree!(&subdir, |lr| lr.is_linked_worktree()));
+    assert_eq!(
+        assert_agree!(&subdir, |lr| lr.worktrees()),
+        [Worktree {
+            path: root.join("sub"),
+            branch: Some(String::from("trunk")),
+            bare: false,
+            locked: false,
+        }]
+    );
+}
+
+#[test]
+fn test_backends_submodules() {
+    if which("git").is_err() {
+        return;
+    }
+    let sub = RepoMaker::new().unwrap();
+    sub.init("trunk").unwrap();
+    sub.commit_file("README.md", "Hello\n").unwrap();
+    let superproject = RepoMaker::new().unwrap();
+    superproject.init("main").unwrap();
+    superproject
+        .add_remote("origin", "https://github.com/octocat/superproject.git")
+        .unwrap();
+    superproject.add_submodule(sub.path(), "sub").unwrap();
+    superproject
+        .add_submodule(sub.path(), "lib/sibling")
+        .unwrap();
+    let git = |args: &[&str]| {
+        let r = Command::new("git")
+            .args(args)
+            .current_dir(superproject.path())
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap().trim().to_string()
+    };
+    git(&[
+        "config",
+        "-f",
+        ".gitmodules",
+        "submodule.sub.url",
+        "git@github.com:octocat/submodule.git",
+    ]);
+    git(&[
+        "config",
+        "-f",
+        ".gitmodules",
+        "submodule.lib/sibling.url",
+        "../sibling.git",
+    ]);
+    git(&[
+        "config",
+        "-f",
+        ".gitmodules",
+        "submodule.lib/sibling.branch",
+        "trunk",
+    ]);
+    let commit = Some(git(&["-C", "sub", "rev-parse", "HEAD"]));
+    let expected = [
+        Submodule {
+            name: String::from("lib/sibling"),
+            path: String::from("lib/sibling"),
+            url: Some(String::from("../sibling.git")),
+            repo: Ok(GHRepo::new("octocat", "sibling").unwrap()),
+            branch: Some(String::from("trunk")),
+            commit: commit.clone(),
+        },
+        Submodule {
+            name: String::from("sub"),
+            path: String::from("sub"),
+            url: Some(String::from("git@github.com:octocat/submodule.git")),
+            repo: Ok(GHRepo::new("octocat", "submodule").unwrap()),
+            branch: None,
+            commit,
+        },
+    ];
+    assert_eq!(
+        assert_agree!(superproject.path(), |lr| lr.submodules()),
+        expected
+    );
+    let libdir = superproject.path().join("lib");
+    assert_eq!(assert_agree!(&libdir, |lr| lr.submodules()), expected);
+    git(&["update-index", "--index-version", "4"]);
+    assert_eq!(
+        assert_agree!(superproject.path(), |lr| lr.submodules()),
+        expected
+    );
+}
+
+#[test]
+fn test_backends_head_commit() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.backend().head_commit(lr.path())),
+        None
+    );
+    maker.commit_file("README.md", "Hello\n").unwrap();
+    let commit = assert_agree!(maker.path(), |lr| lr.backend().head_commit(lr.path()));
+    assert!(commit.as_ref().is_some_and(|c| c.len() == 40));
+    let r = Command::new("git")
+        .args(["pack-refs", "--all"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    assert!(!maker.path().join(".git/refs/heads/trunk").exists());
+    assert_eq!(
+        assert_agree!(maker.path(), |lr| lr.backend().head_commit(lr.path())),
+        commit
+    );
+    maker.detach().unwrap();
+    let detached = assert_agree!(maker.path(), |lr| lr.backend().head_commit(lr.path()));
+    assert!(detached.is_some());
+    assert_ne!(detached, commit);
+}
+
+#[test]
+fn test_backends_file_status() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.commit_file("clean.txt", "Clean\n").unwrap();
+    maker.commit_file("changed.txt", "Original\n").unwrap();
+    maker.commit_file("staged.txt", "Original\n").unwrap();
+    fs::write(maker.path().join("changed.txt"), "Changed\n").unwrap();
+    fs::write(maker.path().join("staged.txt"), "Staged\n").unwrap();
+    let r = Command::new("git")
+        .args(["add", "staged.txt"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    fs::write(maker.path().join("new.txt"), "New\n").unwrap();
+    fs::write(maker.path().join(".gitignore"), "ignored.txt\n").unwrap();
+    fs::write(maker.path().join("ignored.txt"), "Ignored\n").unwrap();
+    let subdir = maker.path().join("sub");
+    fs::create_dir(&subdir).unwrap();
+    for (file, status) in [
+        ("clean.txt", FileStatus::Unmodified),
+        ("changed.txt", FileStatus::Modified),
+        ("staged.txt", FileStatus::Modified),
+        ("new.txt", FileStatus::Untracked),
+        ("ignored.txt", FileStatus::Untracked),
+    ] {
+        let lr = LocalRepo::new(&subdir);
+        assert_eq!(
+            lr.backend().file_status(lr.path(), file).unwrap(),
+            status,
+            "{file}"
+        );
+        #[cfg(feature = "git2")]
+        {
+            let lr = LocalRepo::with_backend(&subdir, LibGit2);
+            assert_eq!(
+                lr.backend().file_status(lr.path(), file).unwrap(),
+                status,
+                "{file}"
+            );
+        }
+    }
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    assert!(matches!(
+        lr.backend().file_status(lr.path(), "clean.txt"),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_status() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.commit_file("one.txt", "One\n").unwrap();
+    maker.commit_file("two.txt", "Two\n").unwrap();
+    maker.commit_file("three.txt", "Three\n").unwrap();
+    let git = |args: &[&str]| {
+        let r = Command::new("git")
+            .args(args)
+            .current_dir(maker.path())
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap().trim().to_string()
+    };
+    let subdir = maker.path().join("sub");
+    fs::create_dir(&subdir).unwrap();
+    let base = WorkTreeStatus {
+        branch: Some(String::from("trunk")),
+        commit: Some(git(&["rev-parse", "HEAD"])),
+        ..WorkTreeStatus::default()
+    };
+    macro_rules! check {
+        ($expected:expr) => {{
+            let expected: &WorkTreeStatus = &$expected;
+            let lr = LocalRepo::new(&subdir);
+            assert_eq!(&lr.backend().status(lr.path()).unwrap(), expected);
+            #[cfg(feature = "git2")]
+            {
+                let lr = LocalRepo::with_backend(&subdir, LibGit2);
+                assert_eq!(&lr.backend().status(lr.path()).unwrap(), expected);
+            }
+        }};
+    }
+    check!(base);
+    // The upstream is configured but its remote-tracking branch is missing
+    maker.set_upstream("trunk", "origin").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/trunk")
+        .unwrap();
+    check!(base);
+    let other = git(&[
+        "commit-tree",
+        "HEAD~1^{tree}",
+        "-p",
+        "HEAD~1",
+        "-m",
+        "Other",
+    ]);
+    git(&["update-ref", "refs/remotes/origin/trunk", &other]);
+    check!(WorkTreeStatus {
+        ahead: 1,
+        behind: 1,
+        ..base.clone()
+    });
+    fs::write(maker.path().join("one.txt"), "Changed\n").unwrap();
+    fs::write(maker.path().join("two.txt"), "Staged\n").unwrap();
+    fs::write(maker.path().join("new.txt"), "New\n").unwrap();
+    git(&["add", "two.txt", "new.txt"]);
+    fs::write(maker.path().join("new.txt"), "Changed again\n").unwrap();
+    git(&["mv", "three.txt", "moved.txt"]);
+    fs::write(maker.path().join("untracked.txt"), "Untracked\n").unwrap();
+    fs::write(subdir.join("a.txt"), "A\n").unwrap();
+    fs::write(subdir.join("b.txt"), "B\n").unwrap();
+    check!(WorkTreeStatus {
+        ahead: 1,
+        behind: 1,
+        staged: 3,
+        unstaged: 2,
+        untracked: 2,
+        conflicted: 0,
+        ..base.clone()
+    });
+    git(&["checkout", "--detach"]);
+    check!(WorkTreeStatus {
+        branch: None,
+        staged: 3,
+        unstaged: 2,
+        untracked: 2,
+        ..base
+    });
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    assert!(matches!(
+        lr.backend().status(lr.path()),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_remote_contains() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.commit_file("one.txt", "One\n").unwrap();
+    maker.commit_file("two.txt", "Two\n").unwrap();
+    let git = |args: &[&str]| {
+        let r = Command::new("git")
+            .args(args)
+            .current_dir(maker.path())
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap().trim().to_string()
+    };
+    let head = git(&["rev-parse", "HEAD"]);
+    let parent = git(&["rev-parse", "HEAD^"]);
+    macro_rules! check {
+        ($commit:expr, $expected:expr) => {{
+            assert_eq!(
+                origin_contains(&LocalRepo::new(maker.path()), $commit),
+                $expected
+            );
+            #[cfg(feature = "git2")]
+            assert_eq!(
+                origin_contains(&LocalRepo::with_backend(maker.path(), LibGit2), $commit),
+                $expected
+            );
+            #[cfg(feature = "gix")]
+            assert_eq!(
+                origin_contains(&LocalRepo::with_backend(maker.path(), Gitoxide), $commit),
+                $expected
+            );
+        }};
+    }
+    check!(&head, false);
+    git(&["update-ref", "refs/remotes/origin/trunk", &parent]);
+    check!(&head, false);
+    check!(&parent, true);
+    git(&["update-ref", "refs/remotes/upstream/trunk", &head]);
+    check!(&head, false);
+    git(&["update-ref", "refs/remotes/origin/feature", &head]);
+    check!(&head, true);
+    check!(&parent, true);
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    assert!(matches!(
+        lr.backend().remote_contains(lr.path(), "origin", &head),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_shares_history() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/fork.git")
+        .unwrap();
+    maker
+        .add_remote("upstream", "https://github.com/octocat/repository.git")
+        .unwrap();
+    maker.commit_file("one.txt", "One\n").unwrap();
+    maker.commit_file("two.txt", "Two\n").unwrap();
+    let git = |args: &[&str]| {
+        let r = Command::new("git")
+            .args(args)
+            .current_dir(maker.path())
+            .output()
+            .unwrap();
+        assert!(r.status.success());
+        String::from_utf8(r.stdout).unwrap().trim().to_string()
+    };
+    macro_rules! check {
+        ($expected:expr) => {{
+            let expected: Option<bool> = $expected;
+            let lr = LocalRepo::new(maker.path());
+            assert_eq!(
+                lr.backend()
+                    .shares_history(lr.path(), "upstream", "origin")
+                    .unwrap(),
+                expected
+            );
+            #[cfg(feature = "git2")]
+            {
+                let lr = LocalRepo::with_backend(maker.path(), LibGit2);
+                assert_eq!(
+                    lr.backend()
+                        .shares_history(lr.path(), "upstream", "origin")
+                        .unwrap(),
+                    expected
+                );
+            }
+            #[cfg(feature = "gix")]
+            {
+                let lr = LocalRepo::with_backend(maker.path(), Gitoxide);
+                assert_eq!(
+                    lr.backend()
+                        .shares_history(lr.path(), "upstream", "origin")
+                        .unwrap(),
+                    expected
+                );
+            }
+        }};
+    }
+    check!(None);
+    git(&["update-ref", "refs/remotes/upstream/trunk", "HEAD"]);
+    check!(None);
+    let tree = git(&["rev-parse", "HEAD^{tree}"]);
+    let orphan = git(&["commit-tree", &tree, "-m", "Orphan"]);
+    git(&["update-ref", "refs/remotes/origin/orphan", &orphan]);
+    check!(Some(false));
+    git(&["update-ref", "refs/remotes/origin/trunk", "HEAD^"]);
+    check!(Some(true));
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    assert!(matches!(
+        lr.backend().shares_history(lr.path(), "upstream", "origin"),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_set_remote_url() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let path = maker.path();
+    let urls = || {
+        LocalRepo::new(path)
+            .remote("origin")
+            .expect("remote() should succeed")
+            .raw_fetch_urls
+    };
+    let lr = LocalRepo::new(path);
+    lr.backend()
+        .set_remote_url(lr.path(), "origin", "git@github.com:octocat/one.git")
+        .unwrap();
+    assert_eq!(urls(), ["git@github.com:octocat/one.git"]);
+    assert!(matches!(
+        lr.backend().set_remote_url(lr.path(), "upstream", "x"),
+        Err(LocalRepoError::NoSuchRemote(_))
+    ));
+    #[cfg(feature = "git2")]
+    {
+        let lr = LocalRepo::with_backend(path, LibGit2);
+        lr.backend()
+            .set_remote_url(lr.path(), "origin", "git@github.com:octocat/two.git")
+            .unwrap();
+        assert_eq!(urls(), ["git@github.com:octocat/two.git"]);
+        assert!(matches!(
+            lr.backend().set_remote_url(lr.path(), "upstream", "x"),
+            Err(LocalRepoError::NoSuchRemote(_))
+        ));
+    }
+    let lr = LocalRepo::with_backend(path, NativeGit);
+    assert!(matches!(
+        lr.backend().set_remote_url(lr.path(), "origin", "x"),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_add_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let path = maker.path();
+    let fetch = |remote: &str| {
+        let lr = LocalRepo::new(path);
+        lr.backend()
+            .config_get_all(lr.path(), &format!("remote.{remote}.fetch"))
+            .expect("config_get_all() should succeed")
+    };
+    let lr = LocalRepo::new(path);
+    lr.backend()
+        .add_remote(lr.path(), "upstream", "https://github.com/octocat/one.git")
+        .unwrap();
+    assert_eq!(
+        lr.remote("upstream").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/one.git"]
+    );
+    assert_eq!(fetch("upstream"), ["+refs/heads/*:refs/remotes/upstream/*"]);
+    match lr.backend().add_remote(lr.path(), "origin", "x") {
+        Err(LocalRepoError::RemoteExists(remote)) => assert_eq!(remote, "origin"),
+        r => panic!("add_remote() did not fail with RemoteExists; got: {r:?}"),
+    }
+    lr.backend()
+        .add_fetch_refspec(
+            lr.path(),
+            "upstream",
+            "+refs/pull/*/head:refs/remotes/upstream/pr/*",
+        )
+        .unwrap();
+    assert_eq!(
+        fetch("upstream"),
+        [
+            "+refs/heads/*:refs/remotes/upstream/*",
+            "+refs/pull/*/head:refs/remotes/upstream/pr/*"
+        ]
+    );
+    assert!(matches!(
+        lr.backend().add_fetch_refspec(lr.path(), "nowhere", "x"),
+        Err(LocalRepoError::NoSuchRemote(_))
+    ));
+    #[cfg(feature = "git2")]
+    {
+        let lr = LocalRepo::with_backend(path, LibGit2);
+        lr.backend()
+            .add_remote(lr.path(), "other", "https://github.com/octocat/two.git")
+            .unwrap();
+        assert_eq!(
+            lr.remote("other").unwrap().raw_fetch_urls,
+            ["https://github.com/octocat/two.git"]
+        );
+        assert_eq!(fetch("other"), ["+refs/heads/*:refs/remotes/other/*"]);
+        assert!(matches!(
+            lr.backend().add_remote(lr.path(), "origin", "x"),
+            Err(LocalRepoError::RemoteExists(_))
+        ));
+        lr.backend()
+            .add_fetch_refspec(
+                lr.path(),
+                "other",
+                "+refs/pull/*/head:refs/remotes/other/pr/*",
+            )
+            .unwrap();
+        assert_eq!(
+            fetch("other"),
+            [
+                "+refs/heads/*:refs/remotes/other/*",
+                "+refs/pull/*/head:refs/remotes/other/pr/*"
+            ]
+        );
+        assert!(matches!(
+            lr.backend().add_fetch_refspec(lr.path(), "nowhere", "x"),
+            Err(LocalRepoError::NoSuchRemote(_))
+        ));
+    }
+    let lr = LocalRepo::with_backend(path, NativeGit);
+    assert!(matches!(
+        lr.backend().add_remote(lr.path(), "third", "x"),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_backends_tags_and_describe() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.commit_file("one.txt", "One\n").unwrap();
+    maker.annotated_tag("v0.1.0", "Version 0.1.0").unwrap();
+    maker.commit_file("two.txt", "Two\n").unwrap();
+    maker.tag("v0.2.0").unwrap();
+    maker.annotated_tag("release/0.2", "Release 0.2").unwrap();
+    let head = LocalRepo::new(maker.path()).head_commit().unwrap();
+    macro_rules! check_tags {
+        ($expected:expr) => {{
+            let expected: &[&str] = $expected;
+            let lr = LocalRepo::new(maker.path());
+            assert_eq!(lr.head_tags().unwrap(), expected);
+            #[cfg(feature = "git2")]
+            assert_eq!(
+                LocalRepo::with_backend(maker.path(), LibGit2)
+                    .head_tags()
+                    .unwrap(),
+                expected
+            );
+            #[cfg(feature = "gix")]
+            assert_eq!(
+                LocalRepo::with_backend(maker.path(), Gitoxide)
+                    .head_tags()
+                    .unwrap(),
+                expected
+            );
+        }};
+    }
+    macro_rules! check_describe {
+        ($expected:expr) => {{
+            let expected: Option<Description> = $expected;
+            let lr = LocalRepo::new(maker.path());
+            assert_eq!(lr.describe().unwrap().as_ref(), expected.as_ref());
+            #[cfg(feature = "git2")]
+            assert_eq!(
+                LocalRepo::with_backend(maker.path(), LibGit2)
+                    .describe()
+                    .unwrap()
+                    .as_ref(),
+                expected.as_ref()
+            );
+        }};
+    }
+    check_tags!(&["release/0.2", "v0.2.0"]);
+    check_describe!(Some(Description {
+        tag: String::from("release/0.2"),
+        distance: 0,
+        commit: head,
+    }));
+    maker.commit_file("three.txt", "Three\n").unwrap();
+    maker.commit_file("four.txt", "Four\n").unwrap();
+    let head = LocalRepo::new(maker.path()).head_commit().unwrap();
+    check_tags!(&[]);
+    check_describe!(Some(Description {
+        tag: String::from("release/0.2"),
+        distance: 2,
+        commit: head,
+    }));
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    assert!(matches!(
+        lr.head_tags(),
+        Err(LocalRepoError::Unsupported(_))
+    ));
+    assert!(matches!(lr.describe(), Err(LocalRepoError::Unsupported(_))));
+}
+
+#[test]
+fn test_backends_describe_no_tags() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert!(matches!(lr.describe(), Err(LocalRepoError::NoHeadCommit)));
+    assert!(matches!(lr.head_tags(), Err(LocalRepoError::NoHeadCommit)));
+    assert!(!assert_agree!(maker.path(), |lr| lr.is_detached()));
+    maker.commit_file("one.txt", "One\n").unwrap();
+    assert_eq!(lr.describe().unwrap(), None);
+    #[cfg(feature = "git2")]
+    assert_eq!(
+        LocalRepo::with_backend(maker.path(), LibGit2)
+            .describe()
+            .unwrap(),
+        None
+    );
+    maker.detach().unwrap();
+    assert!(assert_agree!(maker.path(), |lr| lr.is_detached()));
+}
+
+#[test]
+fn test_native_invalid_config() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    append_config(&maker, "[remote \"origin\"\n");
+    let lr = LocalRepo::with_backend(maker.path(), NativeGit);
+    match lr.github_remote("origin") {
+        Err(LocalRepoError::InvalidConfig(e)) => {
+            let config = fs::canonicalize(maker.path())
+                .unwrap()
+                .join(".git")
+                .join("config");
+            assert_eq!(e.path(), config);
+            assert_eq!(
+                e.line(),
+                fs::read_to_string(&config).unwrap().lines().count()
+            );
+            assert_eq!(
+                e.to_string(),
+                format!("bad config line {} in file {}", e.line(), config.display())
+            );
+        }
+        r => panic!("github_remote() did not fail; got: {r:?}"),
+    }
+}
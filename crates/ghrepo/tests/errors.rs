@@ -1,5 +1,7 @@
 use ghrepo::{LocalRepoError, ParseError};
 use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[test]
 fn test_display_parse_error_invalid_spec() {
@@ -22,6 +24,12 @@ fn test_display_parse_error_invalid_name() {
     assert_eq!(e.to_string(), "invalid GitHub repository name: \"foo.git\"");
 }
 
+#[test]
+fn test_display_parse_error_no_url() {
+    let e = ParseError::NoUrl;
+    assert_eq!(e.to_string(), "no URL configured");
+}
+
 #[test]
 fn test_display_local_repo_error_could_not_execute() {
     let e = LocalRepoError::CouldNotExecute(Error::from(ErrorKind::NotFound));
@@ -61,6 +69,15 @@ fn test_display_local_repo_error_no_such_remote() {
     );
 }
 
+#[test]
+fn test_display_local_repo_error_remote_exists() {
+    let e = LocalRepoError::RemoteExists("origin".to_string());
+    assert_eq!(
+        e.to_string(),
+        "remote already exists in Git repository with a different URL: \"origin\""
+    );
+}
+
 #[test]
 fn test_display_local_repo_error_no_upstream() {
     let e = LocalRepoError::NoUpstream("main".to_string());
@@ -70,6 +87,54 @@ fn test_display_local_repo_error_no_upstream() {
     );
 }
 
+#[test]
+fn test_display_local_repo_error_local_push_remote() {
+    let e = LocalRepoError::LocalPushRemote("trunk".to_string());
+    assert_eq!(
+        e.to_string(),
+        "Git branch is pushed to the local repository: \"trunk\""
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_no_remote_head() {
+    let e = LocalRepoError::NoRemoteHead("origin".to_string());
+    assert_eq!(e.to_string(), "HEAD ref not set for Git remote: \"origin\"");
+}
+
+#[test]
+fn test_display_local_repo_error_no_default_remote() {
+    let e = LocalRepoError::NoDefaultRemote;
+    assert_eq!(
+        e.to_string(),
+        "could not determine default GitHub remote for Git repository"
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_not_a_repository() {
+    let e = LocalRepoError::NotARepository(PathBuf::from("/path/to/dir"));
+    assert_eq!(e.to_string(), "not a Git repository: /path/to/dir");
+}
+
+#[test]
+fn test_display_local_repo_error_read_error() {
+    let e = LocalRepoError::ReadError(
+        PathBuf::from("/path/to/repo/.git/HEAD"),
+        Error::new(ErrorKind::PermissionDenied, "Permission denied"),
+    );
+    assert_eq!(
+        e.to_string(),
+        "failed to read /path/to/repo/.git/HEAD: Permission denied"
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_backend_error() {
+    let e = LocalRepoError::BackendError(Box::new(Error::other("libgit2 exploded")));
+    assert_eq!(e.to_string(), "Git backend failed: libgit2 exploded");
+}
+
 #[test]
 fn test_display_local_repo_error_parse_error() {
     let e = LocalRepoError::InvalidRemoteURL(ParseError::InvalidSpec("foo.bar".to_string()));
@@ -78,3 +143,57 @@ fn test_display_local_repo_error_parse_error() {
         "repository remote URL is not a GitHub URL: invalid GitHub repository spec: \"foo.bar\""
     );
 }
+
+#[test]
+fn test_display_local_repo_error_invalid_host() {
+    let e = LocalRepoError::InvalidHost("github.example.com/evil".to_string());
+    assert_eq!(
+        e.to_string(),
+        "invalid GitHub Enterprise Server host: \"github.example.com/evil\""
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_timeout() {
+    let e = LocalRepoError::Timeout(Duration::from_secs(5));
+    assert_eq!(e.to_string(), "Git command timed out after 5s");
+}
+
+#[test]
+fn test_display_local_repo_error_dubious_ownership() {
+    let e = LocalRepoError::DubiousOwnership(PathBuf::from("/path/to/repo"));
+    assert_eq!(
+        e.to_string(),
+        "Git repository is owned by another user and not listed in safe.directory: /path/to/repo"
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_invalid_protocol() {
+    let e = LocalRepoError::InvalidProtocol(String::from("git"));
+    assert_eq!(e.to_string(), "invalid ghrepo.protocol setting: \"git\"");
+}
+
+#[test]
+fn test_display_local_repo_error_no_head_commit() {
+    let e = LocalRepoError::NoHeadCommit;
+    assert_eq!(e.to_string(), "HEAD does not point to a commit");
+}
+
+#[test]
+fn test_display_local_repo_error_not_in_work_tree() {
+    let e = LocalRepoError::NotInWorkTree(PathBuf::from("/path/to/file"));
+    assert_eq!(
+        e.to_string(),
+        "path is not inside the repository's working tree: /path/to/file"
+    );
+}
+
+#[test]
+fn test_display_local_repo_error_unsupported() {
+    let e = LocalRepoError::Unsupported(String::from("file status"));
+    assert_eq!(
+        e.to_string(),
+        "operation not supported by Git backend: file status"
+    );
+}
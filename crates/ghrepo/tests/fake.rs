@@ -0,0 +1,454 @@
+use ghrepo::{
+    BranchUpstream, DefaultRemoteRule, Description, FakeGit, ForkInfo, ForkRelation, GHRepo,
+    GitBackend, LocalRepo, LocalRepoError, ParseError, PermalinkWarning, RepoState, StatusSummary,
+    UrlStyle, WorkTreeStatus, Worktree,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+#[test]
+fn test_fake_defaults() {
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::new());
+    assert!(lr.is_git_repo().unwrap());
+    assert_eq!(
+        lr.inspect().unwrap(),
+        RepoState::WorkTree {
+            toplevel: PathBuf::from("/path/to/repo"),
+            git_dir: PathBuf::from("/path/to/repo/.git"),
+        }
+    );
+    assert_eq!(lr.current_branch().unwrap(), "main");
+    assert_eq!(lr.remotes().unwrap(), []);
+    match lr.github_remote("origin") {
+        Err(LocalRepoError::NoSuchRemote(r)) => assert_eq!(r, "origin"),
+        r => panic!("github_remote() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_not_a_repository() {
+    let lr = LocalRepo::with_backend("/path/to/dir", FakeGit::not_a_repository());
+    assert!(!lr.is_git_repo().unwrap());
+    assert_eq!(lr.inspect().unwrap(), RepoState::NotARepository);
+    match lr.current_branch() {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p.to_str().unwrap(), "/path/to/dir"),
+        r => panic!("current_branch() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_discover() {
+    let lr = LocalRepo::discover_with_backend("/path/to/repo", FakeGit::new()).unwrap();
+    assert_eq!(lr.toplevel(), Some(Path::new("/path/to/repo")));
+    assert_eq!(lr.git_dir(), Some(Path::new("/path/to/repo/.git")));
+    assert_eq!(lr.prefix(), Some(Path::new("")));
+    match LocalRepo::discover_with_backend("/path/to/dir", FakeGit::not_a_repository()) {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p.to_str().unwrap(), "/path/to/dir"),
+        r => panic!("discover_with_backend() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_worktrees() {
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::new().branch("trunk"));
+    assert_eq!(
+        lr.worktrees().unwrap(),
+        [Worktree {
+            path: PathBuf::from("/path/to/repo"),
+            branch: Some(String::from("trunk")),
+            bare: false,
+            locked: false,
+        }]
+    );
+    assert!(!lr.is_bare().unwrap());
+    assert!(!lr.is_linked_worktree().unwrap());
+}
+
+#[test]
+fn test_fake_detached() {
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::new().detached());
+    match lr.current_branch() {
+        Err(LocalRepoError::DetachedHead) => (),
+        r => panic!("current_branch() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_remotes() {
+    let fake = FakeGit::new()
+        .branch("trunk")
+        .remote("upstream", "https://github.com/sourcedog/repository.git")
+        .remote("origin", "gh:octocat/repository")
+        .config("url.git@github.com:.insteadOf", "gh:")
+        .config(
+            "remote.origin.pushurl",
+            "https://github.com/octocat/fork.git",
+        )
+        .config("branch.trunk.remote", "upstream")
+        .config("branch.trunk.merge", "refs/heads/main")
+        .symbolic_ref("refs/remotes/upstream/HEAD", "refs/remotes/upstream/main");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    assert_eq!(lr.current_branch().unwrap(), "trunk");
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    assert_eq!(
+        lr.github_push_remote("origin").unwrap(),
+        GHRepo::new("octocat", "fork").unwrap()
+    );
+    assert_eq!(
+        lr.branch_upstream_details("trunk").unwrap(),
+        BranchUpstream::Remote {
+            remote: "upstream".into(),
+            repo: GHRepo::new("sourcedog", "repository").unwrap(),
+            branch: "main".into(),
+        }
+    );
+    let remotes = lr.remotes().unwrap();
+    assert_eq!(remotes.len(), 2);
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].fetch_urls, ["git@github.com:octocat/repository"]);
+    assert_eq!(remotes[1].name, "upstream");
+    let default = lr.default_github_repo().unwrap();
+    assert_eq!(default.remote, "upstream");
+    assert_eq!(default.rule, DefaultRemoteRule::Upstream);
+    assert_eq!(lr.default_branch("upstream").unwrap(), "main");
+    match lr.default_branch("origin") {
+        Err(LocalRepoError::NoRemoteHead(r)) => assert_eq!(r, "origin"),
+        r => panic!("default_branch() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_gh_resolved_spec() {
+    let fake = FakeGit::new()
+        .remote("origin", "https://git.example.com/octocat/repository.git")
+        .remote("mirror", "https://github.com/mirrors/repository.git")
+        .config("remote.origin.gh-resolved", "octocat/repository");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let default = lr.default_github_repo().unwrap();
+    assert_eq!(default.remote, "origin");
+    assert_eq!(default.repo, GHRepo::new("octocat", "repository").unwrap());
+    assert_eq!(default.rule, DefaultRemoteRule::Origin);
+}
+
+#[test]
+fn test_fake_remote_no_url() {
+    let fake = FakeGit::new().config(
+        "remote.origin.pushurl",
+        "https://github.com/octocat/repository.git",
+    );
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let remote = lr.remote("origin").unwrap();
+    assert!(
+        remote.fetch_urls.is_empty(),
+        "remote should have no fetch URLs"
+    );
+    assert_eq!(remote.repo, Err(ParseError::NoUrl));
+}
+
+#[test]
+fn test_fake_gh_resolved() {
+    let fake = FakeGit::new()
+        .remote("upstream", "https://github.com/sourcedog/repository.git")
+        .remote("origin", "https://github.com/octocat/repository.git")
+        .config("remote.origin.gh-resolved", "base");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let default = lr.default_github_repo().unwrap();
+    assert_eq!(default.remote, "origin");
+    assert_eq!(default.rule, DefaultRemoteRule::GhResolved);
+}
+
+#[test]
+fn test_fake_permalink() {
+    let tmp_path = tempdir().unwrap();
+    fs::create_dir(tmp_path.path().join("src")).unwrap();
+    fs::write(
+        tmp_path.path().join("src").join("main.rs"),
+        "fn main() {}\n",
+    )
+    .unwrap();
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    let fake = FakeGit::new()
+        .remote("origin", "https://github.com/octocat/repository.git")
+        .head_commit(commit);
+    let lr = LocalRepo::with_backend(tmp_path.path(), fake);
+    let link = lr.permalink("src/main.rs", Some(3..=5)).unwrap();
+    assert_eq!(
+        link.url,
+        format!("https://github.com/octocat/repository/blob/{commit}/src/main.rs#L3-L5")
+    );
+    assert_eq!(link.commit, commit);
+    assert_eq!(link.path, "src/main.rs");
+    assert_eq!(link.warnings, []);
+    let lr = LocalRepo::with_backend(
+        tmp_path.path(),
+        FakeGit::new()
+            .remote("origin", "https://github.com/octocat/repository.git")
+            .head_commit(commit)
+            .unpushed(),
+    );
+    let link = lr.permalink("src/main.rs", None).unwrap();
+    assert_eq!(
+        link.url,
+        format!("https://github.com/octocat/repository/blob/{commit}/src/main.rs")
+    );
+    assert_eq!(
+        link.warnings,
+        [PermalinkWarning::NotPushed(String::from("origin"))]
+    );
+}
+
+#[test]
+fn test_fake_permalink_no_head_commit() {
+    let tmp_path = tempdir().unwrap();
+    fs::write(tmp_path.path().join("file.txt"), "Text\n").unwrap();
+    let fake = FakeGit::new().remote("origin", "https://github.com/octocat/repository.git");
+    let lr = LocalRepo::with_backend(tmp_path.path(), fake);
+    match lr.permalink("file.txt", None) {
+        Err(LocalRepoError::NoHeadCommit) => (),
+        r => panic!("permalink() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_head_tags() {
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    let fake = FakeGit::new()
+        .head_commit(commit)
+        .tag("v1.0.0")
+        .tag("latest");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    assert!(!lr.is_detached().unwrap());
+    assert_eq!(lr.head_commit().unwrap(), commit);
+    assert_eq!(lr.head_tags().unwrap(), ["latest", "v1.0.0"]);
+    let description = lr.describe().unwrap().unwrap();
+    assert!(description.is_exact());
+    assert_eq!(description.to_string(), "v1.0.0");
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(
+        repo.release_url(&description.tag),
+        "https://github.com/octocat/repository/releases/tag/v1.0.0"
+    );
+}
+
+#[test]
+fn test_fake_no_head_commit() {
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::new().detached());
+    assert!(lr.is_detached().unwrap());
+    match lr.head_commit() {
+        Err(LocalRepoError::NoHeadCommit) => (),
+        r => panic!("head_commit() did not fail; got: {r:?}"),
+    }
+    match lr.head_tags() {
+        Err(LocalRepoError::NoHeadCommit) => (),
+        r => panic!("head_tags() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_description_display() {
+    let description = Description {
+        tag: String::from("v1.0.0"),
+        distance: 3,
+        commit: String::from("0123456789abcdef0123456789abcdef01234567"),
+    };
+    assert!(!description.is_exact());
+    assert_eq!(description.to_string(), "v1.0.0-3-g0123456");
+}
+
+#[test]
+fn test_fake_status_summary() {
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    let fake = FakeGit::new()
+        .head_commit(commit)
+        .remote("origin", "https://github.com/octocat/repository.git")
+        .config("branch.main.remote", "origin")
+        .config("branch.main.merge", "refs/heads/trunk")
+        .status(WorkTreeStatus {
+            ahead: 2,
+            behind: 1,
+            staged: 3,
+            unstaged: 0,
+            untracked: 1,
+            conflicted: 0,
+            ..WorkTreeStatus::default()
+        });
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let summary = lr.status_summary().unwrap();
+    assert_eq!(
+        summary,
+        StatusSummary {
+            branch: Some(String::from("main")),
+            commit: Some(commit.to_string()),
+            upstream: Some(BranchUpstream::Remote {
+                remote: String::from("origin"),
+                repo: GHRepo::new("octocat", "repository").unwrap(),
+                branch: String::from("trunk"),
+            }),
+            ahead: 2,
+            behind: 1,
+            staged: 3,
+            unstaged: 0,
+            untracked: 1,
+            conflicted: 0,
+        }
+    );
+    assert!(!summary.is_clean());
+}
+
+#[test]
+fn test_fake_status_summary_no_upstream() {
+    let lr = LocalRepo::with_backend(
+        "/path/to/repo",
+        FakeGit::new()
+            .remote("origin", "https://gitlab.com/octocat/repository.git")
+            .config("branch.main.remote", "origin")
+            .config("branch.main.merge", "refs/heads/main"),
+    );
+    let summary = lr.status_summary().unwrap();
+    assert_eq!(summary.branch.as_deref(), Some("main"));
+    assert_eq!(summary.commit, None);
+    assert_eq!(summary.upstream, None);
+    assert!(summary.is_clean());
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::new().detached());
+    let summary = lr.status_summary().unwrap();
+    assert_eq!(summary.branch, None);
+    assert_eq!(summary.upstream, None);
+    let lr = LocalRepo::with_backend("/path/to/repo", FakeGit::not_a_repository());
+    assert!(matches!(
+        lr.status_summary(),
+        Err(LocalRepoError::NotARepository(_))
+    ));
+}
+
+#[test]
+fn test_fake_fork_info() {
+    let fake = FakeGit::new()
+        .remote("origin", "git@github.com:me/Repository.git")
+        .remote("upstream", "https://github.com/octocat/repository.git");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let info = lr.fork_info().unwrap();
+    assert_eq!(
+        info,
+        ForkInfo {
+            parent_remote: String::from("upstream"),
+            parent: GHRepo::new("octocat", "repository").unwrap(),
+            fork_remote: String::from("origin"),
+            fork: GHRepo::new("me", "Repository").unwrap(),
+            relation: ForkRelation::SameName,
+        }
+    );
+    assert!(info.is_fork());
+    let info = lr.remote_fork_info("origin", "origin").unwrap();
+    assert_eq!(info.relation, ForkRelation::SameRepository);
+    assert!(!info.is_fork());
+}
+
+#[test]
+fn test_fake_fork_info_renamed() {
+    let fake = FakeGit::new()
+        .remote("origin", "https://github.com/me/my-fork.git")
+        .remote("upstream", "https://github.com/octocat/repository.git");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake.clone());
+    let info = lr.fork_info().unwrap();
+    assert_eq!(info.relation, ForkRelation::Renamed);
+    assert!(info.is_fork());
+    let lr = LocalRepo::with_backend("/path/to/repo", fake.unrelated_histories());
+    let info = lr.fork_info().unwrap();
+    assert_eq!(info.relation, ForkRelation::Unrelated);
+    assert!(!info.is_fork());
+    assert_eq!(info.relation.to_string(), "unrelated");
+}
+
+#[test]
+fn test_fake_fork_info_no_upstream() {
+    let lr = LocalRepo::with_backend(
+        "/path/to/repo",
+        FakeGit::new().remote("origin", "https://github.com/me/repository.git"),
+    );
+    match lr.fork_info() {
+        Err(LocalRepoError::NoSuchRemote(remote)) => assert_eq!(remote, "upstream"),
+        r => panic!("fork_info() did not fail with NoSuchRemote; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_fake_set_remote_url_style() {
+    let fake = FakeGit::new()
+        .remote("origin", "gh:octocat/repository")
+        .config("url.https://github.com/.insteadOf", "gh:");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let change = lr
+        .set_remote_url_style(
+            "origin",
+            &UrlStyle::EnterpriseSsh(String::from("github.example.com")),
+        )
+        .unwrap();
+    assert_eq!(change.old_url, "gh:octocat/repository");
+    assert_eq!(
+        change.new_url,
+        "git@github.example.com:octocat/repository.git"
+    );
+    let remote = lr.remote("origin").unwrap();
+    assert_eq!(
+        remote.raw_fetch_urls,
+        ["git@github.example.com:octocat/repository.git"]
+    );
+    assert!(remote.repo.is_err());
+    let change = lr
+        .set_remote_url_style(
+            "origin",
+            &UrlStyle::EnterpriseHttps(String::from("github.example.com")),
+        )
+        .unwrap();
+    assert_eq!(
+        change.new_url,
+        "https://github.example.com/octocat/repository.git"
+    );
+    lr.set_remote_url_style("origin", &UrlStyle::Https).unwrap();
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+}
+
+#[test]
+fn test_fake_add_github_remote() {
+    let fake = FakeGit::new().remote("origin", "https://github.com/me/repository.git");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake);
+    let parent = GHRepo::new("octocat", "repository").unwrap();
+    assert!(
+        lr.add_github_remote("upstream", &parent, &UrlStyle::Ssh)
+            .unwrap()
+    );
+    assert_eq!(
+        lr.remote("upstream").unwrap().raw_fetch_urls,
+        ["git@github.com:octocat/repository.git"]
+    );
+    assert!(
+        !lr.add_github_remote("upstream", &parent, &UrlStyle::Https)
+            .unwrap()
+    );
+    match lr.add_github_remote("origin", &parent, &UrlStyle::Https) {
+        Err(LocalRepoError::RemoteExists(remote)) => assert_eq!(remote, "origin"),
+        r => panic!("add_github_remote() did not fail with RemoteExists; got: {r:?}"),
+    }
+    assert!(lr.fetch_pull_requests("upstream").unwrap());
+    assert!(!lr.fetch_pull_requests("upstream").unwrap());
+    assert_eq!(
+        lr.backend()
+            .config_get_all(lr.path(), "remote.upstream.fetch")
+            .unwrap(),
+        [
+            "+refs/heads/*:refs/remotes/upstream/*",
+            "+refs/pull/*/head:refs/remotes/upstream/pr/*"
+        ]
+    );
+    assert!(matches!(
+        lr.fetch_pull_requests("nowhere"),
+        Err(LocalRepoError::NoSuchRemote(_))
+    ));
+}
@@ -1,5 +1,5 @@
 #![allow(clippy::items_after_test_module)]
-use ghrepo::{GHRepo, ParseError};
+use ghrepo::{GHRepo, ParseError, UrlStyle};
 use rstest::rstest;
 use rstest_reuse::{self, apply, template};
 use std::str::FromStr;
@@ -73,6 +73,70 @@ fn test_html_url() {
     assert_eq!(r.html_url().parse::<GHRepo>(), Ok(r));
 }
 
+#[test]
+fn test_blob_url() {
+    let r = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(
+        r.blob_url("main", "src/lib.rs"),
+        "https://github.com/octocat/repository/blob/main/src/lib.rs"
+    );
+    assert_eq!(
+        r.blob_url("v1.0", "docs/read me#1%.md"),
+        "https://github.com/octocat/repository/blob/v1.0/docs/read%20me%231%25.md"
+    );
+}
+
+#[test]
+fn test_commit_url() {
+    let r = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(
+        r.commit_url("0123456789abcdef0123456789abcdef01234567"),
+        "https://github.com/octocat/repository/commit/0123456789abcdef0123456789abcdef01234567"
+    );
+}
+
+#[rstest]
+#[case(UrlStyle::Https, "https://github.com/octocat/repository.git")]
+#[case(UrlStyle::Ssh, "git@github.com:octocat/repository.git")]
+#[case(
+    UrlStyle::Ssh443,
+    "ssh://git@ssh.github.com:443/octocat/repository.git"
+)]
+#[case(
+    UrlStyle::EnterpriseHttps(String::from("github.example.com")),
+    "https://github.example.com/octocat/repository.git"
+)]
+#[case(
+    UrlStyle::EnterpriseSsh(String::from("github.example.com")),
+    "git@github.example.com:octocat/repository.git"
+)]
+fn test_url_style(#[case] style: UrlStyle, #[case] url: &str) {
+    let r = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(style.url(&r), url);
+}
+
+#[test]
+fn test_release_url() {
+    let r = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(
+        r.release_url("v1.0.0"),
+        "https://github.com/octocat/repository/releases/tag/v1.0.0"
+    );
+    assert_eq!(
+        r.release_url("release/1.0 rc"),
+        "https://github.com/octocat/repository/releases/tag/release/1.0%20rc"
+    );
+}
+
+#[test]
+fn test_tree_url() {
+    let r = GHRepo::new("octocat", "repository").unwrap();
+    assert_eq!(
+        r.tree_url("v1.0.0"),
+        "https://github.com/octocat/repository/tree/v1.0.0"
+    );
+}
+
 #[test]
 fn test_ssh_url() {
     let r = GHRepo::new("octocat", "repository").unwrap();
@@ -97,6 +161,11 @@ fn test_ssh_url() {
 )]
 #[case("ssh://git@github.com/-/test", "-", "test")]
 #[case("SSH://git@GITHUB.COM/-/test", "-", "test")]
+#[case(
+    "ssh://git@ssh.github.com:443/jwodder/headerparser.git",
+    "jwodder",
+    "headerparser"
+)]
 #[case(
     "https://api.github.com/repos/jwodder/headerparser",
     "jwodder",
@@ -236,6 +305,8 @@ fn repo_urls(#[case] url: &str, #[case] owner: &str, #[case] name: &str) {}
 #[case("ssh://GIT@github.com/-/test")]
 #[case("https://http://github.com/joe-coder/hello.world")]
 #[case("https://github.com/-Jerry-/geshi-1.0.Git")]
+#[case("ghe.example.com/jwodder/headerparser")]
+#[case("github.com/jwodder")]
 fn bad_repos(#[case] url: &str) {}
 
 #[apply(repo_urls)]
@@ -244,6 +315,8 @@ fn bad_repos(#[case] url: &str) {}
 #[case("nonely/headerparser", "nonely", "headerparser")]
 #[case("none-none/headerparser", "none-none", "headerparser")]
 #[case("nonenone/headerparser", "nonenone", "headerparser")]
+#[case("GitHub.com/jwodder/headerparser", "jwodder", "headerparser")]
+#[case("GITHUB.COM/jwodder/headerparser", "jwodder", "headerparser")]
 fn test_from_str(#[case] spec: &str, #[case] owner: &str, #[case] name: &str) {
     let r = GHRepo::new(owner, name).unwrap();
     assert_eq!(GHRepo::from_str(spec), Ok(r));
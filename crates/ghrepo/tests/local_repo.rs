@@ -1,6 +1,12 @@
-use ghrepo::{GHRepo, LocalRepo, LocalRepoError};
+use ghrepo::{
+    BranchUpstream, CloneOptions, DefaultRemoteRule, GHRepo, GitBackend, LocalRepo, LocalRepoError,
+    ParseError, PermalinkWarning, RepoState, UrlStyle,
+};
 use repomaker::RepoMaker;
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
 use tempfile::tempdir;
 use which::which;
 
@@ -172,6 +178,94 @@ fn test_github_remote_non_utf8_url() {
     }
 }
 
+#[test]
+fn test_github_push_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "fork").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.clone_url()).unwrap();
+    maker.add_push_url("origin", fork.ssh_url()).unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.github_remote("origin").unwrap(), origin);
+    assert_eq!(lr.github_push_remote("origin").unwrap(), fork);
+}
+
+#[test]
+fn test_github_push_remote_no_push_url() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.clone_url()).unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.github_push_remote("origin").unwrap(), origin);
+}
+
+#[test]
+fn test_github_push_remote_no_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.github_push_remote("origin") {
+        Err(LocalRepoError::NoSuchRemote(rem)) if rem == "origin" => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_push_remote_for_branch() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.push_remote_for_branch("trunk").unwrap(), "origin");
+    maker.set_upstream("trunk", "upstream").unwrap();
+    assert_eq!(lr.push_remote_for_branch("trunk").unwrap(), "upstream");
+    maker.set_config("remote.pushDefault", "mine").unwrap();
+    assert_eq!(lr.push_remote_for_branch("trunk").unwrap(), "mine");
+    maker.set_config("branch.trunk.pushRemote", "fork").unwrap();
+    assert_eq!(lr.push_remote_for_branch("trunk").unwrap(), "fork");
+    assert_eq!(lr.push_remote_for_branch("other").unwrap(), "mine");
+    maker.set_config("branch.trunk.pushRemote", ".").unwrap();
+    match lr.push_remote_for_branch("trunk") {
+        Err(LocalRepoError::LocalPushRemote(branch)) if branch == "trunk" => (),
+        r => panic!("Got wrong result: {r:?}"),
+    }
+    match lr.branch_push_repo("trunk") {
+        Err(LocalRepoError::LocalPushRemote(branch)) if branch == "trunk" => (),
+        r => panic!("Got wrong result: {r:?}"),
+    }
+}
+
+#[test]
+fn test_branch_push_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("upstream", upstream.clone_url()).unwrap();
+    maker.add_remote("fork", fork.ssh_url()).unwrap();
+    maker.set_upstream("trunk", "upstream").unwrap();
+    maker.set_config("remote.pushDefault", "fork").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.branch_upstream("trunk").unwrap(), upstream);
+    assert_eq!(lr.branch_push_repo("trunk").unwrap(), fork);
+}
+
 #[test]
 fn test_branch_upstream_no_upstream() {
     if which("git").is_err() {
@@ -202,3 +296,1155 @@ fn test_branch_upstream() {
         e => panic!("Got wrong result: {e:?}"),
     }
 }
+
+#[test]
+fn test_branch_upstream_details() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("github", repo.clone_url()).unwrap();
+    maker.set_upstream("trunk", "github").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/main")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(
+        lr.branch_upstream_details("trunk").unwrap(),
+        BranchUpstream::Remote {
+            remote: String::from("github"),
+            repo,
+            branch: String::from("main"),
+        }
+    );
+}
+
+#[test]
+fn test_branch_upstream_details_local() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.set_upstream("feature", ".").unwrap();
+    maker
+        .set_config("branch.feature.merge", "refs/heads/trunk")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(
+        lr.branch_upstream_details("feature").unwrap(),
+        BranchUpstream::Local {
+            branch: String::from("trunk"),
+        }
+    );
+}
+
+#[test]
+fn test_branch_upstream_details_no_upstream() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.branch_upstream_details("trunk") {
+        Err(LocalRepoError::NoUpstream(branch)) if branch == "trunk" => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_branch_upstream_details_no_merge() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("github", repo.clone_url()).unwrap();
+    maker.set_upstream("trunk", "github").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.branch_upstream_details("trunk") {
+        Err(LocalRepoError::NoUpstream(branch)) if branch == "trunk" => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_branch_upstream_details_missing_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.set_upstream("trunk", "github").unwrap();
+    maker
+        .set_config("branch.trunk.merge", "refs/heads/trunk")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.branch_upstream_details("trunk") {
+        Err(LocalRepoError::NoSuchRemote(remote)) if remote == "github" => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_default_branch() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", repo.clone_url()).unwrap();
+    maker.set_remote_head("origin", "main").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.default_branch("origin").unwrap(), "main");
+}
+
+#[test]
+fn test_default_branch_unset() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", repo.clone_url()).unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.default_branch("origin") {
+        Err(LocalRepoError::NoRemoteHead(remote)) if remote == "origin" => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_default_github_repo_gh_resolved() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker.add_remote("upstream", upstream.clone_url()).unwrap();
+    maker
+        .set_config("remote.origin.gh-resolved", "base")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let dr = lr.default_github_repo().unwrap();
+    assert_eq!(dr.remote, "origin");
+    assert_eq!(dr.repo, origin);
+    assert_eq!(dr.rule, DefaultRemoteRule::GhResolved);
+}
+
+#[test]
+fn test_default_github_repo_upstream() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let upstream = GHRepo::new("sourcedog", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker.add_remote("upstream", upstream.clone_url()).unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let dr = lr.default_github_repo().unwrap();
+    assert_eq!(dr.remote, "upstream");
+    assert_eq!(dr.repo, upstream);
+    assert_eq!(dr.rule, DefaultRemoteRule::Upstream);
+}
+
+#[test]
+fn test_default_github_repo_origin() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let other = GHRepo::new("sourcedog", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.ssh_url()).unwrap();
+    maker.add_remote("other", other.clone_url()).unwrap();
+    maker
+        .add_remote("upstream", "https://git.example.com/repo.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let dr = lr.default_github_repo().unwrap();
+    assert_eq!(dr.remote, "origin");
+    assert_eq!(dr.repo, origin);
+    assert_eq!(dr.rule, DefaultRemoteRule::Origin);
+}
+
+#[test]
+fn test_default_github_repo_only_github_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("github", repo.ssh_url()).unwrap();
+    maker
+        .add_remote("origin", "https://git.example.com/repo.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let dr = lr.default_github_repo().unwrap();
+    assert_eq!(dr.remote, "github");
+    assert_eq!(dr.repo, repo);
+    assert_eq!(dr.rule, DefaultRemoteRule::OnlyGitHubRemote);
+}
+
+#[test]
+fn test_default_github_repo_ambiguous() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote(
+            "mine",
+            GHRepo::new("octocat", "repository").unwrap().ssh_url(),
+        )
+        .unwrap();
+    maker
+        .add_remote(
+            "theirs",
+            GHRepo::new("sourcedog", "repository").unwrap().ssh_url(),
+        )
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.default_github_repo() {
+        Err(LocalRepoError::NoDefaultRemote) => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_default_github_repo_no_remotes() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.default_github_repo() {
+        Err(LocalRepoError::NoDefaultRemote) => (),
+        e => panic!("Got wrong result: {e:?}"),
+    }
+}
+
+#[test]
+fn test_remotes() {
+    if which("git").is_err() {
+        return;
+    }
+    let origin = GHRepo::new("octocat", "repository").unwrap();
+    let fork = GHRepo::new("octocat", "fork").unwrap();
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", origin.clone_url()).unwrap();
+    maker.add_push_url("origin", fork.ssh_url()).unwrap();
+    maker
+        .add_remote("upstream", "https://git.example.com/repo.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let remotes = lr.remotes().unwrap();
+    assert_eq!(remotes.len(), 2);
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].fetch_urls, [origin.clone_url()]);
+    assert_eq!(remotes[0].push_urls, [fork.ssh_url()]);
+    assert_eq!(remotes[0].repo, Ok(origin));
+    assert_eq!(remotes[1].name, "upstream");
+    assert_eq!(remotes[1].fetch_urls, ["https://git.example.com/repo.git"]);
+    assert_eq!(remotes[1].push_urls, ["https://git.example.com/repo.git"]);
+    assert_eq!(
+        remotes[1].raw_fetch_urls,
+        ["https://git.example.com/repo.git"]
+    );
+    assert!(remotes[1].raw_push_urls.is_empty());
+    assert_eq!(
+        remotes[1].repo,
+        Err(ParseError::InvalidSpec(String::from(
+            "https://git.example.com/repo.git"
+        )))
+    );
+}
+
+#[test]
+fn test_remote_instead_of() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .set_config("url.https://github.com/.insteadOf", "gh:")
+        .unwrap();
+    maker
+        .set_config("url.git@github.com:.pushInsteadOf", "gh:")
+        .unwrap();
+    maker.add_remote("origin", "gh:octocat/repository").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    let remote = lr.remote("origin").unwrap();
+    assert_eq!(remote.name, "origin");
+    assert_eq!(remote.fetch_urls, ["https://github.com/octocat/repository"]);
+    assert_eq!(remote.push_urls, ["git@github.com:octocat/repository"]);
+    assert_eq!(remote.raw_fetch_urls, ["gh:octocat/repository"]);
+    assert!(remote.raw_push_urls.is_empty());
+    assert_eq!(remote.repo, GHRepo::new("octocat", "repository"));
+}
+
+#[test]
+fn test_remote_nonexistent() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.remote("origin") {
+        Err(LocalRepoError::NoSuchRemote(r)) => assert_eq!(r, "origin"),
+        r => panic!("remote() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_remotes_none() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.remotes().unwrap(), []);
+}
+
+#[test]
+fn test_builder_bad_git_path() {
+    let tmp_path = tempdir().unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .git_path(tmp_path.path().join("no-such-git"))
+        .build();
+    match lr.is_git_repo() {
+        Err(LocalRepoError::CouldNotExecute(_)) => (),
+        r => panic!("is_git_repo() did not fail with CouldNotExecute; got: {r:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_timeout() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+    let tmp_path = tempdir().unwrap();
+    let script = tmp_path.path().join("slow-git");
+    fs::write(&script, "#!/bin/sh\nexec sleep 10\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .git_path(&script)
+        .timeout(Duration::from_millis(200))
+        .build();
+    let start = Instant::now();
+    match lr.current_branch() {
+        Err(LocalRepoError::Timeout(d)) => assert_eq!(d, Duration::from_millis(200)),
+        r => panic!("current_branch() did not time out; got: {r:?}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_builder_timeout_inherited_pipe() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+    let tmp_path = tempdir().unwrap();
+    let script = tmp_path.path().join("forking-git");
+    // The backgrounded `sleep` keeps stdout open after the script exits
+    fs::write(&script, "#!/bin/sh\necho main\nsleep 10 &\nexit 0\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .git_path(&script)
+        .timeout(Duration::from_millis(200))
+        .build();
+    let start = Instant::now();
+    match lr.current_branch() {
+        Err(LocalRepoError::Timeout(d)) => assert_eq!(d, Duration::from_millis(200)),
+        r => panic!("current_branch() did not time out; got: {r:?}"),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_builder_git_dir_env() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let tmp_path = tempdir().unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .env("GIT_DIR", maker.path().join(".git"))
+        .env("GIT_WORK_TREE", maker.path())
+        .build();
+    assert!(lr.is_git_repo().unwrap());
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+}
+
+#[test]
+fn test_builder_config() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker.add_remote("origin", "gh:octocat/repository").unwrap();
+    let lr = LocalRepo::builder(maker.path())
+        .config("url.https://github.com/.insteadOf", "gh:")
+        .build();
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+}
+
+#[test]
+fn test_capture_stderr_not_a_repository() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .env("GIT_CEILING_DIRECTORIES", tmp_path.path())
+        .capture_stderr(true)
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+        r => panic!("current_branch() did not fail with NotARepository; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_capture_stderr_non_english_locale() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .env("GIT_CEILING_DIRECTORIES", tmp_path.path())
+        .env("LANG", "de_DE.UTF-8")
+        .env("LC_ALL", "de_DE.UTF-8")
+        .env("LANGUAGE", "de")
+        .capture_stderr(true)
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+        r => panic!("current_branch() did not fail with NotARepository; got: {r:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_capture_stderr_translated_messages() {
+    use std::os::unix::fs::PermissionsExt;
+    let tmp_path = tempdir().unwrap();
+    let script = tmp_path.path().join("german-git");
+    // Emulate a Git with German translations installed, which only outputs
+    // English messages in the C locale
+    fs::write(
+        &script,
+        concat!(
+            "#!/bin/sh\n",
+            "if [ \"$LC_ALL\" = C ] && [ -z \"$LANGUAGE\" ]\n",
+            "then echo 'fatal: not a git repository (or any of the parent directories): .git' >&2\n",
+            "else echo 'fatal: Kein Git-Repository (oder irgendein Elternverzeichnis): .git' >&2\n",
+            "fi\n",
+            "exit 128\n",
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .git_path(&script)
+        .env("LANG", "de_DE.UTF-8")
+        .env("LANGUAGE", "de")
+        .capture_stderr(true)
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+        r => panic!("current_branch() did not fail with NotARepository; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_capture_stderr_dubious_ownership() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::builder(maker.path())
+        .env("GIT_TEST_ASSUME_DIFFERENT_OWNER", "1")
+        .capture_stderr(true)
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::DubiousOwnership(p)) => assert_eq!(
+            fs::canonicalize(p).unwrap(),
+            fs::canonicalize(maker.path()).unwrap()
+        ),
+        r => panic!("current_branch() did not fail with DubiousOwnership; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_capture_stderr_bad_config() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let config_path = maker.path().join(".git").join("config");
+    let mut config = fs::read_to_string(&config_path).unwrap();
+    let lineno = config.lines().count() + 1;
+    config.push_str("[core\n");
+    fs::write(&config_path, config).unwrap();
+    let lr = LocalRepo::builder(maker.path())
+        .capture_stderr(true)
+        .build();
+    match lr.github_remote("origin") {
+        Err(LocalRepoError::InvalidConfig(e)) => {
+            assert_eq!(e.path(), maker.path().join(".git/config"));
+            assert_eq!(e.line(), lineno);
+        }
+        r => panic!("github_remote() did not fail with InvalidConfig; got: {r:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_capture_stderr_command_failed() {
+    use std::os::unix::fs::PermissionsExt;
+    let tmp_path = tempdir().unwrap();
+    let script = tmp_path.path().join("failing-git");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'error: something broke' >&2\nexit 3\n",
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .git_path(&script)
+        .config("core.quotePath", "false")
+        .capture_stderr(true)
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::CommandFailed(e)) => {
+            assert_eq!(
+                e.argv(),
+                [
+                    script.as_os_str(),
+                    OsStr::new("-c"),
+                    OsStr::new("core.quotePath=false"),
+                    OsStr::new("symbolic-ref"),
+                    OsStr::new("--short"),
+                    OsStr::new("-q"),
+                    OsStr::new("HEAD"),
+                ]
+            );
+            assert_eq!(e.status().code(), Some(3));
+            assert_eq!(e.stderr(), Some("error: something broke"));
+            assert_eq!(
+                LocalRepoError::CommandFailed(e).to_string(),
+                "Git command exited unsuccessfully: exit status: 3: error: something broke"
+            );
+        }
+        r => panic!("current_branch() did not fail with CommandFailed; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_no_capture_stderr_not_a_repository() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let lr = LocalRepo::builder(tmp_path.path())
+        .env("GIT_CEILING_DIRECTORIES", tmp_path.path())
+        .build();
+    match lr.current_branch() {
+        Err(LocalRepoError::CommandFailed(e)) => {
+            assert_eq!(e.status().code(), Some(128));
+            assert_eq!(e.stderr(), None);
+        }
+        r => panic!("current_branch() did not fail with CommandFailed; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_inspect_unsafe_ownership() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::builder(maker.path())
+        .env("GIT_TEST_ASSUME_DIFFERENT_OWNER", "1")
+        .build();
+    match lr.inspect().unwrap() {
+        RepoState::UnsafeOwnership { path } => assert_eq!(
+            fs::canonicalize(path).unwrap(),
+            fs::canonicalize(maker.path()).unwrap()
+        ),
+        r => panic!("inspect() did not report unsafe ownership; got: {r:?}"),
+    }
+    assert!(!lr.is_git_repo().unwrap());
+}
+
+#[test]
+fn test_discover() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let subdir = maker.path().join("src").join("bin");
+    fs::create_dir_all(&subdir).unwrap();
+    let lr = LocalRepo::discover(&subdir).unwrap();
+    let root = fs::canonicalize(maker.path()).unwrap();
+    assert_eq!(lr.path(), subdir);
+    assert_eq!(lr.toplevel(), Some(root.as_path()));
+    assert_eq!(lr.git_dir(), Some(root.join(".git").as_path()));
+    assert_eq!(lr.common_dir(), Some(root.join(".git").as_path()));
+    assert_eq!(lr.prefix(), Some(Path::new("src/bin")));
+    assert_eq!(lr.current_branch().unwrap(), "trunk");
+}
+
+#[test]
+fn test_discover_not_a_repository() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    match LocalRepo::discover(tmp_path.path()) {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, tmp_path.path()),
+        r => panic!("discover() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_discover_ceiling_directories() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let subdir = maker.path().join("sub");
+    fs::create_dir(&subdir).unwrap();
+    match LocalRepo::builder(&subdir)
+        .env("GIT_CEILING_DIRECTORIES", maker.path())
+        .discover()
+    {
+        Err(LocalRepoError::NotARepository(p)) => assert_eq!(p, subdir),
+        r => panic!("discover() did not fail; got: {r:?}"),
+    }
+    let lr = LocalRepo::builder(maker.path())
+        .env("GIT_CEILING_DIRECTORIES", maker.path())
+        .discover()
+        .unwrap();
+    assert_eq!(
+        lr.toplevel(),
+        Some(fs::canonicalize(maker.path()).unwrap().as_path())
+    );
+}
+
+#[test]
+fn test_no_layout_without_discover() {
+    let lr = LocalRepo::new("/path/to/repo");
+    assert_eq!(lr.layout(), None);
+    assert_eq!(lr.toplevel(), None);
+    assert_eq!(lr.git_dir(), None);
+    assert_eq!(lr.common_dir(), None);
+    assert_eq!(lr.prefix(), None);
+}
+
+#[test]
+fn test_submodules_none() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    assert_eq!(lr.submodules().unwrap(), []);
+}
+
+#[test]
+fn test_submodules_relative_urls() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/fork.git")
+        .unwrap();
+    maker
+        .add_remote("upstream", "git@github.com:acme/project.git")
+        .unwrap();
+    maker.set_upstream("trunk", "upstream").unwrap();
+    fs::write(
+        maker.path().join(".gitmodules"),
+        concat!(
+            "[submodule \"sibling\"]\n",
+            "\tpath = sibling\n",
+            "\turl = ../sibling.git\n",
+            "[submodule \"other\"]\n",
+            "\tpath = vendor/other\n",
+            "\turl = ../../octocat/other\n",
+            "[submodule \"nested\"]\n",
+            "\tpath = nested\n",
+            "\turl = ./nested.git\n",
+            "[submodule \"elsewhere\"]\n",
+            "\tpath = elsewhere\n",
+            "\turl = https://example.com/elsewhere.git\n",
+        ),
+    )
+    .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let repos = lr
+        .submodules()
+        .unwrap()
+        .into_iter()
+        .map(|sm| (sm.path, sm.repo))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        repos,
+        [
+            (
+                String::from("elsewhere"),
+                Err(ParseError::InvalidSpec(String::from(
+                    "https://example.com/elsewhere.git"
+                )))
+            ),
+            (
+                String::from("nested"),
+                Err(ParseError::InvalidSpec(String::from("./nested.git")))
+            ),
+            (
+                String::from("sibling"),
+                Ok(GHRepo::new("acme", "sibling").unwrap())
+            ),
+            (
+                String::from("vendor/other"),
+                Ok(GHRepo::new("octocat", "other").unwrap())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_permalink() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    fs::create_dir(maker.path().join("src")).unwrap();
+    maker.commit_file("src/lib.rs", "pub fn f() {}\n").unwrap();
+    let srcdir = maker.path().join("src");
+    let lr = LocalRepo::new(&srcdir);
+    let commit = lr.backend().head_commit(lr.path()).unwrap().unwrap();
+    let link = lr.permalink("lib.rs", Some(10..=10)).unwrap();
+    assert_eq!(
+        link.url,
+        format!("https://github.com/octocat/repository/blob/{commit}/src/lib.rs#L10")
+    );
+    assert_eq!(link.repo, GHRepo::new("octocat", "repository").unwrap());
+    assert_eq!(link.path, "src/lib.rs");
+    assert_eq!(
+        link.warnings,
+        [PermalinkWarning::NotPushed(String::from("origin"))]
+    );
+    let r = Command::new("git")
+        .args(["update-ref", "refs/remotes/origin/trunk", "HEAD"])
+        .current_dir(maker.path())
+        .status()
+        .unwrap();
+    assert!(r.success());
+    assert_eq!(lr.permalink("lib.rs", None).unwrap().warnings, []);
+    fs::write(srcdir.join("lib.rs"), "pub fn g() {}\n").unwrap();
+    assert_eq!(
+        lr.permalink("lib.rs", None).unwrap().warnings,
+        [PermalinkWarning::Modified]
+    );
+    fs::write(srcdir.join("new.rs"), "pub fn h() {}\n").unwrap();
+    assert_eq!(
+        lr.permalink(srcdir.join("new.rs"), None).unwrap().warnings,
+        [PermalinkWarning::Untracked]
+    );
+}
+
+#[test]
+fn test_permalink_errors() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    fs::write(maker.path().join("file.txt"), "Text\n").unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.permalink("file.txt", None) {
+        Err(LocalRepoError::NoHeadCommit) => (),
+        r => panic!("permalink() did not fail; got: {r:?}"),
+    }
+    match lr.permalink("nonexistent.txt", None) {
+        Err(LocalRepoError::ReadError(p, _)) => assert_eq!(p, maker.path().join("nonexistent.txt")),
+        r => panic!("permalink() did not fail; got: {r:?}"),
+    }
+    let tmp_path = tempdir().unwrap();
+    let outside = tmp_path.path().join("outside.txt");
+    fs::write(&outside, "Text\n").unwrap();
+    match lr.permalink(&outside, None) {
+        Err(LocalRepoError::NotInWorkTree(p)) => assert_eq!(p, outside),
+        r => panic!("permalink() did not fail; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_set_remote_url_style() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository")
+        .unwrap();
+    maker
+        .add_push_url("origin", "https://github.com/me/repository.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let change = lr.remote_url_in_style("origin", &UrlStyle::Ssh).unwrap();
+    assert_eq!(change.old_url, "https://github.com/octocat/repository");
+    assert_eq!(change.new_url, "git@github.com:octocat/repository.git");
+    assert!(change.is_changed());
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository"]
+    );
+    assert_eq!(
+        lr.set_remote_url_style("origin", &UrlStyle::Ssh).unwrap(),
+        change
+    );
+    let remote = lr.remote("origin").unwrap();
+    assert_eq!(
+        remote.raw_fetch_urls,
+        ["git@github.com:octocat/repository.git"]
+    );
+    assert_eq!(
+        remote.raw_push_urls,
+        ["https://github.com/me/repository.git"]
+    );
+    let change = lr.set_remote_url_style("origin", &UrlStyle::Ssh).unwrap();
+    assert!(!change.is_changed());
+    lr.set_remote_url_style("origin", &UrlStyle::Ssh443)
+        .unwrap();
+    let change = lr.set_remote_url_style("origin", &UrlStyle::Https).unwrap();
+    assert_eq!(
+        change.old_url,
+        "ssh://git@ssh.github.com:443/octocat/repository.git"
+    );
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+}
+
+#[test]
+fn test_set_remote_url_style_errors() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "/srv/git/repository.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    match lr.set_remote_url_style("upstream", &UrlStyle::Ssh) {
+        Err(LocalRepoError::NoSuchRemote(remote)) => assert_eq!(remote, "upstream"),
+        r => panic!("set_remote_url_style() did not fail with NoSuchRemote; got: {r:?}"),
+    }
+    match lr.set_remote_url_style("origin", &UrlStyle::Ssh) {
+        Err(LocalRepoError::InvalidRemoteURL(_)) => (),
+        r => panic!("set_remote_url_style() did not fail with InvalidRemoteURL; got: {r:?}"),
+    }
+    for host in [
+        "",
+        "github.example.com/evil",
+        "git@github.example.com",
+        "a b",
+    ] {
+        match lr.set_remote_url_style("origin", &UrlStyle::EnterpriseSsh(host.to_string())) {
+            Err(LocalRepoError::InvalidHost(h)) => assert_eq!(h, host),
+            r => panic!("set_remote_url_style() did not fail with InvalidHost; got: {r:?}"),
+        }
+    }
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["/srv/git/repository.git"]
+    );
+}
+
+#[test]
+fn test_set_remote_url_style_enterprise_round_trip() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/octocat/repository.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let style = UrlStyle::EnterpriseSsh(String::from("github.example.com"));
+    let change = lr.set_remote_url_style("origin", &style).unwrap();
+    assert_eq!(
+        change.new_url,
+        "git@github.example.com:octocat/repository.git"
+    );
+    assert!(
+        !lr.set_remote_url_style("origin", &style)
+            .unwrap()
+            .is_changed()
+    );
+    let change = lr.set_remote_url_style("origin", &UrlStyle::Https).unwrap();
+    assert_eq!(
+        change.old_url,
+        "git@github.example.com:octocat/repository.git"
+    );
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+}
+
+#[test]
+fn test_add_github_remote() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    maker.init("trunk").unwrap();
+    maker
+        .add_remote("origin", "https://github.com/me/repository.git")
+        .unwrap();
+    maker
+        .add_remote("mirror", "https://git.example.com/octocat/repository.git")
+        .unwrap();
+    let lr = LocalRepo::new(maker.path());
+    let parent = GHRepo::new("octocat", "repository").unwrap();
+    assert!(
+        lr.add_github_remote("upstream", &parent, &UrlStyle::Https)
+            .unwrap()
+    );
+    assert_eq!(lr.github_remote("upstream").unwrap(), parent);
+    assert!(
+        !lr.add_github_remote(
+            "upstream",
+            &"OCTOCAT/Repository".parse().unwrap(),
+            &UrlStyle::Ssh
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        lr.remote("upstream").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+    match lr.add_github_remote("origin", &parent, &UrlStyle::Https) {
+        Err(LocalRepoError::RemoteExists(remote)) => assert_eq!(remote, "origin"),
+        r => panic!("add_github_remote() did not fail with RemoteExists; got: {r:?}"),
+    }
+    match lr.add_github_remote("mirror", &parent, &UrlStyle::Https) {
+        Err(LocalRepoError::RemoteExists(remote)) => assert_eq!(remote, "mirror"),
+        r => panic!("add_github_remote() did not fail with RemoteExists; got: {r:?}"),
+    }
+    assert!(lr.fetch_pull_requests("upstream").unwrap());
+    assert!(!lr.fetch_pull_requests("upstream").unwrap());
+    assert_eq!(
+        lr.backend()
+            .config_get_all(lr.path(), "remote.upstream.fetch")
+            .unwrap(),
+        [
+            "+refs/heads/*:refs/remotes/upstream/*",
+            "+refs/pull/*/head:refs/remotes/upstream/pr/*"
+        ]
+    );
+    match lr.fetch_pull_requests("nowhere") {
+        Err(LocalRepoError::NoSuchRemote(remote)) => assert_eq!(remote, "nowhere"),
+        r => panic!("fetch_pull_requests() did not fail with NoSuchRemote; got: {r:?}"),
+    }
+}
+
+/// Create a bare repository with two commits on `trunk` & one commit on
+/// `feature` and return a `CloneOptions` that redirects clones of
+/// `octocat/repository` (in any of the standard URL styles) to it
+fn clone_source(maker: &RepoMaker, mirror: &RepoMaker) -> std::io::Result<CloneOptions> {
+    let base = maker.init_clone_source(mirror)?;
+    Ok(CloneOptions::new()
+        .config(&base, "https://github.com/octocat/repository.git")
+        .config(&base, "git@github.com:octocat/repository.git")
+        .config(&base, "ssh://git@ssh.github.com:443/octocat/repository.git"))
+}
+
+#[test]
+fn test_clone_into() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let options = clone_source(&maker, &mirror).unwrap();
+    let tmpdir = tempdir().unwrap();
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let lr = repo
+        .clone_into(
+            tmpdir.path().join("repository"),
+            &options
+                .style(UrlStyle::Ssh)
+                .upstream(GHRepo::new("parent", "repository").unwrap()),
+        )
+        .unwrap();
+    assert_eq!(lr.path(), tmpdir.path().join("repository"));
+    assert_eq!(lr.current_branch().unwrap(), "trunk");
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["git@github.com:octocat/repository.git"]
+    );
+    assert_eq!(
+        lr.remote("upstream").unwrap().raw_fetch_urls,
+        ["git@github.com:parent/repository.git"]
+    );
+    assert!(tmpdir.path().join("repository").join("two.txt").exists());
+}
+
+#[test]
+fn test_clone_into_depth_and_branch() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let options = clone_source(&maker, &mirror).unwrap();
+    let tmpdir = tempdir().unwrap();
+    let dest = tmpdir.path().join("clone");
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let lr = repo
+        .clone_into(&dest, &options.depth(1).branch("feature"))
+        .unwrap();
+    assert_eq!(lr.current_branch().unwrap(), "feature");
+    assert!(dest.join(".git").join("shallow").exists());
+    assert!(dest.join("one.txt").exists());
+    assert!(!dest.join("two.txt").exists());
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+    assert!(lr.remote("upstream").is_err());
+}
+
+#[test]
+fn test_clone_into_protocol_config() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let options = clone_source(&maker, &mirror).unwrap();
+    let tmpdir = tempdir().unwrap();
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let lr = repo
+        .clone_into(
+            tmpdir.path().join("ssh"),
+            &options.clone().config("ghrepo.protocol", "ssh-443"),
+        )
+        .unwrap();
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["ssh://git@ssh.github.com:443/octocat/repository.git"]
+    );
+    match repo.clone_into(
+        tmpdir.path().join("bad"),
+        &options.config("ghrepo.protocol", "git"),
+    ) {
+        Err(LocalRepoError::InvalidProtocol(value)) => assert_eq!(value, "git"),
+        r => panic!("clone_into() did not fail with InvalidProtocol; got: {r:?}"),
+    }
+    assert!(!tmpdir.path().join("bad").exists());
+}
+
+#[test]
+fn test_clone_into_ignores_enclosing_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let options = clone_source(&maker, &mirror).unwrap();
+    let outer = RepoMaker::new().unwrap();
+    outer.init("main").unwrap();
+    outer.set_config("ghrepo.protocol", "git").unwrap();
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    let lr = repo
+        .clone_into(
+            outer.path().join("clone"),
+            &options.config("ghrepo.test", "yes"),
+        )
+        .unwrap();
+    assert_eq!(
+        lr.remote("origin").unwrap().raw_fetch_urls,
+        ["https://github.com/octocat/repository.git"]
+    );
+    assert_eq!(
+        lr.backend().config_get(lr.path(), "ghrepo.test").unwrap(),
+        Some(String::from("yes"))
+    );
+}
+
+#[test]
+fn test_clone_into_nonempty_dir() {
+    if which("git").is_err() {
+        return;
+    }
+    let maker = RepoMaker::new().unwrap();
+    let mirror = RepoMaker::new().unwrap();
+    let options = clone_source(&maker, &mirror).unwrap();
+    let tmpdir = tempdir().unwrap();
+    fs::write(tmpdir.path().join("file.txt"), "Hello\n").unwrap();
+    let repo = GHRepo::new("octocat", "repository").unwrap();
+    assert!(matches!(
+        repo.clone_into(tmpdir.path(), &options),
+        Err(LocalRepoError::CommandFailed(_))
+    ));
+}
@@ -0,0 +1,245 @@
+use ghrepo::{GHRepo, LocalRepoError, NativeGit, ScannedRepo, Scanner};
+use repomaker::RepoMaker;
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+use which::which;
+
+/// Create a directory tree containing a mixture of repositories, a bare
+/// repository, repositories nested inside other repositories, and repositories
+/// inside skipped directories
+fn make_tree(root: &Path) -> io::Result<()> {
+    for (path, branch) in [
+        ("alpha", "main"),
+        ("alpha/nested", "main"),
+        ("group/beta", "trunk"),
+        ("node_modules/dep", "main"),
+        ("target/build", "main"),
+        ("vendor/lib", "main"),
+    ] {
+        RepoMaker::at(root.join(path))?.init(branch)?;
+    }
+    RepoMaker::at(root.join("group").join("mirror.git"))?.init_bare("main")?;
+    fs::create_dir_all(root.join("plain").join("dir"))?;
+    fs::write(root.join("file.txt"), "This is not a repository.\n")?;
+    Ok(())
+}
+
+fn join_all(root: &Path, paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(|p| root.join(p)).collect()
+}
+
+#[test]
+fn test_scanner_find_repos() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let root = tmp_path.path();
+    make_tree(root).unwrap();
+    let found = Scanner::new(root).find_repos().unwrap();
+    assert_eq!(
+        found,
+        join_all(
+            root,
+            &["alpha", "group/beta", "group/mirror.git", "vendor/lib"]
+        )
+    );
+}
+
+#[test]
+fn test_scanner_skip_dirs() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let root = tmp_path.path();
+    make_tree(root).unwrap();
+    let found = Scanner::new(root).skip_dir("vendor").find_repos().unwrap();
+    assert_eq!(
+        found,
+        join_all(root, &["alpha", "group/beta", "group/mirror.git"])
+    );
+    let found = Scanner::new(root).no_skip_dirs().find_repos().unwrap();
+    assert_eq!(
+        found,
+        join_all(
+            root,
+            &[
+                "alpha",
+                "group/beta",
+                "group/mirror.git",
+                "node_modules/dep",
+                "target/build",
+                "vendor/lib"
+            ]
+        )
+    );
+}
+
+#[test]
+fn test_scanner_max_depth() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let root = tmp_path.path();
+    make_tree(root).unwrap();
+    let found = Scanner::new(root).max_depth(1).find_repos().unwrap();
+    assert_eq!(found, join_all(root, &["alpha"]));
+    let found = Scanner::new(root).max_depth(0).find_repos().unwrap();
+    assert!(
+        found.is_empty(),
+        "no repositories should be found at depth 0"
+    );
+}
+
+#[test]
+fn test_scanner_root_is_repo() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let root = tmp_path.path();
+    RepoMaker::at(root).unwrap().init("main").unwrap();
+    RepoMaker::at(root.join("sub"))
+        .unwrap()
+        .init("main")
+        .unwrap();
+    let found = Scanner::new(root).find_repos().unwrap();
+    assert_eq!(found, [root.to_path_buf()]);
+    let found = Scanner::new(root).max_depth(0).find_repos().unwrap();
+    assert_eq!(found, [root.to_path_buf()]);
+}
+
+#[test]
+fn test_scanner_nonexistent_root() {
+    let tmp_path = tempdir().unwrap();
+    let root = tmp_path.path().join("nowhere");
+    match Scanner::new(&root).find_repos() {
+        Err(LocalRepoError::ReadError(p, _)) => assert_eq!(p, root),
+        r => panic!("find_repos() did not fail with ReadError; got: {r:?}"),
+    }
+    match Scanner::new(&root).scan() {
+        Err(LocalRepoError::ReadError(p, _)) => assert_eq!(p, root),
+        r => panic!("scan() did not fail with ReadError; got: {r:?}"),
+    }
+}
+
+#[test]
+fn test_scanner_empty() {
+    let tmp_path = tempdir().unwrap();
+    let results = Scanner::new(tmp_path.path()).scan().unwrap();
+    assert!(results.is_empty(), "no repositories should be found");
+}
+
+/// The parts of a [`ScannedRepo`] checked by the tests: the path, the
+/// branch, the names & parsed repositories of the remotes, and whether any
+/// errors occurred
+type Summary = (PathBuf, Option<String>, Vec<(String, Option<GHRepo>)>, bool);
+
+fn summarize(results: &[ScannedRepo]) -> Vec<Summary> {
+    results
+        .iter()
+        .map(|r| {
+            (
+                r.path.clone(),
+                r.branch.clone(),
+                r.remotes
+                    .iter()
+                    .map(|rem| (rem.name.clone(), rem.repo.as_ref().ok().cloned()))
+                    .collect(),
+                !r.errors.is_empty(),
+            )
+        })
+        .collect()
+}
+
+/// Create a directory tree for testing `scan()`, returning the expected
+/// summaries of the results
+fn make_scan_tree(root: &Path) -> io::Result<Vec<Summary>> {
+    let bare = RepoMaker::at(root.join("bare.git"))?;
+    bare.init_bare("trunk")?;
+    bare.add_remote("origin", "https://github.com/octocat/repo.git")?;
+    let github = RepoMaker::at(root.join("github"))?;
+    github.init("main")?;
+    github.add_remote("origin", "https://github.com/octocat/repo.git")?;
+    github.add_remote("upstream", "git@github.com:upstream/repo.git")?;
+    let other = RepoMaker::at(root.join("other"))?;
+    other.init("trunk")?;
+    other.add_remote("origin", "https://example.com/repo.git")?;
+    let detached = RepoMaker::at(root.join("sub").join("detached"))?;
+    detached.init("main")?;
+    detached.detach()?;
+    let broken = root.join("sub").join("broken");
+    fs::create_dir_all(&broken)?;
+    fs::write(broken.join(".git"), "This is not a gitdir.\n")?;
+    let octocat = GHRepo::new("octocat", "repo").ok();
+    let upstream = GHRepo::new("upstream", "repo").ok();
+    Ok(vec![
+        (
+            bare.path().to_path_buf(),
+            Some("trunk".into()),
+            vec![("origin".into(), octocat.clone())],
+            false,
+        ),
+        (
+            github.path().to_path_buf(),
+            Some("main".into()),
+            vec![("origin".into(), octocat), ("upstream".into(), upstream)],
+            false,
+        ),
+        (
+            other.path().to_path_buf(),
+            Some("trunk".into()),
+            vec![("origin".into(), None)],
+            false,
+        ),
+        (broken, None, Vec::new(), true),
+        (detached.path().to_path_buf(), None, Vec::new(), false),
+    ])
+}
+
+#[test]
+fn test_scanner_scan() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let expected = make_scan_tree(tmp_path.path()).unwrap();
+    let results = Scanner::new(tmp_path.path()).scan().unwrap();
+    assert_eq!(summarize(&results), expected);
+}
+
+#[test]
+fn test_scanner_scan_native() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let expected = make_scan_tree(tmp_path.path()).unwrap();
+    let results = Scanner::with_backend(tmp_path.path(), NativeGit)
+        .scan()
+        .unwrap();
+    assert_eq!(summarize(&results), expected);
+}
+
+#[test]
+fn test_scanner_scan_each() {
+    if which("git").is_err() {
+        return;
+    }
+    let tmp_path = tempdir().unwrap();
+    let expected = make_scan_tree(tmp_path.path()).unwrap();
+    for jobs in [1, 3, 16] {
+        let mut results = Vec::new();
+        Scanner::new(tmp_path.path())
+            .jobs(NonZeroUsize::new(jobs).unwrap())
+            .scan_each(|scanned| results.push(scanned))
+            .unwrap();
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(summarize(&results), expected);
+    }
+}
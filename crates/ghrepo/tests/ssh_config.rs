@@ -0,0 +1,133 @@
+use ghrepo::{FakeGit, GHRepo, LocalRepo, LocalRepoError, ParseError, SshConfig};
+use rstest::rstest;
+use std::fs;
+use tempfile::tempdir;
+
+const CONFIG: &str = concat!(
+    "Host github-work github-home\n",
+    "    HostName github.com\n",
+    "    User git\n",
+    "\n",
+    "Host gh443\n",
+    "    HostName ssh.github.com\n",
+    "    Port 443\n",
+    "\n",
+    "Host gh-nouser\n",
+    "    HostName GitHub.com\n",
+    "\n",
+    "Host gh-wronguser\n",
+    "    HostName github.com\n",
+    "    User octocat\n",
+    "\n",
+    "Host ghe\n",
+    "    HostName ghe.example.com\n",
+    "    User git\n",
+);
+
+#[rstest]
+#[case("git@github-work:octocat/repository.git", Some("github-work"))]
+#[case("github-work:octocat/repository.git", Some("github-work"))]
+#[case("git@github-home:octocat/repository", Some("github-home"))]
+#[case("ssh://github-work/octocat/repository.git", Some("github-work"))]
+#[case("ssh://git@gh443:443/octocat/repository.git", Some("gh443"))]
+#[case("git@gh-nouser:octocat/repository.git", Some("gh-nouser"))]
+#[case("git@gh-wronguser:octocat/repository.git", Some("gh-wronguser"))]
+#[case("git@github.com:octocat/repository.git", None)]
+#[case("https://github.com/octocat/repository", None)]
+fn test_from_url_with_ssh_config(#[case] url: &str, #[case] alias: Option<&str>) {
+    let config = CONFIG.parse::<SshConfig>().unwrap();
+    let resolved = GHRepo::from_url_with_ssh_config(url, &config).unwrap();
+    assert_eq!(resolved.repo, GHRepo::new("octocat", "repository").unwrap());
+    assert_eq!(resolved.ssh_alias.as_deref(), alias);
+}
+
+#[rstest]
+#[case("gh-nouser:octocat/repository.git")]
+#[case("gh-wronguser:octocat/repository.git")]
+#[case("octocat@github-work:octocat/repository.git")]
+#[case("git@ghe:octocat/repository.git")]
+#[case("git@unknown:octocat/repository.git")]
+#[case("git@github-work:octocat")]
+#[case("git@github-work:octocat/repository/extra")]
+#[case("https://github-work/octocat/repository")]
+fn test_from_url_with_ssh_config_bad(#[case] url: &str) {
+    let config = CONFIG.parse::<SshConfig>().unwrap();
+    assert_eq!(
+        GHRepo::from_url_with_ssh_config(url, &config),
+        Err(ParseError::InvalidSpec(url.to_string()))
+    );
+}
+
+#[test]
+fn test_ssh_config_include() {
+    let tmp_path = tempdir().unwrap();
+    let confd = tmp_path.path().join("conf.d");
+    fs::create_dir(&confd).unwrap();
+    fs::write(
+        confd.join("work.conf"),
+        "Host github-work\n    HostName github.com\n",
+    )
+    .unwrap();
+    fs::write(
+        confd.join("home.conf"),
+        "Host github-home\n    HostName github.com\n",
+    )
+    .unwrap();
+    fs::write(
+        confd.join("unused.txt"),
+        "Host github-other\n    HostName github.com\n",
+    )
+    .unwrap();
+    let main = tmp_path.path().join("config");
+    fs::write(
+        &main,
+        format!(
+            "Include {}/*.conf\nHost *\n    HostName example.com\n",
+            confd.display()
+        ),
+    )
+    .unwrap();
+    let config = SshConfig::from_file(&main).unwrap();
+    assert_eq!(config.hostname("github-work"), "github.com");
+    assert_eq!(config.hostname("github-home"), "github.com");
+    assert_eq!(config.hostname("github-other"), "example.com");
+}
+
+#[test]
+fn test_ssh_config_from_file_missing() {
+    let tmp_path = tempdir().unwrap();
+    let path = tmp_path.path().join("nonexistent");
+    let e = SshConfig::from_file(&path).unwrap_err();
+    assert_eq!(e.path(), path);
+    assert!(e.to_string().starts_with(&format!(
+        "failed to read SSH config file {}: ",
+        path.display()
+    )));
+}
+
+#[test]
+fn test_local_repo_with_ssh_config() {
+    let fake = FakeGit::new()
+        .remote("origin", "git@github-work:octocat/repository.git")
+        .remote("upstream", "https://github.com/sourcedog/repository.git");
+    let lr = LocalRepo::with_backend("/path/to/repo", fake.clone());
+    match lr.github_remote("origin") {
+        Err(LocalRepoError::InvalidRemoteURL(ParseError::InvalidSpec(url))) => {
+            assert_eq!(url, "git@github-work:octocat/repository.git");
+        }
+        r => panic!("github_remote() did not fail; got: {r:?}"),
+    }
+    let lr = LocalRepo::with_backend("/path/to/repo", fake)
+        .with_ssh_config(CONFIG.parse::<SshConfig>().unwrap());
+    assert_eq!(
+        lr.github_remote("origin").unwrap(),
+        GHRepo::new("octocat", "repository").unwrap()
+    );
+    let remotes = lr.remotes().unwrap();
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].ssh_alias.as_deref(), Some("github-work"));
+    assert_eq!(remotes[0].repo, GHRepo::new("octocat", "repository"));
+    assert_eq!(remotes[1].name, "upstream");
+    assert_eq!(remotes[1].ssh_alias, None);
+    assert_eq!(remotes[1].repo, GHRepo::new("sourcedog", "repository"));
+}
@@ -1,22 +1,36 @@
 use std::ffi::OsStr;
 use std::fs;
 use std::io::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::{TempDir, tempdir};
 
 #[derive(Debug)]
 pub struct RepoMaker {
-    tmpdir: TempDir,
+    path: PathBuf,
+    _tmpdir: Option<TempDir>,
 }
 
 impl RepoMaker {
     pub fn new() -> Result<Self> {
-        Ok(RepoMaker { tmpdir: tempdir()? })
+        let tmpdir = tempdir()?;
+        Ok(RepoMaker {
+            path: tmpdir.path().to_path_buf(),
+            _tmpdir: Some(tmpdir),
+        })
+    }
+
+    pub fn at<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(RepoMaker {
+            path,
+            _tmpdir: None,
+        })
     }
 
     pub fn path(&self) -> &Path {
-        self.tmpdir.path()
+        &self.path
     }
 
     fn run<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(&self, args: I) -> Result<()> {
@@ -32,6 +46,15 @@ impl RepoMaker {
         self.run(["-c", &format!("init.defaultBranch={branch}"), "init"])
     }
 
+    pub fn init_bare(&self, branch: &str) -> Result<()> {
+        self.run([
+            "-c",
+            &format!("init.defaultBranch={branch}"),
+            "init",
+            "--bare",
+        ])
+    }
+
     pub fn add_remote<S: AsRef<OsStr>>(&self, remote: &str, url: S) -> Result<()> {
         self.run([
             "remote".as_ref(),
@@ -41,10 +64,105 @@ impl RepoMaker {
         ])
     }
 
+    pub fn add_push_url<S: AsRef<OsStr>>(&self, remote: &str, url: S) -> Result<()> {
+        self.run([
+            "remote".as_ref(),
+            "set-url".as_ref(),
+            "--add".as_ref(),
+            "--push".as_ref(),
+            remote.as_ref(),
+            url.as_ref(),
+        ])
+    }
+
     pub fn set_upstream(&self, branch: &str, remote: &str) -> Result<()> {
         self.run(["config", &format!("branch.{branch}.remote"), remote])
     }
 
+    pub fn set_remote_head(&self, remote: &str, branch: &str) -> Result<()> {
+        self.run([
+            "symbolic-ref",
+            &format!("refs/remotes/{remote}/HEAD"),
+            &format!("refs/remotes/{remote}/{branch}"),
+        ])
+    }
+
+    pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
+        self.run(["config", key, value])
+    }
+
+    pub fn add_worktree(&self, path: &Path, branch: &str) -> Result<()> {
+        self.run([
+            "worktree".as_ref(),
+            "add".as_ref(),
+            "-b".as_ref(),
+            branch.as_ref(),
+            path.as_os_str(),
+        ])
+    }
+
+    pub fn add_detached_worktree(&self, path: &Path) -> Result<()> {
+        self.run([
+            "worktree".as_ref(),
+            "add".as_ref(),
+            "--detach".as_ref(),
+            path.as_os_str(),
+        ])
+    }
+
+    pub fn lock_worktree(&self, path: &Path) -> Result<()> {
+        self.run(["worktree".as_ref(), "lock".as_ref(), path.as_os_str()])
+    }
+
+    pub fn commit_file(&self, name: &str, contents: &str) -> Result<()> {
+        fs::write(self.path().join(name), contents)?;
+        self.run(["add", name])?;
+        self.run(["commit", "-m", &format!("Add {name}")])
+    }
+
+    pub fn tag(&self, name: &str) -> Result<()> {
+        self.run(["tag", name])
+    }
+
+    pub fn annotated_tag(&self, name: &str, message: &str) -> Result<()> {
+        self.run(["tag", "-a", "-m", message, name])
+    }
+
+    pub fn clone_mirror(&self, url: &Path) -> Result<()> {
+        self.run([
+            "clone".as_ref(),
+            "--quiet".as_ref(),
+            "--mirror".as_ref(),
+            url.as_os_str(),
+            ".".as_ref(),
+        ])
+    }
+
+    // Initialize a repository with two commits on `trunk` to stand in for a
+    // GitHub repository, mirror it into `mirror` with an additional `feature`
+    // branch at the first commit, and return the `url.<base>.insteadOf`
+    // config key for redirecting GitHub URLs to the mirror
+    pub fn init_clone_source(&self, mirror: &RepoMaker) -> Result<String> {
+        self.init("trunk")?;
+        self.commit_file("one.txt", "One\n")?;
+        self.commit_file("two.txt", "Two\n")?;
+        mirror.clone_mirror(self.path())?;
+        mirror.run(["branch", "feature", "trunk~1"])?;
+        Ok(format!("url.file://{}.insteadOf", mirror.path().display()))
+    }
+
+    pub fn add_submodule(&self, url: &Path, path: &str) -> Result<()> {
+        self.run([
+            "-c".as_ref(),
+            "protocol.file.allow=always".as_ref(),
+            "submodule".as_ref(),
+            "--quiet".as_ref(),
+            "add".as_ref(),
+            url.as_os_str(),
+            path.as_ref(),
+        ])
+    }
+
     pub fn detach(&self) -> Result<()> {
         fs::write(self.path().join("file.txt"), b"This is test text\n")?;
         self.run(["add", "file.txt"])?;